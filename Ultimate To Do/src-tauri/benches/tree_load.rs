@@ -0,0 +1,89 @@
+//! Times loading the task list for a database of 11,250 tasks nested
+//! four levels deep. Run with `cargo bench --bench tree_load`.
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+use ultimate_to_do_lib::bench::{initialize_database, load_tasks, Task, TaskQuery};
+
+const ROOTS: i64 = 750;
+// Every task above the deepest level gets this many subtasks
+const FAN_OUT: i64 = 2;
+const DEPTH: u32 = 4;
+const RUNS: u32 = 20;
+
+fn main() {
+    let conn = initialize_database(Path::new(":memory:")).expect("open database");
+    let total = seed(&conn);
+
+    let query = || TaskQuery {
+        include_completed: Some(true),
+        ..TaskQuery::default()
+    };
+
+    // The first load warms SQLite's page cache and checks the tree is whole
+    let roots = load_tasks(&conn, query()).expect("load tasks");
+    assert_eq!(roots.len() as i64, ROOTS);
+    assert_eq!(roots.iter().map(count).sum::<i64>(), total);
+
+    let mut times: Vec<Duration> = (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            load_tasks(&conn, query()).expect("load tasks");
+            start.elapsed()
+        })
+        .collect();
+    times.sort();
+
+    println!("tree_load: {} tasks, {} runs", total, RUNS);
+    println!("  min    {:?}", times[0]);
+    println!("  median {:?}", times[times.len() / 2]);
+    println!("  max    {:?}", times[times.len() - 1]);
+}
+
+// Inserts the task trees and a tag on every tenth task, returning the task count
+fn seed(conn: &rusqlite::Connection) -> i64 {
+    conn.execute_batch("BEGIN; INSERT INTO tags (name, color) VALUES ('bench', '#6b7280');")
+        .expect("insert tag");
+
+    let mut insert = conn
+        .prepare(
+            "INSERT INTO tasks (title, parent_id, position, is_completed, estimated_minutes)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+        )
+        .expect("prepare insert");
+    let mut total = 0;
+    let mut level: Vec<Option<i64>> = vec![None; ROOTS as usize];
+
+    for depth in 0..DEPTH {
+        let mut next = Vec::new();
+        for (i, parent_id) in level.iter().enumerate() {
+            insert
+                .execute(rusqlite::params![
+                    format!("Task {} at depth {}", i, depth),
+                    parent_id,
+                    format!("{:08}V", i),
+                    total % 3 == 0,
+                    (total % 4 != 0).then_some(15 + total % 60),
+                ])
+                .expect("insert task");
+            total += 1;
+            let id = conn.last_insert_rowid();
+            if total % 10 == 0 {
+                conn.execute(
+                    "INSERT INTO task_tags (task_id, tag_id) SELECT ?1, id FROM tags",
+                    [id],
+                )
+                .expect("tag task");
+            }
+            next.extend((0..FAN_OUT).map(|_| Some(id)));
+        }
+        level = next;
+    }
+
+    conn.execute_batch("COMMIT").expect("commit");
+    total
+}
+
+fn count(task: &Task) -> i64 {
+    1 + task.subtasks.iter().map(count).sum::<i64>()
+}
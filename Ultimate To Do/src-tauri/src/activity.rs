@@ -0,0 +1,363 @@
+//! The activity log: what happened to each task, and to the projects, tags,
+//! views and settings around them.
+//!
+//! Journaled commands don't write events themselves. Once a command's journal
+//! entry is closed, `record_changes` turns the rows it changed into events, so
+//! a command can't forget one and a completion that spreads to a parent shows
+//! up on the parent too. Undo and redo are logged the same way, from the
+//! changes they write. The Pomodoro timer, which isn't journaled, logs its
+//! sessions with `record_session`.
+
+use crate::journal::Change;
+use rusqlite::{Connection, OptionalExtension};
+use serde_json::{Map, Value as Json};
+use std::collections::HashMap;
+
+/// Command name for sessions the timer completes on its own.
+pub const TIMER_COMMAND: &str = "Pomodoro timer";
+
+type Row = Map<String, Json>;
+
+/// A line of the log, before it is written.
+pub struct Event {
+    pub event_type: &'static str,
+    pub task_id: Option<i64>,
+    /// The task's title, or the name of what changed
+    pub subject: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+
+// Task columns logged as an edit with their old and new value. Completion is
+// logged from `task_completions`, and `position` changes with every move.
+const TASK_FIELDS: &[(&str, &str)] = &[
+    ("title", "retitled"),
+    ("description", "description_changed"),
+    ("due_date", "rescheduled"),
+    ("priority", "priority_changed"),
+    ("estimated_minutes", "estimate_changed"),
+    ("difficulty", "difficulty_changed"),
+];
+
+// Project columns whose changes are logged without their values
+const PROJECT_SETTINGS: &[&str] = &["color", "default_priority", "default_estimated_minutes", "sort_by"];
+
+pub fn record(conn: &Connection, command: &str, event: &Event) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO activity_log (command, event_type, task_id, subject, old_value, new_value)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        (
+            command,
+            event.event_type,
+            event.task_id,
+            &event.subject,
+            &event.old_value,
+            &event.new_value,
+        ),
+    )?;
+    Ok(())
+}
+
+/// Logs the rows `command` changed, in the order it changed them.
+pub fn record_changes(conn: &Connection, command: &str, changes: &[Change]) -> rusqlite::Result<()> {
+    let mut names = Names::new(conn, changes);
+    for change in changes {
+        for event in events(change, &mut names)? {
+            record(conn, command, &event)?;
+        }
+    }
+    Ok(())
+}
+
+/// Logs a Pomodoro session starting, ending early or being completed
+/// (`event_type` `focus_started`, `focus_aborted` or `focus_completed`), under
+/// the task it was for, if any, with its length in minutes.
+pub fn record_session(
+    conn: &Connection,
+    command: &str,
+    event_type: &'static str,
+    session_id: i64,
+) -> rusqlite::Result<()> {
+    let (task_id, title, minutes): (Option<i64>, Option<String>, i64) = conn.query_row(
+        "SELECT ps.task_id, t.title, ps.duration_minutes
+         FROM pomodoro_sessions ps
+         LEFT JOIN tasks t ON t.id = ps.task_id
+         WHERE ps.id = ?1",
+        [session_id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    )?;
+
+    record(
+        conn,
+        command,
+        &Event {
+            event_type,
+            task_id,
+            subject: title.unwrap_or_else(|| "Focus session".to_string()),
+            old_value: None,
+            new_value: Some(minutes.to_string()),
+        },
+    )
+}
+
+fn events(change: &Change, names: &mut Names) -> rusqlite::Result<Vec<Event>> {
+    let old = change.old_row.as_ref();
+    let new = change.new_row.as_ref();
+    let Some(row) = new.or(old) else {
+        return Ok(Vec::new());
+    };
+
+    let mut events = Vec::new();
+    match change.table.as_str() {
+        "tasks" => {
+            let subject = names.task(change.row_id)?;
+            let mut push = |event_type, old_value, new_value| {
+                events.push(Event {
+                    event_type,
+                    task_id: Some(change.row_id),
+                    subject: subject.clone(),
+                    old_value,
+                    new_value,
+                })
+            };
+
+            match (old, new) {
+                (None, Some(new)) => push("created", None, text(new, "title")),
+                (Some(old), None) => push("purged", text(old, "title"), None),
+                (Some(old), Some(new)) => {
+                    for (column, event_type) in TASK_FIELDS {
+                        if changed(old, new, column) {
+                            push(event_type, text(old, column), text(new, column));
+                        }
+                    }
+                    if changed(old, new, "is_recurring") || changed(old, new, "recurrence_pattern") {
+                        push("recurrence_changed", recurrence(old), recurrence(new));
+                    }
+                    if changed(old, new, "parent_id") {
+                        let from = int(old, "parent_id").map(|id| names.task(id)).transpose()?;
+                        let to = int(new, "parent_id").map(|id| names.task(id)).transpose()?;
+                        push("moved", from, to);
+                    }
+                    if changed(old, new, "project_id") {
+                        let from = int(old, "project_id").map(|id| names.project(id)).transpose()?;
+                        let to = int(new, "project_id").map(|id| names.project(id)).transpose()?;
+                        push("project_changed", from, to);
+                    }
+                    match (text(old, "deleted_at"), text(new, "deleted_at")) {
+                        (None, Some(_)) => push("trashed", None, None),
+                        (Some(_), None) => push("restored", None, None),
+                        _ => {}
+                    }
+                }
+                (None, None) => {}
+            }
+        }
+        "task_assignments" | "task_completions" | "task_tags" => {
+            let Some(task_id) = int(row, "task_id") else {
+                return Ok(events);
+            };
+            let mut value = |row: &Row| -> rusqlite::Result<Option<String>> {
+                match change.table.as_str() {
+                    "task_tags" => int(row, "tag_id").map(|id| names.tag(id)).transpose(),
+                    _ => Ok(text(row, "date")),
+                }
+            };
+            let (added, removed, moved) = match change.table.as_str() {
+                "task_assignments" => ("assigned", "unassigned", Some("reassigned")),
+                "task_completions" => ("completed", "uncompleted", None),
+                _ => ("tagged", "untagged", None),
+            };
+
+            let event = match (old, new) {
+                (None, Some(new)) => Some((added, None, value(new)?)),
+                (Some(old), None) => Some((removed, value(old)?, None)),
+                (Some(old), Some(new)) => match moved {
+                    Some(moved) if changed(old, new, "date") => Some((moved, value(old)?, value(new)?)),
+                    _ => None,
+                },
+                (None, None) => None,
+            };
+            if let Some((event_type, old_value, new_value)) = event {
+                events.push(Event {
+                    event_type,
+                    task_id: Some(task_id),
+                    subject: names.task(task_id)?,
+                    old_value,
+                    new_value,
+                });
+            }
+        }
+        "projects" => {
+            let subject = names.project(change.row_id)?;
+            named_events(
+                &mut events,
+                subject,
+                old,
+                new,
+                ("project_created", "project_deleted", "project_renamed"),
+            );
+            if let (Some(old), Some(new)) = (old, new) {
+                if changed(old, new, "is_archived") {
+                    let event_type = if truthy(new, "is_archived") { "project_archived" } else { "project_unarchived" };
+                    events.push(unattached(event_type, names.project(change.row_id)?, None, None));
+                }
+                if PROJECT_SETTINGS.iter().any(|column| changed(old, new, column)) {
+                    events.push(unattached("project_edited", names.project(change.row_id)?, None, None));
+                }
+            }
+        }
+        "tags" => {
+            let subject = names.tag(change.row_id)?;
+            named_events(&mut events, subject.clone(), old, new, ("tag_created", "tag_deleted", "tag_renamed"));
+            if let (Some(old), Some(new)) = (old, new) {
+                if changed(old, new, "color") {
+                    events.push(unattached("tag_recolored", subject, text(old, "color"), text(new, "color")));
+                }
+            }
+        }
+        "saved_views" => {
+            let subject = text(row, "name").unwrap_or_default();
+            named_events(&mut events, subject.clone(), old, new, ("view_saved", "view_deleted", "view_renamed"));
+            if let (Some(old), Some(new)) = (old, new) {
+                if changed(old, new, "filter") || changed(old, new, "sort_by") {
+                    events.push(unattached("view_edited", subject, text(old, "filter"), text(new, "filter")));
+                }
+            }
+        }
+        "app_settings" => {
+            events.push(unattached(
+                "setting_changed",
+                text(row, "key").unwrap_or_default(),
+                old.and_then(|old| text(old, "value")),
+                new.and_then(|new| text(new, "value")),
+            ));
+        }
+        // Assignments in the trash come and go with their task
+        _ => {}
+    }
+    Ok(events)
+}
+
+// Creation, deletion and renaming of something identified by its `name`
+fn named_events(
+    events: &mut Vec<Event>,
+    subject: String,
+    old: Option<&Row>,
+    new: Option<&Row>,
+    (created, deleted, renamed): (&'static str, &'static str, &'static str),
+) {
+    match (old, new) {
+        (None, Some(new)) => events.push(unattached(created, subject, None, text(new, "name"))),
+        (Some(old), None) => events.push(unattached(deleted, subject, text(old, "name"), None)),
+        (Some(old), Some(new)) if changed(old, new, "name") => {
+            events.push(unattached(renamed, subject, text(old, "name"), text(new, "name")))
+        }
+        _ => {}
+    }
+}
+
+fn unattached(
+    event_type: &'static str,
+    subject: String,
+    old_value: Option<String>,
+    new_value: Option<String>,
+) -> Event {
+    Event {
+        event_type,
+        task_id: None,
+        subject,
+        old_value,
+        new_value,
+    }
+}
+
+fn changed(old: &Row, new: &Row, column: &str) -> bool {
+    old.get(column) != new.get(column)
+}
+
+fn text(row: &Row, column: &str) -> Option<String> {
+    match row.get(column)? {
+        Json::Null => None,
+        Json::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+fn int(row: &Row, column: &str) -> Option<i64> {
+    row.get(column).and_then(Json::as_i64)
+}
+
+fn truthy(row: &Row, column: &str) -> bool {
+    int(row, column).is_some_and(|value| value != 0)
+}
+
+// The pattern of a recurring task, or nothing for a one-off
+fn recurrence(row: &Row) -> Option<String> {
+    text(row, "recurrence_pattern").filter(|_| truthy(row, "is_recurring"))
+}
+
+// Titles and names for events, as they are after the command: taken from the
+// changed rows themselves where possible, as a deleted tag or purged task is
+// no longer in its table, and looked up otherwise.
+struct Names<'c> {
+    conn: &'c Connection,
+    tasks: HashMap<i64, String>,
+    tags: HashMap<i64, String>,
+    projects: HashMap<i64, String>,
+}
+
+impl<'c> Names<'c> {
+    fn new(conn: &'c Connection, changes: &[Change]) -> Self {
+        let mut names = Names {
+            conn,
+            tasks: HashMap::new(),
+            tags: HashMap::new(),
+            projects: HashMap::new(),
+        };
+        for change in changes {
+            let (map, column) = match change.table.as_str() {
+                "tasks" => (&mut names.tasks, "title"),
+                "tags" => (&mut names.tags, "name"),
+                "projects" => (&mut names.projects, "name"),
+                _ => continue,
+            };
+            // The newest name wins
+            for row in [&change.old_row, &change.new_row].into_iter().flatten() {
+                if let Some(name) = text(row, column) {
+                    map.insert(change.row_id, name);
+                }
+            }
+        }
+        names
+    }
+
+    fn task(&mut self, id: i64) -> rusqlite::Result<String> {
+        lookup(self.conn, &mut self.tasks, "SELECT title FROM tasks WHERE id = ?1", id, "Task")
+    }
+
+    fn tag(&mut self, id: i64) -> rusqlite::Result<String> {
+        lookup(self.conn, &mut self.tags, "SELECT name FROM tags WHERE id = ?1", id, "Tag")
+    }
+
+    fn project(&mut self, id: i64) -> rusqlite::Result<String> {
+        lookup(self.conn, &mut self.projects, "SELECT name FROM projects WHERE id = ?1", id, "Project")
+    }
+}
+
+fn lookup(
+    conn: &Connection,
+    cache: &mut HashMap<i64, String>,
+    sql: &str,
+    id: i64,
+    kind: &str,
+) -> rusqlite::Result<String> {
+    if let Some(name) = cache.get(&id) {
+        return Ok(name.clone());
+    }
+    let name = conn
+        .query_row(sql, [id], |row| row.get::<_, String>(0))
+        .optional()?
+        .unwrap_or_else(|| format!("{} {}", kind, id));
+    cache.insert(id, name.clone());
+    Ok(name)
+}
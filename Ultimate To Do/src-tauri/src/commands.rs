@@ -1,10 +1,31 @@
-use crate::database::{DbState, Task, DayTracker, PomodoroSession, UserStats};
-use rusqlite::Result;
+use crate::activity;
+use crate::config::{self, DatabaseLocation};
+use crate::database::{self, ActivityEntry, DbState, Project, ProjectField, Task, TaskQuery, TaskSort, DayTracker, CompletionResult, CompletionRules, DeleteTaskResult, PomodoroSession, ReparentResult, RescheduleResult, RestoreTaskResult, SavedView, Tag, TagPomodoroStats, TaskCompletion, TaskPathEntry, TaskSearchResult, TrashedTask, UndoResult, UndoState, UserStats};
+use crate::error::{AppError, AppResult};
+use crate::filters::{self, Filter};
+use crate::journal::{self, Direction};
+use crate::planner::{self, PinnedAssignment, Plan, PlannerTask};
+use crate::pomodoro::{self, PomodoroTimer, TimerSnapshot};
+use crate::ordering;
+use crate::progress::{LeafTally, SubtreeProgress, TaskProgress};
+use crate::recurrence::{self, RecurrenceRule};
+use crate::search;
+use crate::streaks;
+use chrono::{Duration, Local, NaiveDate};
+use rusqlite::OptionalExtension;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use tauri::State;
 
 // Task Management Commands
 
+const MAX_PRIORITY: i32 = 3;
+const MAX_DIFFICULTY: i32 = 5;
+// One week of work
+const MAX_ESTIMATED_MINUTES: i32 = 7 * 24 * 60;
+
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub fn create_task(
     state: State<DbState>,
     title: String,
@@ -13,104 +34,654 @@ pub fn create_task(
     is_recurring: bool,
     recurrence_pattern: Option<String>,
     parent_id: Option<i64>,
-) -> Result<i64, String> {
-    let conn = state.conn.lock().unwrap();
-    
+    priority: Option<i32>,
+    estimated_minutes: Option<i32>,
+    difficulty: Option<i32>,
+    project_id: Option<i64>,
+) -> AppResult<i64> {
+    validate_title(&title)?;
+    if is_recurring {
+        validate_recurrence(recurrence_pattern.as_deref())?;
+    }
+    validate_effort(priority, estimated_minutes, difficulty)?;
+
+    journaled(&state, "Create task", |conn| {
+        insert_task(
+            conn,
+            &title,
+            description.as_deref(),
+            due_date.as_deref(),
+            is_recurring,
+            recurrence_pattern.as_deref(),
+            parent_id,
+            priority,
+            estimated_minutes,
+            difficulty,
+            project_id,
+        )
+    })
+}
+
+// `create_task` without the app state
+#[allow(clippy::too_many_arguments)]
+fn insert_task(
+    conn: &rusqlite::Connection,
+    title: &str,
+    description: Option<&str>,
+    due_date: Option<&str>,
+    is_recurring: bool,
+    recurrence_pattern: Option<&str>,
+    parent_id: Option<i64>,
+    priority: Option<i32>,
+    estimated_minutes: Option<i32>,
+    difficulty: Option<i32>,
+    project_id: Option<i64>,
+) -> AppResult<i64> {
+    // Subtasks always live in their parent's project
+    let project_id = match parent_id {
+        Some(parent_id) => {
+            ensure_task_exists(conn, parent_id)?;
+            conn.query_row("SELECT project_id FROM tasks WHERE id = ?1", [parent_id], |row| row.get(0))?
+        }
+        None => project_id,
+    };
+
+    let defaults = match project_id {
+        Some(project_id) => Some(load_project(conn, project_id)?),
+        None => None,
+    };
+    let priority = priority
+        .or(defaults.as_ref().map(|p| p.default_priority))
+        .unwrap_or(0);
+    let estimated_minutes = estimated_minutes.or(defaults.as_ref().and_then(|p| p.default_estimated_minutes));
+
+    // New top-level tasks go first, new subtasks after their siblings
+    let placement = if parent_id.is_some() { Placement::Last } else { Placement::First };
+    let position = sibling_position(conn, parent_id, None, placement)?;
+
     conn.execute(
-        "INSERT INTO tasks (title, description, due_date, is_recurring, recurrence_pattern, parent_id)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        "INSERT INTO tasks (title, description, due_date, is_recurring, recurrence_pattern, parent_id,
+                            priority, estimated_minutes, difficulty, project_id, position, recurrence_anchor)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, CASE WHEN ?4 THEN ?3 END)",
         (
-            &title,
-            &description,
-            &due_date,
-            &is_recurring,
-            &recurrence_pattern,
-            &parent_id,
+            title,
+            description,
+            due_date,
+            is_recurring,
+            recurrence_pattern,
+            parent_id,
+            priority,
+            estimated_minutes,
+            difficulty,
+            project_id,
+            position,
         ),
+    )?;
+
+    let id = conn.last_insert_rowid();
+
+    // A new subtask is open, which may reopen a completed parent
+    if let Some(parent_id) = parent_id {
+        let rules = load_completion_rules(conn)?;
+        let mut changes = CompletionChanges::default();
+        sync_ancestor_completion(conn, parent_id, Local::now().date_naive(), &rules, &mut changes)?;
+        changes.finish(conn)?;
+    }
+
+    Ok(id)
+}
+
+/// Top-level tasks with their subtrees. `query` filters and sorts the top
+/// level; without it, tasks come in their manual order.
+#[tauri::command]
+pub fn get_all_tasks(state: State<DbState>, query: Option<TaskQuery>) -> AppResult<Vec<Task>> {
+    let conn = state.conn.lock()?;
+    load_tasks(&conn, query.unwrap_or_default())
+}
+
+/// `get_all_tasks` without the app state, so the benchmarks can run it.
+pub fn load_tasks(conn: &rusqlite::Connection, mut query: TaskQuery) -> AppResult<Vec<Task>> {
+    validate_task_query(&query)?;
+
+    // A project's own sort order applies unless the query picks one
+    if let (None, Some(project_id)) = (query.sort_by, query.project_id) {
+        query.sort_by = project_sort(conn, project_id)?;
+    }
+
+    let mut sql = format!("SELECT {} FROM tasks WHERE parent_id IS NULL AND deleted_at IS NULL", TASK_COLUMNS);
+    let mut param_values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+
+    if let Some(min_priority) = query.min_priority {
+        sql.push_str(" AND priority >= ?");
+        param_values.push(Box::new(min_priority));
+    }
+    if let Some(min_difficulty) = query.min_difficulty {
+        sql.push_str(" AND difficulty >= ?");
+        param_values.push(Box::new(min_difficulty));
+    }
+    if let Some(max_difficulty) = query.max_difficulty {
+        sql.push_str(" AND difficulty <= ?");
+        param_values.push(Box::new(max_difficulty));
+    }
+    if let Some(max_minutes) = query.max_estimated_minutes {
+        sql.push_str(" AND estimated_minutes <= ?");
+        param_values.push(Box::new(max_minutes));
+    }
+    if !query.include_completed.unwrap_or(true) {
+        sql.push_str(" AND is_completed = 0");
+    }
+    if let Some(project_id) = query.project_id {
+        sql.push_str(" AND project_id = ?");
+        param_values.push(Box::new(project_id));
+    } else if !query.include_archived.unwrap_or(false) {
+        sql.push_str(" AND (project_id IS NULL OR project_id NOT IN (SELECT id FROM projects WHERE is_archived = 1))");
+    }
+    if let Some(tag_ids) = query.tag_ids.as_ref().filter(|ids| !ids.is_empty()) {
+        let placeholders = vec!["?"; tag_ids.len()].join(", ");
+        if query.match_all_tags.unwrap_or(false) {
+            sql.push_str(&format!(
+                " AND id IN (SELECT task_id FROM task_tags WHERE tag_id IN ({}) \
+                 GROUP BY task_id HAVING COUNT(*) = ?)",
+                placeholders
+            ));
+        } else {
+            sql.push_str(&format!(
+                " AND id IN (SELECT task_id FROM task_tags WHERE tag_id IN ({}))",
+                placeholders
+            ));
+        }
+        for tag_id in tag_ids {
+            param_values.push(Box::new(*tag_id));
+        }
+        if query.match_all_tags.unwrap_or(false) {
+            let mut distinct = tag_ids.clone();
+            distinct.sort_unstable();
+            distinct.dedup();
+            param_values.push(Box::new(distinct.len() as i64));
+        }
+    }
+
+    sql.push_str(" ORDER BY ");
+    sql.push_str(&task_order_by(&query));
+
+    let params: Vec<&dyn rusqlite::ToSql> = param_values.iter()
+        .map(|b| b.as_ref())
+        .collect();
+
+    let mut stmt = conn.prepare(&sql)?;
+    let roots = stmt
+        .query_map(params.as_slice(), task_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    attach_subtrees(conn, roots)
+}
+
+// Fills in the subtrees and tags of `roots` with one query for every task
+// below them and one for the tags of all of them, then assembles the trees
+// in memory. Subtasks keep their manual order.
+fn attach_subtrees(conn: &rusqlite::Connection, mut roots: Vec<Task>) -> AppResult<Vec<Task>> {
+    if roots.is_empty() {
+        return Ok(roots);
+    }
+
+    let root_ids = json_id_list(roots.iter().map(|task| task.id));
+    let mut stmt = conn.prepare(&format!(
+        // UNION rather than UNION ALL, so a damaged tree with a cycle still ends
+        "WITH RECURSIVE subtree(id) AS (
+            SELECT id FROM tasks
+            WHERE parent_id IN (SELECT value FROM json_each(?1)) AND deleted_at IS NULL
+            UNION
+            SELECT t.id FROM tasks t JOIN subtree s ON t.parent_id = s.id
+            WHERE t.deleted_at IS NULL
+         )
+         SELECT {} FROM tasks
+         WHERE id IN (SELECT id FROM subtree)
+         ORDER BY parent_id, position, id",
+        TASK_COLUMNS
+    ))?;
+    let descendants = stmt
+        .query_map([&root_ids], task_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let all_ids = json_id_list(roots.iter().chain(&descendants).map(|task| task.id));
+    let mut tags = load_tags_by_task(conn, &all_ids)?;
+
+    let mut children: HashMap<i64, Vec<Task>> = HashMap::new();
+    for mut task in descendants {
+        task.tags = tags.remove(&task.id).unwrap_or_default();
+        if let Some(parent_id) = task.parent_id {
+            children.entry(parent_id).or_default().push(task);
+        }
+    }
+
+    for root in &mut roots {
+        root.tags = tags.remove(&root.id).unwrap_or_default();
+        assemble_subtree(root, &mut children);
+        roll_up_progress(root);
+    }
+
+    Ok(roots)
+}
+
+fn assemble_subtree(task: &mut Task, children: &mut HashMap<i64, Vec<Task>>) {
+    let mut subtasks = children.remove(&task.id).unwrap_or_default();
+    for subtask in &mut subtasks {
+        assemble_subtree(subtask, children);
+    }
+    task.subtasks = subtasks;
+}
+
+// A JSON array of IDs, for `json_each` in place of a variable-length IN list
+fn json_id_list(ids: impl Iterator<Item = i64>) -> String {
+    let ids: Vec<String> = ids.map(|id| id.to_string()).collect();
+    format!("[{}]", ids.join(","))
+}
+
+fn validate_task_query(query: &TaskQuery) -> AppResult<()> {
+    validate_effort(query.min_priority, query.max_estimated_minutes, query.min_difficulty)?;
+    validate_effort(None, None, query.max_difficulty)
+}
+
+// Only fixed column names end up in the SQL; undated or unrated tasks sort last
+fn task_order_by(query: &TaskQuery) -> String {
+    let sort = query.sort_by.unwrap_or(TaskSort::Manual);
+    let descending = query.descending.unwrap_or(matches!(
+        sort,
+        TaskSort::CreatedAt | TaskSort::Priority | TaskSort::Difficulty | TaskSort::Effort
+    ));
+    let direction = if descending { "DESC" } else { "ASC" };
+
+    let (column, collation) = match sort {
+        TaskSort::Manual => ("position", ""),
+        TaskSort::CreatedAt => ("created_at", ""),
+        TaskSort::DueDate => ("due_date", ""),
+        TaskSort::Priority => ("priority", ""),
+        TaskSort::Difficulty => ("difficulty", ""),
+        TaskSort::Effort => ("estimated_minutes", ""),
+        TaskSort::Title => ("title", " COLLATE NOCASE"),
+    };
+
+    format!(
+        "{col} IS NULL, {col}{collation} {dir}, id {dir}",
+        col = column,
+        collation = collation,
+        dir = direction
     )
-    .map_err(|e| e.to_string())?;
+}
+
+// Column order expected by `task_from_row`
+const TASK_COLUMNS: &str = "id, title, description, is_completed, parent_id, due_date, is_recurring, \
+                            recurrence_pattern, created_at, priority, estimated_minutes, difficulty, \
+                            project_id";
+
+// `TASK_COLUMNS` for a query where the tasks table goes by `alias`
+fn prefixed_task_columns(alias: &str) -> String {
+    TASK_COLUMNS
+        .split(", ")
+        .map(|column| format!("{}.{}", alias, column.trim()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
 
-    Ok(conn.last_insert_rowid())
+fn task_from_row(row: &rusqlite::Row) -> rusqlite::Result<Task> {
+    Ok(Task {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        description: row.get(2)?,
+        is_completed: row.get(3)?,
+        parent_id: row.get(4)?,
+        due_date: row.get(5)?,
+        is_recurring: row.get(6)?,
+        recurrence_pattern: row.get(7)?,
+        created_at: row.get(8)?,
+        priority: row.get(9)?,
+        estimated_minutes: row.get(10)?,
+        difficulty: row.get(11)?,
+        project_id: row.get(12)?,
+        // Right for a leaf; `roll_up_progress` fills in parents
+        progress: LeafTally::leaf(row.get(3)?, row.get(10)?).progress(),
+        tags: vec![],
+        subtasks: vec![],
+    })
 }
 
+/// Moves a task to sit right after `after_id` (or first, without it) among the
+/// children of `parent_id`, or among the top-level tasks when that is `None`.
+/// Only the moved task's sort key changes, unless the parent does too; see
+/// `reparent`.
 #[tauri::command]
-pub fn get_all_tasks(state: State<DbState>) -> Result<Vec<Task>, String> {
-    let conn = state.conn.lock().unwrap();
-    
-    let mut stmt = conn
-        .prepare(
-            "SELECT id, title, description, is_completed, parent_id, due_date, is_recurring, recurrence_pattern, created_at 
-             FROM tasks 
-             WHERE parent_id IS NULL 
-             ORDER BY created_at DESC",
-        )
-        .map_err(|e| e.to_string())?;
+pub fn move_task(
+    state: State<DbState>,
+    id: i64,
+    parent_id: Option<i64>,
+    after_id: Option<i64>,
+) -> AppResult<ReparentResult> {
+    let placement = match after_id {
+        Some(after_id) if after_id == id => {
+            return Err(AppError::Validation("A task can't be placed after itself".to_string()));
+        }
+        Some(after_id) => Placement::After(after_id),
+        None => Placement::First,
+    };
 
-    let tasks = stmt
-        .query_map([], |row| {
-            Ok(Task {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                description: row.get(2)?,
-                is_completed: row.get(3)?,
-                parent_id: row.get(4)?,
-                due_date: row.get(5)?,
-                is_recurring: row.get(6)?,
-                recurrence_pattern: row.get(7)?,
-                created_at: row.get(8)?,
-                subtasks: vec![],
+    journaled(&state, "Move task", |tx| {
+        reparent(tx, id, parent_id, placement)
+    })
+}
+
+/// Makes a task the last subtask of the task just above it.
+#[tauri::command]
+pub fn indent_task(state: State<DbState>, id: i64) -> AppResult<ReparentResult> {
+    journaled(&state, "Indent task", |tx| {
+        let (parent_id, position): (Option<i64>, String) = tx
+            .query_row("SELECT parent_id, position FROM tasks WHERE id = ?1 AND deleted_at IS NULL", [id], |row| {
+                Ok((row.get(0)?, row.get(1)?))
             })
-        })
-        .map_err(|e| e.to_string())?;
+            .optional()?
+            .ok_or_else(|| AppError::NotFound(format!("Task {} not found", id)))?;
+
+        let above: i64 = tx
+            .query_row(
+                "SELECT id FROM tasks
+                 WHERE parent_id IS ?1 AND id != ?3 AND deleted_at IS NULL
+                   AND (position < ?2 OR (position = ?2 AND id < ?3))
+                 ORDER BY position DESC, id DESC
+                 LIMIT 1",
+                (parent_id, &position, id),
+                |row| row.get(0),
+            )
+            .optional()?
+            .ok_or_else(|| AppError::Validation("There is no task above this one to indent it under".to_string()))?;
+
+        reparent(tx, id, Some(above), Placement::Last)
+    })
+}
+
+/// Moves a subtask up one level, right after its current parent.
+#[tauri::command]
+pub fn outdent_task(state: State<DbState>, id: i64) -> AppResult<ReparentResult> {
+    journaled(&state, "Outdent task", |tx| {
+        let parent_id = load_parent_id(tx, id)?
+            .ok_or_else(|| AppError::Validation("Top-level tasks can't be outdented".to_string()))?;
+        let grandparent_id = load_parent_id(tx, parent_id)?;
+
+        reparent(tx, id, grandparent_id, Placement::After(parent_id))
+    })
+}
+
+/// Makes a subtask a top-level task, right after the top-level task it was under.
+#[tauri::command]
+pub fn promote_task(state: State<DbState>, id: i64) -> AppResult<ReparentResult> {
+    journaled(&state, "Promote task", |tx| {
+        let mut root_id = load_parent_id(tx, id)?
+            .ok_or_else(|| AppError::Validation("The task is already a top-level task".to_string()))?;
+        while let Some(parent_id) = load_parent_id(tx, root_id)? {
+            root_id = parent_id;
+        }
+
+        reparent(tx, id, None, Placement::After(root_id))
+    })
+}
+
+// Moves `id` (with its subtree) under `parent_id`. A subtree that changes
+// parent takes on the new parent's project, and both the old and the new
+// ancestors have their completion recomputed under the completion rules.
+// The days the subtree is assigned to are recounted along with any day a
+// parent was closed on.
+fn reparent(
+    conn: &rusqlite::Connection,
+    id: i64,
+    parent_id: Option<i64>,
+    placement: Placement,
+) -> AppResult<ReparentResult> {
+    let old_parent_id = load_parent_id(conn, id)?;
+    if let Some(parent_id) = parent_id {
+        ensure_task_exists(conn, parent_id)?;
+        if parent_id == id || is_ancestor(conn, id, parent_id)? {
+            return Err(AppError::Validation(
+                "A task can't be moved under itself or one of its subtasks".to_string(),
+            ));
+        }
+    }
+
+    let position = sibling_position(conn, parent_id, Some(id), placement)?;
+    conn.execute(
+        "UPDATE tasks SET parent_id = ?1, position = ?2 WHERE id = ?3",
+        (parent_id, &position, id),
+    )?;
 
-    let mut all_tasks = Vec::new();
-    for task in tasks {
-        let mut task = task.map_err(|e| e.to_string())?;
-        task.subtasks = get_subtasks(&conn, task.id)?;
-        all_tasks.push(task);
+    if old_parent_id == parent_id {
+        return Ok(ReparentResult {
+            task_id: id,
+            parent_id,
+            changed_task_ids: Vec::new(),
+            affected_days: Vec::new(),
+        });
     }
 
-    Ok(all_tasks)
+    // Subtasks always live in their parent's project
+    if let Some(parent_id) = parent_id {
+        conn.execute(
+            "WITH RECURSIVE subtree(id) AS (
+                SELECT ?1
+                UNION ALL
+                SELECT t.id FROM tasks t JOIN subtree s ON t.parent_id = s.id
+             )
+             UPDATE tasks SET project_id = (SELECT project_id FROM tasks WHERE id = ?2)
+             WHERE id IN (SELECT id FROM subtree)",
+            (id, parent_id),
+        )?;
+    }
+
+    let rules = load_completion_rules(conn)?;
+    let today = Local::now().date_naive();
+    let mut changes = CompletionChanges::default();
+    for start in [old_parent_id, parent_id].into_iter().flatten() {
+        sync_ancestor_completion(conn, start, today, &rules, &mut changes)?;
+    }
+
+    let mut stmt = conn.prepare(
+        "WITH RECURSIVE subtree(id) AS (
+            SELECT ?1
+            UNION ALL
+            SELECT t.id FROM tasks t JOIN subtree s ON t.parent_id = s.id
+         )
+         SELECT DISTINCT date FROM task_assignments WHERE task_id IN (SELECT id FROM subtree)",
+    )?;
+    let assigned = stmt
+        .query_map([id], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+    changes.days.extend(assigned);
+
+    let (changed_task_ids, affected_days) = changes.finish(conn)?;
+    Ok(ReparentResult {
+        task_id: id,
+        parent_id,
+        changed_task_ids,
+        affected_days,
+    })
 }
 
-fn get_subtasks(conn: &rusqlite::Connection, parent_id: i64) -> Result<Vec<Task>, String> {
-    let mut stmt = conn
-        .prepare(
-            "SELECT id, title, description, is_completed, parent_id, due_date, is_recurring, recurrence_pattern, created_at 
-             FROM tasks 
-             WHERE parent_id = ?1 
-             ORDER BY created_at ASC",
+fn load_parent_id(conn: &rusqlite::Connection, id: i64) -> AppResult<Option<i64>> {
+    conn.query_row("SELECT parent_id FROM tasks WHERE id = ?1 AND deleted_at IS NULL", [id], |row| row.get(0))
+        .optional()?
+        .ok_or_else(|| AppError::NotFound(format!("Task {} not found", id)))
+}
+
+// Where a task goes among its siblings
+enum Placement {
+    First,
+    Last,
+    After(i64),
+}
+
+// Sort key for a task placed among the children of `parent_id`, not counting
+// `moving_id` itself. If the neighbours' keys leave no room, the siblings are
+// renumbered first; that is the only time more than one row changes.
+fn sibling_position(
+    conn: &rusqlite::Connection,
+    parent_id: Option<i64>,
+    moving_id: Option<i64>,
+    placement: Placement,
+) -> AppResult<String> {
+    let mut stmt = conn.prepare(
+        "SELECT id, position FROM tasks
+         WHERE parent_id IS ?1 AND id IS NOT ?2 AND deleted_at IS NULL
+         ORDER BY position, id",
+    )?;
+    let mut siblings = stmt
+        .query_map((parent_id, moving_id), |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let index = match placement {
+        Placement::First => 0,
+        Placement::Last => siblings.len(),
+        Placement::After(after_id) => {
+            siblings
+                .iter()
+                .position(|(id, _)| *id == after_id)
+                .ok_or_else(|| {
+                    AppError::Validation(format!("Task {} is not in the list the task is moved to", after_id))
+                })?
+                + 1
+        }
+    };
+
+    let between = |siblings: &[(i64, String)]| {
+        ordering::key_between(
+            index.checked_sub(1).map(|i| siblings[i].1.as_str()),
+            siblings.get(index).map(|(_, key)| key.as_str()),
         )
-        .map_err(|e| e.to_string())?;
+    };
+    if let Some(key) = between(&siblings) {
+        return Ok(key);
+    }
 
-    let subtasks = stmt
-        .query_map([parent_id], |row| {
-            Ok(Task {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                description: row.get(2)?,
-                is_completed: row.get(3)?,
-                parent_id: row.get(4)?,
-                due_date: row.get(5)?,
-                is_recurring: row.get(6)?,
-                recurrence_pattern: row.get(7)?,
-                created_at: row.get(8)?,
-                subtasks: vec![],
-            })
-        })
-        .map_err(|e| e.to_string())?;
+    let keys = ordering::spread(siblings.len());
+    for ((id, key), new_key) in siblings.iter_mut().zip(keys) {
+        conn.execute("UPDATE tasks SET position = ?1 WHERE id = ?2", (&new_key, *id))?;
+        *key = new_key;
+    }
+    Ok(between(&siblings).expect("spread keys leave room between them"))
+}
+
+// Whether `ancestor_id` is somewhere above `task_id` in the tree
+fn is_ancestor(conn: &rusqlite::Connection, ancestor_id: i64, task_id: i64) -> AppResult<bool> {
+    let found = conn.query_row(
+        "WITH RECURSIVE ancestors(id) AS (
+            SELECT parent_id FROM tasks WHERE id = ?1
+            UNION
+            SELECT t.parent_id FROM tasks t JOIN ancestors a ON t.id = a.id
+         )
+         SELECT EXISTS(SELECT 1 FROM ancestors WHERE id = ?2)",
+        (task_id, ancestor_id),
+        |row| row.get(0),
+    )?;
+    Ok(found)
+}
+
+/// Progress of a task and of every task below it, parents before their
+/// subtasks.
+#[tauri::command]
+pub fn get_subtree_progress(state: State<DbState>, task_id: i64) -> AppResult<Vec<SubtreeProgress>> {
+    let conn = state.conn.lock()?;
+    ensure_task_exists(&conn, task_id)?;
+    load_subtree_progress(&conn, task_id)
+}
+
+// Sets `progress` on a loaded task tree and returns the tally for its root
+fn roll_up_progress(task: &mut Task) -> LeafTally {
+    if task.subtasks.is_empty() {
+        return LeafTally::leaf(task.is_completed, task.estimated_minutes);
+    }
 
-    let mut all_subtasks = Vec::new();
-    for subtask in subtasks {
-        let mut subtask = subtask.map_err(|e| e.to_string())?;
-        subtask.subtasks = get_subtasks(conn, subtask.id)?;
-        all_subtasks.push(subtask);
+    let mut tally = LeafTally::default();
+    for subtask in &mut task.subtasks {
+        tally.add(roll_up_progress(subtask));
     }
+    task.progress = tally.progress();
+    tally
+}
+
+// Starts with `task_id` itself, which must exist
+fn load_subtree_progress(conn: &rusqlite::Connection, task_id: i64) -> AppResult<Vec<SubtreeProgress>> {
+    let mut stmt = conn.prepare(
+        "WITH RECURSIVE subtree(id, depth) AS (
+            SELECT ?1, 0
+            UNION ALL
+            SELECT t.id, s.depth + 1 FROM tasks t JOIN subtree s ON t.parent_id = s.id
+            WHERE t.deleted_at IS NULL
+         )
+         SELECT t.id, t.parent_id, s.depth, t.is_completed, t.estimated_minutes
+         FROM subtree s JOIN tasks t ON t.id = s.id
+         ORDER BY s.depth, t.position, t.id",
+    )?;
+    let nodes = stmt
+        .query_map([task_id], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, Option<i64>>(1)?,
+                row.get::<_, i64>(2)?,
+                LeafTally::leaf(row.get(3)?, row.get(4)?),
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Deepest first, so every subtree is summed before its parent is reached
+    let mut child_tallies: HashMap<i64, LeafTally> = HashMap::new();
+    let mut tallies: HashMap<i64, LeafTally> = HashMap::new();
+    for (id, parent_id, depth, leaf) in nodes.iter().rev() {
+        let tally = child_tallies.remove(id).unwrap_or(*leaf);
+        if let (Some(parent_id), true) = (parent_id, *depth > 0) {
+            child_tallies.entry(*parent_id).or_default().add(tally);
+        }
+        tallies.insert(*id, tally);
+    }
+
+    Ok(nodes
+        .into_iter()
+        .map(|(id, parent_id, depth, _)| SubtreeProgress {
+            task_id: id,
+            parent_id,
+            depth,
+            progress: tallies[&id].progress(),
+        })
+        .collect())
+}
 
-    Ok(all_subtasks)
+// Progress of each task in `task_ids`, a JSON array as from `json_id_list`,
+// summed over the leaves below it in one query
+fn load_progress_by_task(
+    conn: &rusqlite::Connection,
+    task_ids: &str,
+) -> AppResult<HashMap<i64, TaskProgress>> {
+    let mut stmt = conn.prepare(
+        "WITH RECURSIVE subtree(root, id) AS (
+            SELECT value, value FROM json_each(?1)
+            UNION ALL
+            SELECT s.root, t.id FROM tasks t JOIN subtree s ON t.parent_id = s.id
+            WHERE t.deleted_at IS NULL
+         )
+         SELECT s.root, t.is_completed, t.estimated_minutes
+         FROM subtree s JOIN tasks t ON t.id = s.id
+         WHERE NOT EXISTS (
+            SELECT 1 FROM tasks c WHERE c.parent_id = t.id AND c.deleted_at IS NULL
+         )",
+    )?;
+    let leaves = stmt.query_map([task_ids], |row| {
+        Ok((row.get::<_, i64>(0)?, LeafTally::leaf(row.get(1)?, row.get(2)?)))
+    })?;
+
+    let mut tallies: HashMap<i64, LeafTally> = HashMap::new();
+    for leaf in leaves {
+        let (root, tally) = leaf?;
+        tallies.entry(root).or_default().add(tally);
+    }
+    Ok(tallies.into_iter().map(|(id, tally)| (id, tally.progress())).collect())
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub fn update_task(
     state: State<DbState>,
     id: i64,
@@ -119,366 +690,2817 @@ pub fn update_task(
     due_date: Option<String>,
     is_recurring: Option<bool>,
     recurrence_pattern: Option<String>,
-) -> Result<(), String> {
-    let conn = state.conn.lock().unwrap();
-    
-    let mut query = String::from("UPDATE tasks SET ");
-    let mut updates = Vec::new();
-    let mut param_values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
-
-    if let Some(t) = title {
-        updates.push("title = ?");
-        param_values.push(Box::new(t));
+    priority: Option<i32>,
+    estimated_minutes: Option<i32>,
+    difficulty: Option<i32>,
+) -> AppResult<()> {
+    if let Some(t) = &title {
+        validate_title(t)?;
     }
-    if let Some(d) = description {
-        updates.push("description = ?");
-        param_values.push(Box::new(d));
+    validate_effort(priority, estimated_minutes, difficulty)?;
+
+    journaled(&state, "Edit task", |conn| {
+        ensure_task_exists(conn, id)?;
+
+        if let Some(rp) = &recurrence_pattern {
+            validate_recurrence(Some(rp))?;
+        } else if is_recurring == Some(true) {
+            let existing: Option<String> = conn
+                .query_row("SELECT recurrence_pattern FROM tasks WHERE id = ?1", [id], |row| row.get(0))?;
+            validate_recurrence(existing.as_deref())?;
+        }
+        let series_before = load_series(conn, id)?;
+
+        let mut query = String::from("UPDATE tasks SET ");
+        let mut updates = Vec::new();
+        let mut param_values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+
+        if let Some(t) = title {
+            updates.push("title = ?");
+            param_values.push(Box::new(t));
+        }
+        if let Some(d) = description {
+            updates.push("description = ?");
+            param_values.push(Box::new(d));
+        }
+        if let Some(dd) = due_date {
+            updates.push("due_date = ?");
+            param_values.push(Box::new(dd));
+        }
+        if let Some(ir) = is_recurring {
+            updates.push("is_recurring = ?");  
+            param_values.push(Box::new(ir as i32));
+        }
+        if let Some(rp) = recurrence_pattern {
+            updates.push("recurrence_pattern = ?");
+            param_values.push(Box::new(rp));
+        }
+        if let Some(p) = priority {
+            updates.push("priority = ?");
+            param_values.push(Box::new(p));
+        }
+        if let Some(em) = estimated_minutes {
+            updates.push("estimated_minutes = ?");
+            param_values.push(Box::new(em));
+        }
+        if let Some(d) = difficulty {
+            updates.push("difficulty = ?");
+            param_values.push(Box::new(d));
+        }
+
+        if updates.is_empty() {
+            return Ok(());
+        }
+
+        query.push_str(&updates.join(", "));
+        query.push_str(" WHERE id = ?");
+        param_values.push(Box::new(id));
+
+        let params: Vec<&dyn rusqlite::ToSql> = param_values.iter()
+            .map(|b| b.as_ref())
+            .collect();
+
+        conn.execute(&query, params.as_slice())?;
+
+        // A new due date or pattern starts the series over from the due date;
+        // the form sends them back unchanged with every edit
+        if load_series(conn, id)? != series_before {
+            conn.execute(
+                "UPDATE tasks SET recurrence_anchor = CASE WHEN is_recurring THEN due_date END WHERE id = ?1",
+                [id],
+            )?;
+        }
+
+        Ok(())
+    })
+}
+
+// What decides when a task recurs: its due date, whether it recurs and how
+fn load_series(conn: &rusqlite::Connection, id: i64) -> AppResult<(Option<String>, bool, Option<String>)> {
+    Ok(conn.query_row(
+        "SELECT due_date, is_recurring, recurrence_pattern FROM tasks WHERE id = ?1",
+        [id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    )?)
+}
+
+fn validate_recurrence(pattern: Option<&str>) -> AppResult<RecurrenceRule> {
+    match pattern {
+        Some(p) => RecurrenceRule::parse(p).map_err(AppError::Validation),
+        None => Err(AppError::Validation(
+            "Recurring tasks need a recurrence pattern".to_string(),
+        )),
     }
-    if let Some(dd) = due_date {
-        updates.push("due_date = ?");
-        param_values.push(Box::new(dd));
+}
+
+fn validate_effort(
+    priority: Option<i32>,
+    estimated_minutes: Option<i32>,
+    difficulty: Option<i32>,
+) -> AppResult<()> {
+    if let Some(p) = priority {
+        if !(0..=MAX_PRIORITY).contains(&p) {
+            return Err(AppError::Validation(format!(
+                "Priority must be between 0 and {}",
+                MAX_PRIORITY
+            )));
+        }
     }
-    if let Some(ir) = is_recurring {
-        updates.push("is_recurring = ?");  
-        param_values.push(Box::new(ir as i32));
+    if let Some(m) = estimated_minutes {
+        if !(1..=MAX_ESTIMATED_MINUTES).contains(&m) {
+            return Err(AppError::Validation(format!(
+                "Estimated effort must be between 1 and {} minutes",
+                MAX_ESTIMATED_MINUTES
+            )));
+        }
     }
-    if let Some(rp) = recurrence_pattern {
-        updates.push("recurrence_pattern = ?");
-        param_values.push(Box::new(rp));
+    if let Some(d) = difficulty {
+        if !(1..=MAX_DIFFICULTY).contains(&d) {
+            return Err(AppError::Validation(format!(
+                "Difficulty must be between 1 and {}",
+                MAX_DIFFICULTY
+            )));
+        }
     }
+    Ok(())
+}
 
-    if updates.is_empty() {
-        return Ok(());
+fn validate_title(title: &str) -> AppResult<()> {
+    if title.trim().is_empty() {
+        return Err(AppError::Validation("Task title cannot be empty".to_string()));
     }
+    Ok(())
+}
 
-    query.push_str(&updates.join(", "));
-    query.push_str(&format!(" WHERE id = ?"));
-    param_values.push(Box::new(id));
+fn ensure_task_exists(conn: &rusqlite::Connection, id: i64) -> AppResult<()> {
+    conn.query_row("SELECT 1 FROM tasks WHERE id = ?1 AND deleted_at IS NULL", [id], |_| Ok(()))
+        .optional()?
+        .ok_or_else(|| AppError::NotFound(format!("Task {} not found", id)))
+}
 
-    let params: Vec<&dyn rusqlite::ToSql> = param_values.iter()
-        .map(|b| b.as_ref())
-        .collect();
+fn parse_day(date: &str) -> AppResult<NaiveDate> {
+    recurrence::parse_date(date)
+        .filter(|_| date.len() == 10)
+        .ok_or_else(|| AppError::Validation(format!("'{}' is not a YYYY-MM-DD date", date)))
+}
 
-    conn.execute(&query, params.as_slice())
-        .map_err(|e| e.to_string())?;
+fn format_day(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
 
-    Ok(())
+/// Moves a task and its subtasks to the trash. Their tracker assignments are
+/// set aside until the task is restored, so the days they were on are
+/// recounted without them, and a parent left with only done subtasks is
+/// closed under the completion rules.
+#[tauri::command]
+pub fn delete_task(state: State<DbState>, id: i64) -> AppResult<DeleteTaskResult> {
+    journaled(&state, "Delete task", |tx| {
+        let parent_id = load_parent_id(tx, id)?;
+
+        // Subtasks already in the trash stay there under their own deletion
+        let deleted_task_ids: Vec<i64> = {
+            let mut stmt = tx.prepare(
+                "WITH RECURSIVE subtree(id) AS (
+                    SELECT ?1
+                    UNION ALL
+                    SELECT t.id FROM tasks t JOIN subtree s ON t.parent_id = s.id
+                    WHERE t.deleted_at IS NULL
+                 )
+                 SELECT id FROM subtree",
+            )?;
+            let ids = stmt
+                .query_map([id], |row| row.get(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            ids
+        };
+        let ids = json_id_list(deleted_task_ids.iter().copied());
+
+        let mut changes = CompletionChanges::default();
+        let mut removed_assignments = 0;
+        for task_id in &deleted_task_ids {
+            let days = assigned_dates(tx, *task_id)?;
+            removed_assignments += days.len() as i64;
+            changes.days.extend(days);
+        }
+
+        tx.execute(
+            "UPDATE tasks SET deleted_at = strftime('%Y-%m-%d %H:%M:%f', 'now')
+             WHERE id IN (SELECT value FROM json_each(?1))",
+            [&ids],
+        )?;
+        tx.execute(
+            "INSERT OR IGNORE INTO trashed_assignments (task_id, date, assigned_by, moved_from, moved_by, moved_at)
+             SELECT task_id, date, assigned_by, moved_from, moved_by, moved_at
+             FROM task_assignments WHERE task_id IN (SELECT value FROM json_each(?1))",
+            [&ids],
+        )?;
+        tx.execute(
+            "DELETE FROM task_assignments WHERE task_id IN (SELECT value FROM json_each(?1))",
+            [&ids],
+        )?;
+
+        if let Some(parent_id) = parent_id {
+            let rules = load_completion_rules(tx)?;
+            sync_ancestor_completion(tx, parent_id, Local::now().date_naive(), &rules, &mut changes)?;
+        }
+        let (changed_task_ids, affected_days) = changes.finish(tx)?;
+
+        Ok(DeleteTaskResult {
+            deleted_task_ids,
+            removed_assignments,
+            changed_task_ids,
+            affected_days,
+        })
+    })
 }
 
+/// Checks a task off in the task list, or reopens it. A recurring task moves on
+/// to its next occurrence instead of closing. Subtasks and parents follow
+/// along as set by the completion rules.
 #[tauri::command]
-pub fn delete_task(state: State<DbState>, id: i64) -> Result<(), String> {
-    let conn = state.conn.lock().unwrap();
-    
-    conn.execute("DELETE FROM tasks WHERE id = ?1", [id])
-        .map_err(|e| e.to_string())?;
+pub fn toggle_task_completion(state: State<DbState>, id: i64) -> AppResult<CompletionResult> {
+    journaled(&state, "Toggle task", |tx| {
+        let is_completed: bool = tx
+            .query_row("SELECT is_completed FROM tasks WHERE id = ?1 AND deleted_at IS NULL", [id], |row| row.get(0))
+            .optional()?
+            .ok_or_else(|| AppError::NotFound(format!("Task {} not found", id)))?;
 
-    Ok(())
+        let rules = load_completion_rules(tx)?;
+        let today = Local::now().date_naive();
+        let mut changes = CompletionChanges::default();
+        changes.task_ids.push(id);
+
+        let is_completed = if is_completed {
+            reopen_task(tx, id, &rules, &mut changes)?;
+            false
+        } else {
+            complete_on(tx, id, today, COMPLETION_SOURCE_TASK_LIST, &rules, &mut changes)?
+        };
+
+        let (changed_task_ids, affected_days) = changes.finish(tx)?;
+        update_user_stats(tx)?;
+
+        Ok(CompletionResult {
+            task_id: id,
+            is_completed,
+            changed_task_ids,
+            affected_days,
+        })
+    })
 }
 
+/// Marks a task done for one tracker day. Recurring tasks can be done once per
+/// date; anything else is closed, subtasks included.
 #[tauri::command]
-pub fn toggle_task_completion(state: State<DbState>, id: i64) -> Result<bool, String> {
-    let conn = state.conn.lock().unwrap();
-    
-    // Get current completion status
-    let is_completed: bool = conn
-        .query_row("SELECT is_completed FROM tasks WHERE id = ?1", [id], |row| {
-            row.get(0)
+pub fn complete_task_for_date(state: State<DbState>, task_id: i64, date: String) -> AppResult<CompletionResult> {
+    let day = parse_day(&date)?;
+
+    journaled(&state, "Complete task", |tx| {
+        ensure_task_exists(tx, task_id)?;
+
+        let rules = load_completion_rules(tx)?;
+        let mut changes = CompletionChanges::default();
+        changes.task_ids.push(task_id);
+
+        let is_completed = complete_on(tx, task_id, day, COMPLETION_SOURCE_TRACKER, &rules, &mut changes)?;
+
+        let (changed_task_ids, affected_days) = changes.finish(tx)?;
+        update_user_stats(tx)?;
+
+        Ok(CompletionResult {
+            task_id,
+            is_completed,
+            changed_task_ids,
+            affected_days,
         })
-        .map_err(|e| e.to_string())?;
+    })
+}
 
-    let new_status = !is_completed;
-    
-    // Update the task
-    conn.execute(
-        "UPDATE tasks SET is_completed = ?1 WHERE id = ?2",
-        (new_status, id),
-    )
-    .map_err(|e| e.to_string())?;
+/// Removes the completion of a task for one date. If it was the completion that
+/// moved a recurring task on, the task is due on its earlier date again. A task
+/// reopened this way takes its subtasks and parents along like
+/// `toggle_task_completion` does.
+#[tauri::command]
+pub fn uncomplete_task_for_date(state: State<DbState>, task_id: i64, date: String) -> AppResult<CompletionResult> {
+    parse_day(&date)?;
+
+    journaled(&state, "Reopen task", |tx| {
+        let was_completed: bool = tx
+            .query_row("SELECT is_completed FROM tasks WHERE id = ?1 AND deleted_at IS NULL", [task_id], |row| row.get(0))
+            .optional()?
+            .ok_or_else(|| AppError::NotFound(format!("Task {} not found", task_id)))?;
+
+        let completion: Option<(i64, Option<String>)> = tx
+            .query_row(
+                "SELECT id, rolled_from FROM task_completions WHERE task_id = ?1 AND date = ?2",
+                (task_id, &date),
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+
+        let Some((completion_id, rolled_from)) = completion else {
+            return Ok(CompletionResult {
+                task_id,
+                is_completed: was_completed,
+                changed_task_ids: Vec::new(),
+                affected_days: Vec::new(),
+            });
+        };
+
+        tx.execute("DELETE FROM task_completions WHERE id = ?1", [completion_id])?;
+
+        // Only the latest roll can be undone without losing track of the series
+        let later_roll: bool = tx.query_row(
+            "SELECT EXISTS(SELECT 1 FROM task_completions
+                           WHERE task_id = ?1 AND rolled_from IS NOT NULL AND id > ?2)",
+            (task_id, completion_id),
+            |row| row.get(0),
+        )?;
+
+        match rolled_from {
+            Some(previous_due) if !later_roll => {
+                tx.execute(
+                    "UPDATE tasks SET is_completed = 0, due_date = ?1 WHERE id = ?2",
+                    (&previous_due, task_id),
+                )?;
+            }
+            Some(_) => {}
+            None => {
+                tx.execute(
+                    "UPDATE tasks SET is_completed = EXISTS(SELECT 1 FROM task_completions WHERE task_id = ?1)
+                     WHERE id = ?1 AND is_recurring = 0",
+                    [task_id],
+                )?;
+            }
+        }
+
+        let is_completed: bool =
+            tx.query_row("SELECT is_completed FROM tasks WHERE id = ?1", [task_id], |row| row.get(0))?;
+
+        let rules = load_completion_rules(tx)?;
+        let mut changes = CompletionChanges::default();
+        changes.task_ids.push(task_id);
+        changes.days.push(date);
+        if was_completed && !is_completed {
+            if rules.reopen_subtasks {
+                reopen_subtasks(tx, task_id, &mut changes)?;
+            }
+            if let Some(parent_id) = load_parent_id(tx, task_id)? {
+                sync_ancestor_completion(tx, parent_id, Local::now().date_naive(), &rules, &mut changes)?;
+            }
+        }
+
+        let (changed_task_ids, affected_days) = changes.finish(tx)?;
+        update_user_stats(tx)?;
+
+        Ok(CompletionResult {
+            task_id,
+            is_completed,
+            changed_task_ids,
+            affected_days,
+        })
+    })
+}
+
+/// How completing or reopening a task spreads to its subtasks and parents.
+#[tauri::command]
+pub fn get_completion_rules(state: State<DbState>) -> AppResult<CompletionRules> {
+    let conn = state.conn.lock()?;
+    load_completion_rules(&conn)
+}
+
+/// Changes the completion rules. Tasks already checked off are left as they are.
+#[tauri::command]
+pub fn set_completion_rules(state: State<DbState>, rules: CompletionRules) -> AppResult<()> {
+    journaled(&state, "Change completion rules", |conn| {
+        let value = serde_json::to_string(&rules)
+            .map_err(|e| AppError::Validation(format!("Invalid completion rules: {}", e)))?;
+        database::set_setting(conn, COMPLETION_RULES_SETTING, &value)?;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn get_task_completions(state: State<DbState>, task_id: i64) -> AppResult<Vec<TaskCompletion>> {
+    let conn = state.conn.lock()?;
+    ensure_task_exists(&conn, task_id)?;
+
+    let mut stmt = conn.prepare(
+        "SELECT id, task_id, date, completed_at, source
+         FROM task_completions
+         WHERE task_id = ?1
+         ORDER BY date DESC",
+    )?;
+
+    let completions = stmt
+        .query_map([task_id], |row| {
+            Ok(TaskCompletion {
+                id: row.get(0)?,
+                task_id: row.get(1)?,
+                date: row.get(2)?,
+                completed_at: row.get(3)?,
+                source: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(completions)
+}
+
+const COMPLETION_SOURCE_TASK_LIST: &str = "task_list";
+const COMPLETION_SOURCE_TRACKER: &str = "tracker";
+const COMPLETION_SOURCE_PARENT: &str = "parent";
+// A parent closed because all of its subtasks were done
+const COMPLETION_SOURCE_SUBTASKS: &str = "subtasks";
+
+const COMPLETION_RULES_SETTING: &str = "completion_rules";
+
+// Missing or unreadable rules fall back to the defaults
+fn load_completion_rules(conn: &rusqlite::Connection) -> AppResult<CompletionRules> {
+    let stored = database::get_setting(conn, COMPLETION_RULES_SETTING)?;
+    Ok(stored
+        .and_then(|value| serde_json::from_str(&value).ok())
+        .unwrap_or_default())
+}
+
+// Tasks and tracker days touched while a completion spreads through the tree
+#[derive(Default)]
+struct CompletionChanges {
+    task_ids: Vec<i64>,
+    days: Vec<String>,
+}
+
+impl CompletionChanges {
+    // Recounts every affected day and returns the task IDs (first change
+    // first) and the days, without duplicates
+    fn finish(mut self, conn: &rusqlite::Connection) -> AppResult<(Vec<i64>, Vec<String>)> {
+        let mut seen = HashSet::new();
+        self.task_ids.retain(|id| seen.insert(*id));
+
+        self.days.sort();
+        self.days.dedup();
+        for date in &self.days {
+            update_day_status_internal(conn, date)?;
+        }
+
+        Ok((self.task_ids, self.days))
+    }
+}
+
+// Records a completion of `id` on `date` and returns whether the task is now
+// closed. A recurring task moves on to the occurrence after `date` when the
+// completion is for its open occurrence (on or after the due date) or done
+// early today; ticking off an older tracker day only adds to its history.
+fn complete_on(
+    conn: &rusqlite::Connection,
+    id: i64,
+    date: NaiveDate,
+    source: &str,
+    rules: &CompletionRules,
+    changes: &mut CompletionChanges,
+) -> AppResult<bool> {
+    let (is_recurring, recurrence_pattern, due_date, anchor, parent_id): (
+        bool,
+        Option<String>,
+        Option<String>,
+        Option<String>,
+        Option<i64>,
+    ) = conn
+        .query_row(
+            "SELECT is_recurring, recurrence_pattern, due_date, recurrence_anchor, parent_id
+             FROM tasks WHERE id = ?1 AND deleted_at IS NULL",
+            [id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+        )
+        .optional()?
+        .ok_or_else(|| AppError::NotFound(format!("Task {} not found", id)))?;
+
+    let date_str = format_day(date);
+    let inserted = conn.execute(
+        "INSERT OR IGNORE INTO task_completions (task_id, date, source) VALUES (?1, ?2, ?3)",
+        (id, &date_str, source),
+    )?;
+    // A recurring task done on this date has already moved on, so checking it
+    // again would otherwise do nothing at all
+    if inserted == 0 && is_recurring {
+        return Err(AppError::Conflict(format!("Task {} is already completed for {}", id, date_str)));
+    }
+    changes.days.push(date_str.clone());
+
+    let closed = if is_recurring {
+        let rule = validate_recurrence(recurrence_pattern.as_deref())?;
+        let due = due_date.as_deref().and_then(recurrence::parse_date).unwrap_or(date);
+        // The due date may have been clamped to a short month; the anchor
+        // keeps the day the series falls on
+        let anchor = anchor.as_deref().and_then(recurrence::parse_date).unwrap_or(due);
+        let today = Local::now().date_naive();
+
+        if date >= due || date == today {
+            conn.execute(
+                "UPDATE task_completions SET rolled_from = ?1 WHERE task_id = ?2 AND date = ?3",
+                (format_day(due), id, &date_str),
+            )?;
+
+            match rule.next_after(anchor, date.max(due)) {
+                Some(next_due) => {
+                    roll_recurring_task(conn, id, &format_day(next_due), &format_day(anchor), changes)?;
+                    false
+                }
+                // The series has ended
+                None => {
+                    close_task(conn, id, date, rules, changes)?;
+                    true
+                }
+            }
+        } else {
+            false
+        }
+    } else {
+        close_task(conn, id, date, rules, changes)?;
+        true
+    };
+
+    if let Some(parent_id) = parent_id {
+        sync_ancestor_completion(conn, parent_id, date, rules, changes)?;
+    }
+
+    Ok(closed)
+}
+
+fn close_task(
+    conn: &rusqlite::Connection,
+    id: i64,
+    date: NaiveDate,
+    rules: &CompletionRules,
+    changes: &mut CompletionChanges,
+) -> AppResult<()> {
+    conn.execute("UPDATE tasks SET is_completed = 1 WHERE id = ?1", [id])?;
+    changes.task_ids.push(id);
+
+    if rules.complete_subtasks {
+        complete_subtasks(conn, id, &format_day(date), changes)?;
+    }
+    Ok(())
+}
+
+// Reopens a closed task. Its completion history goes with it, so the days it
+// was ticked off on go back to counting it as open.
+fn reopen_task(
+    conn: &rusqlite::Connection,
+    id: i64,
+    rules: &CompletionRules,
+    changes: &mut CompletionChanges,
+) -> AppResult<()> {
+    let dates: Vec<String> = {
+        let mut stmt = conn.prepare("SELECT date FROM task_completions WHERE task_id = ?1")?;
+        let dates = stmt
+            .query_map([id], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        dates
+    };
+
+    conn.execute("DELETE FROM task_completions WHERE task_id = ?1", [id])?;
+    conn.execute("UPDATE tasks SET is_completed = 0 WHERE id = ?1", [id])?;
+    changes.task_ids.push(id);
+    changes.days.extend(dates);
+
+    if rules.reopen_subtasks {
+        reopen_subtasks(conn, id, changes)?;
+    }
+    if let Some(parent_id) = load_parent_id(conn, id)? {
+        sync_ancestor_completion(conn, parent_id, Local::now().date_naive(), rules, changes)?;
+    }
+    Ok(())
+}
+
+// Brings `task_id` and the tasks above it in line with their subtasks, as far
+// as the rules allow: a closed parent with an open subtask is reopened, and
+// an open parent whose subtasks are all done is closed on `date`. Recurring
+// parents are never closed this way, as that would roll them on to their next
+// occurrence. A reopened parent loses the completion it got from its
+// subtasks, but keeps the days it was checked off by hand.
+fn sync_ancestor_completion(
+    conn: &rusqlite::Connection,
+    task_id: i64,
+    date: NaiveDate,
+    rules: &CompletionRules,
+    changes: &mut CompletionChanges,
+) -> AppResult<()> {
+    let mut current = Some(task_id);
+
+    while let Some(task_id) = current {
+        let (is_completed, is_recurring, parent_id, subtasks, open_subtasks): (bool, bool, Option<i64>, i64, i64) =
+            conn.query_row(
+                "SELECT is_completed, is_recurring, parent_id,
+                        (SELECT COUNT(*) FROM tasks c WHERE c.parent_id = tasks.id AND c.deleted_at IS NULL),
+                        (SELECT COUNT(*) FROM tasks c
+                         WHERE c.parent_id = tasks.id AND c.deleted_at IS NULL AND c.is_completed = 0)
+                 FROM tasks WHERE id = ?1",
+                [task_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+            )?;
+
+        if is_completed && open_subtasks > 0 && rules.reopen_parent {
+            let dates = completion_dates(conn, task_id, COMPLETION_SOURCE_SUBTASKS)?;
+            conn.execute(
+                "DELETE FROM task_completions WHERE task_id = ?1 AND source = ?2",
+                (task_id, COMPLETION_SOURCE_SUBTASKS),
+            )?;
+            conn.execute("UPDATE tasks SET is_completed = 0 WHERE id = ?1", [task_id])?;
+            changes.task_ids.push(task_id);
+            changes.days.extend(dates);
+        } else if !is_completed && !is_recurring && subtasks > 0 && open_subtasks == 0 && rules.complete_parent {
+            let date = format_day(date);
+            conn.execute("UPDATE tasks SET is_completed = 1 WHERE id = ?1", [task_id])?;
+            conn.execute(
+                "INSERT OR IGNORE INTO task_completions (task_id, date, source) VALUES (?1, ?2, ?3)",
+                (task_id, &date, COMPLETION_SOURCE_SUBTASKS),
+            )?;
+            changes.task_ids.push(task_id);
+            changes.days.push(date);
+        }
+
+        current = parent_id;
+    }
+
+    Ok(())
+}
+
+fn completion_dates(conn: &rusqlite::Connection, task_id: i64, source: &str) -> AppResult<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT date FROM task_completions WHERE task_id = ?1 AND source = ?2")?;
+    let dates = stmt
+        .query_map((task_id, source), |row| row.get(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(dates)
+}
+
+// A series that had no due date, and so no anchor, counts from `anchor`
+// from now on
+fn roll_recurring_task(
+    conn: &rusqlite::Connection,
+    id: i64,
+    next_due: &str,
+    anchor: &str,
+    changes: &mut CompletionChanges,
+) -> AppResult<()> {
+    conn.execute(
+        "UPDATE tasks SET is_completed = 0, due_date = ?1, recurrence_anchor = IFNULL(recurrence_anchor, ?2)
+         WHERE id = ?3",
+        (next_due, anchor, id),
+    )?;
+
+    // The subtask tree carries over to the next occurrence, unchecked
+    reset_subtasks(conn, id, changes)
+}
+
+fn assigned_dates(conn: &rusqlite::Connection, task_id: i64) -> AppResult<Vec<String>> {
+    let mut stmt = conn
+        .prepare("SELECT date FROM task_assignments WHERE task_id = ?1")?;
+
+    let dates = stmt
+        .query_map([task_id], |row| row.get(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(dates)
+}
+
+// IDs of the tasks below `parent_id`, at any depth, that are (not) completed
+fn subtree_ids(conn: &rusqlite::Connection, parent_id: i64, is_completed: bool) -> AppResult<Vec<i64>> {
+    let mut stmt = conn.prepare(
+        "WITH RECURSIVE subtree(id) AS (
+            SELECT id FROM tasks WHERE parent_id = ?1 AND deleted_at IS NULL
+            UNION ALL
+            SELECT t.id FROM tasks t JOIN subtree s ON t.parent_id = s.id
+            WHERE t.deleted_at IS NULL
+         )
+         SELECT id FROM tasks WHERE id IN (SELECT id FROM subtree) AND is_completed = ?2",
+    )?;
+    let ids = stmt
+        .query_map((parent_id, is_completed), |row| row.get(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(ids)
+}
+
+fn complete_subtasks(
+    conn: &rusqlite::Connection,
+    parent_id: i64,
+    date: &str,
+    changes: &mut CompletionChanges,
+) -> AppResult<()> {
+    changes.task_ids.extend(subtree_ids(conn, parent_id, false)?);
+
+    let subtree = "WITH RECURSIVE subtree(id) AS (
+                       SELECT id FROM tasks WHERE parent_id = ?1 AND deleted_at IS NULL
+                       UNION ALL
+                       SELECT t.id FROM tasks t JOIN subtree s ON t.parent_id = s.id
+                       WHERE t.deleted_at IS NULL
+                   )";
+    conn.execute(
+        &format!("{} UPDATE tasks SET is_completed = 1 WHERE id IN (SELECT id FROM subtree)", subtree),
+        [parent_id],
+    )?;
+    conn.execute(
+        &format!(
+            "{} INSERT OR IGNORE INTO task_completions (task_id, date, source)
+                SELECT id, ?2, ?3 FROM subtree",
+            subtree
+        ),
+        (parent_id, date, COMPLETION_SOURCE_PARENT),
+    )?;
+
+    Ok(())
+}
+
+// Reopens the closed tasks below `parent_id`. Only the completions they got
+// from a parent are removed; days they were checked off on their own stay.
+fn reopen_subtasks(conn: &rusqlite::Connection, parent_id: i64, changes: &mut CompletionChanges) -> AppResult<()> {
+    for id in subtree_ids(conn, parent_id, true)? {
+        let dates = completion_dates(conn, id, COMPLETION_SOURCE_PARENT)?;
+        conn.execute(
+            "DELETE FROM task_completions WHERE task_id = ?1 AND source = ?2",
+            (id, COMPLETION_SOURCE_PARENT),
+        )?;
+        conn.execute("UPDATE tasks SET is_completed = 0 WHERE id = ?1", [id])?;
+        changes.task_ids.push(id);
+        changes.days.extend(dates);
+    }
+    Ok(())
+}
+
+fn reset_subtasks(conn: &rusqlite::Connection, parent_id: i64, changes: &mut CompletionChanges) -> AppResult<()> {
+    changes.task_ids.extend(subtree_ids(conn, parent_id, true)?);
+
+    conn.execute(
+        "WITH RECURSIVE subtree(id) AS (
+            SELECT id FROM tasks WHERE parent_id = ?1 AND deleted_at IS NULL
+            UNION ALL
+            SELECT t.id FROM tasks t JOIN subtree s ON t.parent_id = s.id
+            WHERE t.deleted_at IS NULL
+         )
+         UPDATE tasks SET is_completed = 0 WHERE id IN (SELECT id FROM subtree)",
+        [parent_id],
+    )?;
+
+    Ok(())
+}
+
+// Trash Commands
+
+const TRASH_RETENTION_SETTING: &str = "trash_retention_days";
+const DEFAULT_TRASH_RETENTION_DAYS: u32 = 30;
+const MAX_TRASH_RETENTION_DAYS: u32 = 3650;
+
+/// Tasks in the trash, most recently deleted first. Subtasks deleted along
+/// with their parent are counted under it rather than listed.
+#[tauri::command]
+pub fn get_trash(state: State<DbState>) -> AppResult<Vec<TrashedTask>> {
+    let conn = state.conn.lock()?;
+    let mut stmt = conn.prepare(&format!(
+        // The parent's title only if the task would go back under it
+        "SELECT {}, t.deleted_at, CASE WHEN p.deleted_at IS NULL THEN p.title END
+         FROM tasks t
+         LEFT JOIN tasks p ON p.id = t.parent_id
+         WHERE t.deleted_at IS NOT NULL AND (p.id IS NULL OR p.deleted_at IS NOT t.deleted_at)
+         ORDER BY t.deleted_at DESC, t.id",
+        prefixed_task_columns("t")
+    ))?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                task_from_row(row)?,
+                row.get::<_, String>(13)?,
+                row.get::<_, Option<String>>(14)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut tags = load_tags_by_task(&conn, &json_id_list(rows.iter().map(|(task, _, _)| task.id)))?;
+    let mut trash = Vec::with_capacity(rows.len());
+    for (mut task, deleted_at, restores_under) in rows {
+        task.tags = tags.remove(&task.id).unwrap_or_default();
+        let subtask_count = trash_batch_ids(&conn, task.id, &deleted_at)?.len() as i64 - 1;
+        trash.push(TrashedTask {
+            task,
+            deleted_at,
+            subtask_count,
+            restores_under,
+        });
+    }
+
+    Ok(trash)
+}
+
+/// Brings a task back from the trash with the subtasks deleted along with it,
+/// and their tracker assignments. It returns under its old parent, unless
+/// that parent is still in the trash, in which case it becomes a top-level
+/// task.
+#[tauri::command]
+pub fn restore_task(state: State<DbState>, id: i64) -> AppResult<RestoreTaskResult> {
+    journaled(&state, "Restore task", |tx| {
+        let (parent_id, deleted_at): (Option<i64>, Option<String>) = tx
+            .query_row("SELECT parent_id, deleted_at FROM tasks WHERE id = ?1", [id], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .optional()?
+            .ok_or_else(|| AppError::NotFound(format!("Task {} not found", id)))?;
+        let deleted_at = deleted_at
+            .ok_or_else(|| AppError::Validation(format!("Task {} is not in the trash", id)))?;
+
+        let restored_task_ids = trash_batch_ids(tx, id, &deleted_at)?;
+        let ids = json_id_list(restored_task_ids.iter().copied());
+        tx.execute(
+            "UPDATE tasks SET deleted_at = NULL WHERE id IN (SELECT value FROM json_each(?1))",
+            [&ids],
+        )?;
+
+        let parent_id = match parent_id {
+            Some(parent_id) if !is_trashed(tx, Some(parent_id))? => {
+                // Subtasks always live in their parent's project
+                tx.execute(
+                    "UPDATE tasks SET project_id = (SELECT project_id FROM tasks WHERE id = ?2)
+                     WHERE id IN (SELECT value FROM json_each(?1))",
+                    (&ids, parent_id),
+                )?;
+                Some(parent_id)
+            }
+            Some(_) => {
+                let position = sibling_position(tx, None, Some(id), Placement::First)?;
+                tx.execute(
+                    "UPDATE tasks SET parent_id = NULL, position = ?1 WHERE id = ?2",
+                    (&position, id),
+                )?;
+                None
+            }
+            None => None,
+        };
+
+        let mut changes = CompletionChanges::default();
+        let mut stmt = tx.prepare(
+            "SELECT DISTINCT date FROM trashed_assignments WHERE task_id IN (SELECT value FROM json_each(?1))",
+        )?;
+        changes.days = stmt
+            .query_map([&ids], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        drop(stmt);
+        let restored_assignments = tx.execute(
+            "INSERT OR IGNORE INTO task_assignments (task_id, date, assigned_by, moved_from, moved_by, moved_at)
+             SELECT task_id, date, assigned_by, moved_from, moved_by, moved_at
+             FROM trashed_assignments
+             WHERE task_id IN (SELECT value FROM json_each(?1))",
+            [&ids],
+        )? as i64;
+        tx.execute(
+            "DELETE FROM trashed_assignments WHERE task_id IN (SELECT value FROM json_each(?1))",
+            [&ids],
+        )?;
+
+        if let Some(parent_id) = parent_id {
+            let rules = load_completion_rules(tx)?;
+            sync_ancestor_completion(tx, parent_id, Local::now().date_naive(), &rules, &mut changes)?;
+        }
+        let (changed_task_ids, affected_days) = changes.finish(tx)?;
+
+        Ok(RestoreTaskResult {
+            task_id: id,
+            parent_id,
+            restored_task_ids,
+            restored_assignments,
+            changed_task_ids,
+            affected_days,
+        })
+    })
+}
+
+/// Permanently deletes a task in the trash, with everything below it.
+#[tauri::command]
+pub fn purge_task(state: State<DbState>, id: i64) -> AppResult<()> {
+    journaled(&state, "Delete task for good", |conn| {
+        let deleted_at: Option<String> = conn
+            .query_row("SELECT deleted_at FROM tasks WHERE id = ?1", [id], |row| row.get(0))
+            .optional()?
+            .ok_or_else(|| AppError::NotFound(format!("Task {} not found", id)))?;
+        if deleted_at.is_none() {
+            return Err(AppError::Validation(
+                "Only tasks in the trash can be deleted permanently".to_string(),
+            ));
+        }
+
+        conn.execute("DELETE FROM tasks WHERE id = ?1", [id])?;
+        Ok(())
+    })
+}
+
+/// Permanently deletes everything in the trash and returns how many tasks
+/// that was.
+#[tauri::command]
+pub fn empty_trash(state: State<DbState>) -> AppResult<i64> {
+    journaled(&state, "Empty trash", |conn| purge_trash_before(conn, None))
+}
+
+/// Days a task stays in the trash before it is purged; 0 keeps it until the
+/// trash is emptied by hand.
+#[tauri::command]
+pub fn get_trash_retention(state: State<DbState>) -> AppResult<u32> {
+    let conn = state.conn.lock()?;
+    trash_retention(&conn)
+}
+
+/// Sets the retention period and purges what is already past it.
+#[tauri::command]
+pub fn set_trash_retention(state: State<DbState>, days: u32) -> AppResult<()> {
+    if days > MAX_TRASH_RETENTION_DAYS {
+        return Err(AppError::Validation(format!(
+            "Tasks can be kept in the trash for at most {} days",
+            MAX_TRASH_RETENTION_DAYS
+        )));
+    }
+    journaled(&state, "Change trash retention", |conn| {
+        database::set_setting(conn, TRASH_RETENTION_SETTING, &days.to_string())?;
+        purge_expired(conn)?;
+        Ok(())
+    })
+}
+
+/// Purges tasks that have been in the trash longer than the retention period,
+/// as an undo step of its own. Runs on startup.
+pub(crate) fn purge_expired_trash(conn: &mut rusqlite::Connection) -> AppResult<i64> {
+    run_journaled(conn, "Purge expired trash", |tx| purge_expired(tx))
+}
+
+fn purge_expired(conn: &rusqlite::Connection) -> AppResult<i64> {
+    match trash_retention(conn)? {
+        0 => Ok(0),
+        days => purge_trash_before(conn, Some(&format!("-{} days", days))),
+    }
+}
+
+fn trash_retention(conn: &rusqlite::Connection) -> AppResult<u32> {
+    let stored = database::get_setting(conn, TRASH_RETENTION_SETTING)?;
+    Ok(stored
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_TRASH_RETENTION_DAYS))
+}
+
+// Deletes the tasks trashed before now shifted by `age` (an SQLite date
+// modifier), or all of them. Their subtasks are in the trash too, and go
+// with them.
+fn purge_trash_before(conn: &rusqlite::Connection, age: Option<&str>) -> AppResult<i64> {
+    let condition = "deleted_at IS NOT NULL
+                     AND (?1 IS NULL OR deleted_at < strftime('%Y-%m-%d %H:%M:%f', 'now', ?1))";
+    let count: i64 = conn.query_row(
+        &format!("SELECT COUNT(*) FROM tasks WHERE {}", condition),
+        [age],
+        |row| row.get(0),
+    )?;
+    conn.execute(&format!("DELETE FROM tasks WHERE {}", condition), [age])?;
+    Ok(count)
+}
+
+// `id` and the tasks below it that went to the trash at the same time
+fn trash_batch_ids(conn: &rusqlite::Connection, id: i64, deleted_at: &str) -> AppResult<Vec<i64>> {
+    let mut stmt = conn.prepare(
+        "WITH RECURSIVE batch(id) AS (
+            SELECT ?1
+            UNION ALL
+            SELECT t.id FROM tasks t JOIN batch b ON t.parent_id = b.id
+            WHERE t.deleted_at = ?2
+         )
+         SELECT id FROM batch",
+    )?;
+    let ids = stmt
+        .query_map((id, deleted_at), |row| row.get(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(ids)
+}
+
+fn is_trashed(conn: &rusqlite::Connection, id: Option<i64>) -> AppResult<bool> {
+    let Some(id) = id else {
+        return Ok(false);
+    };
+    let trashed = conn
+        .query_row("SELECT deleted_at IS NOT NULL FROM tasks WHERE id = ?1", [id], |row| row.get(0))
+        .optional()?;
+    Ok(trashed.unwrap_or(false))
+}
+
+// Search Commands
+
+const DEFAULT_SEARCH_LIMIT: u32 = 50;
+const MAX_SEARCH_LIMIT: u32 = 500;
+// Matches in a title count for more than matches in a description
+const SEARCH_TITLE_WEIGHT: f64 = 10.0;
+const SEARCH_DESCRIPTION_WEIGHT: f64 = 1.0;
+// Tokens of description shown around the matches
+const SEARCH_SNIPPET_TOKENS: i32 = 16;
+
+/// Tasks whose title or description matches `query`, best match first. See
+/// `search.rs` for what the query can contain; one without any words finds
+/// nothing. Subtasks are found on their own, with the path down to them.
+/// Archived projects are only searched when asked for by `project_id`.
+#[tauri::command]
+pub fn search_tasks(
+    state: State<DbState>,
+    query: String,
+    project_id: Option<i64>,
+    include_completed: Option<bool>,
+    limit: Option<u32>,
+) -> AppResult<Vec<TaskSearchResult>> {
+    let limit = limit.unwrap_or(DEFAULT_SEARCH_LIMIT);
+    if limit == 0 || limit > MAX_SEARCH_LIMIT {
+        return Err(AppError::Validation(format!(
+            "Search limit must be between 1 and {}",
+            MAX_SEARCH_LIMIT
+        )));
+    }
+    let Some(expression) = search::match_expression(&query) else {
+        return Ok(Vec::new());
+    };
+
+    let conn = state.conn.lock()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}, m.marked_title, m.marked_description
+         FROM (
+            SELECT rowid,
+                   highlight(tasks_fts, 0, ?2, ?3) AS marked_title,
+                   snippet(tasks_fts, 1, ?2, ?3, '…', ?4) AS marked_description,
+                   bm25(tasks_fts, ?5, ?6) AS score
+            FROM tasks_fts
+            WHERE tasks_fts MATCH ?1
+         ) m
+         JOIN tasks ON tasks.id = m.rowid
+         WHERE deleted_at IS NULL
+           AND (?7 IS NULL OR project_id = ?7)
+           AND (?7 IS NOT NULL OR project_id IS NULL
+                OR project_id NOT IN (SELECT id FROM projects WHERE is_archived = 1))
+           AND (?8 OR is_completed = 0)
+         ORDER BY m.score, tasks.id
+         LIMIT ?9",
+        TASK_COLUMNS
+    ))?;
+    let rows = stmt
+        .query_map(
+            rusqlite::params![
+                expression,
+                search::MATCH_START,
+                search::MATCH_END,
+                SEARCH_SNIPPET_TOKENS,
+                SEARCH_TITLE_WEIGHT,
+                SEARCH_DESCRIPTION_WEIGHT,
+                project_id,
+                include_completed.unwrap_or(true),
+                limit,
+            ],
+            |row| {
+                Ok((
+                    task_from_row(row)?,
+                    row.get::<_, String>(13)?,
+                    row.get::<_, Option<String>>(14)?,
+                ))
+            },
+        )?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let ids = json_id_list(rows.iter().map(|(task, _, _)| task.id));
+    let mut tags = load_tags_by_task(&conn, &ids)?;
+    let mut progress = load_progress_by_task(&conn, &ids)?;
+    let ancestors = load_ancestors(&conn, &ids)?;
+
+    let mut results = Vec::with_capacity(rows.len());
+    for (mut task, title, description) in rows {
+        task.tags = tags.remove(&task.id).unwrap_or_default();
+        task.progress = progress.remove(&task.id).unwrap_or_default();
+
+        let mut path = Vec::new();
+        let mut next = task.parent_id;
+        while let Some((id, (parent_id, title))) = next.and_then(|id| ancestors.get_key_value(&id)) {
+            // A damaged tree with a cycle would otherwise loop forever
+            if path.len() > ancestors.len() {
+                break;
+            }
+            path.push(TaskPathEntry {
+                id: *id,
+                title: title.clone(),
+            });
+            next = *parent_id;
+        }
+        path.reverse();
+
+        results.push(TaskSearchResult {
+            task,
+            title: search::text_parts(&title),
+            description: description.as_deref().map(search::text_parts).unwrap_or_default(),
+            path,
+        });
+    }
+
+    Ok(results)
+}
+
+// Parent and title of every ancestor of the tasks in `task_ids` (a JSON array)
+fn load_ancestors(
+    conn: &rusqlite::Connection,
+    task_ids: &str,
+) -> AppResult<HashMap<i64, (Option<i64>, String)>> {
+    let mut stmt = conn.prepare(
+        "WITH RECURSIVE ancestors(id) AS (
+            SELECT parent_id FROM tasks
+            WHERE id IN (SELECT value FROM json_each(?1)) AND parent_id IS NOT NULL
+            UNION
+            SELECT t.parent_id FROM tasks t JOIN ancestors a ON t.id = a.id
+            WHERE t.parent_id IS NOT NULL
+         )
+         SELECT id, parent_id, title FROM tasks WHERE id IN (SELECT id FROM ancestors)",
+    )?;
+
+    let ancestors = stmt
+        .query_map([task_ids], |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?))))?
+        .collect::<Result<HashMap<_, _>, _>>()?;
+
+    Ok(ancestors)
+}
+
+// Saved View Commands
+
+const MAX_VIEW_NAME_LENGTH: usize = 80;
+const MAX_FILTER_LENGTH: usize = 1000;
+
+/// Tasks at any level that match `filter`, written in the language described
+/// in `filters.rs`. Archived projects are left out unless the filter names a
+/// project.
+#[tauri::command]
+pub fn filter_tasks(state: State<DbState>, filter: String, sort_by: Option<TaskSort>) -> AppResult<Vec<Task>> {
+    let filter = parse_filter(&filter)?;
+    let conn = state.conn.lock()?;
+    load_filtered_tasks(&conn, &filter, sort_by)
+}
+
+#[tauri::command]
+pub fn get_saved_views(state: State<DbState>) -> AppResult<Vec<SavedView>> {
+    let conn = state.conn.lock()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM saved_views ORDER BY name COLLATE NOCASE",
+        VIEW_COLUMNS
+    ))?;
+    let views = stmt
+        .query_map([], view_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(views)
+}
+
+#[tauri::command]
+pub fn create_saved_view(
+    state: State<DbState>,
+    name: String,
+    filter: String,
+    sort_by: Option<TaskSort>,
+) -> AppResult<SavedView> {
+    let name = validate_view_name(&name)?;
+    parse_filter(&filter)?;
+
+    journaled(&state, "Save view", |conn| {
+        ensure_view_name_free(conn, &name, None)?;
+
+        conn.execute(
+            "INSERT INTO saved_views (name, filter, sort_by) VALUES (?1, ?2, ?3)",
+            (&name, filter.trim(), sort_by.map(task_sort_name)),
+        )?;
+
+        load_view(conn, conn.last_insert_rowid())
+    })
+}
+
+/// Changes a view's name, filter or sort order; fields left out stay as they are.
+#[tauri::command]
+pub fn update_saved_view(
+    state: State<DbState>,
+    id: i64,
+    name: Option<String>,
+    filter: Option<String>,
+    sort_by: Option<TaskSort>,
+    clear_sort_by: Option<bool>,
+) -> AppResult<SavedView> {
+    let name = name.as_deref().map(validate_view_name).transpose()?;
+    if let Some(filter) = &filter {
+        parse_filter(filter)?;
+    }
+    let clear_sort_by = clear_sort_by.unwrap_or(false);
+    if clear_sort_by && sort_by.is_some() {
+        return Err(AppError::Validation(
+            "A view's sort order can't be both changed and cleared".to_string(),
+        ));
+    }
+
+    journaled(&state, "Edit view", |conn| {
+        load_view(conn, id)?;
+        if let Some(name) = &name {
+            ensure_view_name_free(conn, name, Some(id))?;
+        }
+
+        conn.execute(
+            "UPDATE saved_views SET
+                name = COALESCE(?1, name),
+                filter = COALESCE(?2, filter),
+                sort_by = CASE WHEN ?5 THEN NULL ELSE COALESCE(?3, sort_by) END
+             WHERE id = ?4",
+            (&name, filter.as_deref().map(str::trim), sort_by.map(task_sort_name), id, clear_sort_by),
+        )?;
+
+        load_view(conn, id)
+    })
+}
+
+#[tauri::command]
+pub fn delete_saved_view(state: State<DbState>, id: i64) -> AppResult<()> {
+    journaled(&state, "Delete view", |conn| {
+        if conn.execute("DELETE FROM saved_views WHERE id = ?1", [id])? == 0 {
+            return Err(AppError::NotFound(format!("Saved view {} not found", id)));
+        }
+        Ok(())
+    })
+}
+
+/// The tasks a saved view shows right now; relative dates like `today` are
+/// resolved on every run.
+#[tauri::command]
+pub fn run_saved_view(state: State<DbState>, id: i64) -> AppResult<Vec<Task>> {
+    let conn = state.conn.lock()?;
+
+    let view = load_view(&conn, id)?;
+    let filter = parse_filter(&view.filter)?;
+    load_filtered_tasks(&conn, &filter, view.sort_by)
+}
+
+// Flat, without subtasks, but with each task's progress over its subtree
+fn load_filtered_tasks(
+    conn: &rusqlite::Connection,
+    filter: &Filter,
+    sort_by: Option<TaskSort>,
+) -> AppResult<Vec<Task>> {
+    let (condition, params) = filter.to_sql(Local::now().date_naive());
+
+    let mut sql = format!("SELECT {} FROM tasks t WHERE t.deleted_at IS NULL AND ({})", TASK_COLUMNS, condition);
+    if !filter.names_project() {
+        sql.push_str(" AND (t.project_id IS NULL OR t.project_id NOT IN (SELECT id FROM projects WHERE is_archived = 1))");
+    }
+    sql.push_str(" ORDER BY ");
+    sql.push_str(&task_order_by(&TaskQuery {
+        sort_by: Some(sort_by.unwrap_or(TaskSort::DueDate)),
+        ..TaskQuery::default()
+    }));
+
+    let mut stmt = conn.prepare(&sql)?;
+    let mut tasks = stmt
+        .query_map(rusqlite::params_from_iter(params), task_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let ids = json_id_list(tasks.iter().map(|task| task.id));
+    let mut tags = load_tags_by_task(conn, &ids)?;
+    let mut progress = load_progress_by_task(conn, &ids)?;
+    for task in &mut tasks {
+        task.tags = tags.remove(&task.id).unwrap_or_default();
+        task.progress = progress.remove(&task.id).unwrap_or_default();
+    }
+
+    Ok(tasks)
+}
+
+fn parse_filter(filter: &str) -> AppResult<Filter> {
+    if filter.chars().count() > MAX_FILTER_LENGTH {
+        return Err(AppError::Validation(format!(
+            "Filters are limited to {} characters",
+            MAX_FILTER_LENGTH
+        )));
+    }
+    filters::parse(filter).map_err(AppError::Validation)
+}
+
+// Column order expected by `view_from_row`
+const VIEW_COLUMNS: &str = "id, name, filter, sort_by";
+
+fn view_from_row(row: &rusqlite::Row) -> rusqlite::Result<SavedView> {
+    let sort_by: Option<String> = row.get(3)?;
+    Ok(SavedView {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        filter: row.get(2)?,
+        sort_by: sort_by.as_deref().and_then(parse_task_sort),
+    })
+}
+
+fn load_view(conn: &rusqlite::Connection, id: i64) -> AppResult<SavedView> {
+    conn.query_row(
+        &format!("SELECT {} FROM saved_views WHERE id = ?1", VIEW_COLUMNS),
+        [id],
+        view_from_row,
+    )
+    .optional()?
+    .ok_or_else(|| AppError::NotFound(format!("Saved view {} not found", id)))
+}
+
+fn validate_view_name(name: &str) -> AppResult<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::Validation("View name cannot be empty".to_string()));
+    }
+    if name.chars().count() > MAX_VIEW_NAME_LENGTH {
+        return Err(AppError::Validation(format!(
+            "View names are limited to {} characters",
+            MAX_VIEW_NAME_LENGTH
+        )));
+    }
+    Ok(name.to_string())
+}
+
+fn ensure_view_name_free(conn: &rusqlite::Connection, name: &str, except_id: Option<i64>) -> AppResult<()> {
+    let existing: Option<i64> = conn
+        .query_row(
+            "SELECT id FROM saved_views WHERE name = ?1 COLLATE NOCASE",
+            [name],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    match existing {
+        Some(id) if Some(id) != except_id => Err(AppError::Conflict(format!(
+            "A view named '{}' already exists",
+            name
+        ))),
+        _ => Ok(()),
+    }
+}
+
+// Project Commands
+
+const MAX_PROJECT_NAME_LENGTH: usize = 80;
+
+/// Projects in their saved order. Archived ones are left out unless asked for.
+#[tauri::command]
+pub fn get_projects(state: State<DbState>, include_archived: Option<bool>) -> AppResult<Vec<Project>> {
+    let conn = state.conn.lock()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM projects WHERE ?1 OR is_archived = 0 ORDER BY position, id",
+        PROJECT_COLUMNS
+    ))?;
+
+    let projects = stmt
+        .query_map([include_archived.unwrap_or(false)], project_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(projects)
+}
+
+#[tauri::command]
+pub fn create_project(state: State<DbState>, name: String, color: Option<String>) -> AppResult<Project> {
+    let name = validate_project_name(&name)?;
+    let color = color.unwrap_or_else(|| DEFAULT_TAG_COLOR.to_string());
+    validate_tag_color(&color)?;
+
+    journaled(&state, "Create project", |conn| {
+        ensure_project_name_free(conn, &name, None)?;
+
+        conn.execute(
+            "INSERT INTO projects (name, color, position)
+             VALUES (?1, ?2, (SELECT COALESCE(MAX(position), -1) + 1 FROM projects))",
+            (&name, &color),
+        )?;
+
+        load_project(conn, conn.last_insert_rowid())
+    })
+}
+
+/// Changes a project's name, color or defaults; fields left out stay as they are.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub fn update_project(
+    state: State<DbState>,
+    id: i64,
+    name: Option<String>,
+    color: Option<String>,
+    default_priority: Option<i32>,
+    default_estimated_minutes: Option<i32>,
+    sort_by: Option<TaskSort>,
+    clear: Option<Vec<ProjectField>>,
+) -> AppResult<Project> {
+    let name = name.as_deref().map(validate_project_name).transpose()?;
+    let clear = clear.unwrap_or_default();
+    let set = [
+        (ProjectField::Color, color.is_some()),
+        (ProjectField::DefaultEstimatedMinutes, default_estimated_minutes.is_some()),
+        (ProjectField::SortBy, sort_by.is_some()),
+    ];
+    if set.iter().any(|(field, given)| *given && clear.contains(field)) {
+        return Err(AppError::Validation(
+            "A project setting can't be both changed and cleared".to_string(),
+        ));
+    }
+    // The color can't be empty, so clearing it goes back to the default
+    let color = if clear.contains(&ProjectField::Color) {
+        Some(DEFAULT_TAG_COLOR.to_string())
+    } else {
+        color
+    };
+    if let Some(color) = &color {
+        validate_tag_color(color)?;
+    }
+    validate_effort(default_priority, default_estimated_minutes, None)?;
+
+    journaled(&state, "Edit project", |conn| {
+        load_project(conn, id)?;
+        if let Some(name) = &name {
+            ensure_project_name_free(conn, name, Some(id))?;
+        }
+
+        conn.execute(
+            "UPDATE projects SET
+                name = COALESCE(?1, name),
+                color = COALESCE(?2, color),
+                default_priority = COALESCE(?3, default_priority),
+                default_estimated_minutes = CASE WHEN ?7 THEN NULL
+                    ELSE COALESCE(?4, default_estimated_minutes) END,
+                sort_by = CASE WHEN ?8 THEN NULL ELSE COALESCE(?5, sort_by) END
+             WHERE id = ?6",
+            (
+                &name,
+                &color,
+                &default_priority,
+                &default_estimated_minutes,
+                sort_by.map(task_sort_name),
+                id,
+                clear.contains(&ProjectField::DefaultEstimatedMinutes),
+                clear.contains(&ProjectField::SortBy),
+            ),
+        )?;
+
+        load_project(conn, id)
+    })
+}
+
+#[tauri::command]
+pub fn set_project_archived(state: State<DbState>, id: i64, archived: bool) -> AppResult<()> {
+    journaled(&state, if archived { "Archive project" } else { "Unarchive project" }, |conn| {
+        load_project(conn, id)?;
+
+        conn.execute("UPDATE projects SET is_archived = ?1 WHERE id = ?2", (archived, id))?;
+        Ok(())
+    })
+}
+
+/// Saves the order of the projects; `ids` must list every project exactly once.
+#[tauri::command]
+pub fn reorder_projects(state: State<DbState>, ids: Vec<i64>) -> AppResult<()> {
+    journaled(&state, "Reorder projects", |tx| {
+        let count: i64 = tx.query_row("SELECT COUNT(*) FROM projects", [], |row| row.get(0))?;
+        let mut unique = ids.clone();
+        unique.sort_unstable();
+        unique.dedup();
+        if unique.len() != ids.len() || ids.len() as i64 != count {
+            return Err(AppError::Validation(
+                "The new order must list every project exactly once".to_string(),
+            ));
+        }
+
+        for (position, id) in ids.iter().enumerate() {
+            let updated = tx.execute(
+                "UPDATE projects SET position = ?1 WHERE id = ?2",
+                (position as i64, id),
+            )?;
+            if updated == 0 {
+                return Err(AppError::NotFound(format!("Project {} not found", id)));
+            }
+        }
+
+        Ok(())
+    })
+}
+
+/// Deletes a project. Its tasks are kept and end up without a project.
+#[tauri::command]
+pub fn delete_project(state: State<DbState>, id: i64) -> AppResult<()> {
+    journaled(&state, "Delete project", |conn| {
+        if conn.execute("DELETE FROM projects WHERE id = ?1", [id])? == 0 {
+            return Err(AppError::NotFound(format!("Project {} not found", id)));
+        }
+        Ok(())
+    })
+}
+
+/// Moves a top-level task, with all its subtasks, into a project (or out of
+/// any with `None`).
+#[tauri::command]
+pub fn move_task_to_project(state: State<DbState>, task_id: i64, project_id: Option<i64>) -> AppResult<()> {
+    journaled(&state, "Move task to project", |conn| {
+        let parent_id: Option<i64> = conn
+            .query_row("SELECT parent_id FROM tasks WHERE id = ?1 AND deleted_at IS NULL", [task_id], |row| row.get(0))
+            .optional()?
+            .ok_or_else(|| AppError::NotFound(format!("Task {} not found", task_id)))?;
+        if parent_id.is_some() {
+            return Err(AppError::Validation(
+                "Subtasks belong to their parent's project; move the parent instead".to_string(),
+            ));
+        }
+        if let Some(project_id) = project_id {
+            load_project(conn, project_id)?;
+        }
+
+        conn.execute(
+            "WITH RECURSIVE subtree(id) AS (
+                SELECT ?1
+                UNION ALL
+                SELECT t.id FROM tasks t JOIN subtree s ON t.parent_id = s.id
+             )
+             UPDATE tasks SET project_id = ?2 WHERE id IN (SELECT id FROM subtree)",
+            (task_id, project_id),
+        )?;
+
+        Ok(())
+    })
+}
+
+// Column order expected by `project_from_row`
+const PROJECT_COLUMNS: &str = "id, name, color, position, is_archived, default_priority, \
+                               default_estimated_minutes, sort_by, \
+                               (SELECT COUNT(*) FROM tasks t \
+                                WHERE t.project_id = projects.id AND t.parent_id IS NULL AND t.is_completed = 0 \
+                                  AND t.deleted_at IS NULL)";
+
+fn project_from_row(row: &rusqlite::Row) -> rusqlite::Result<Project> {
+    let sort_by: Option<String> = row.get(7)?;
+    Ok(Project {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        color: row.get(2)?,
+        position: row.get(3)?,
+        is_archived: row.get(4)?,
+        default_priority: row.get(5)?,
+        default_estimated_minutes: row.get(6)?,
+        sort_by: sort_by.as_deref().and_then(parse_task_sort),
+        open_task_count: row.get(8)?,
+    })
+}
+
+fn load_project(conn: &rusqlite::Connection, id: i64) -> AppResult<Project> {
+    conn.query_row(
+        &format!("SELECT {} FROM projects WHERE id = ?1", PROJECT_COLUMNS),
+        [id],
+        project_from_row,
+    )
+    .optional()?
+    .ok_or_else(|| AppError::NotFound(format!("Project {} not found", id)))
+}
+
+fn project_sort(conn: &rusqlite::Connection, id: i64) -> AppResult<Option<TaskSort>> {
+    Ok(load_project(conn, id)?.sort_by)
+}
+
+fn validate_project_name(name: &str) -> AppResult<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::Validation("Project name cannot be empty".to_string()));
+    }
+    if name.chars().count() > MAX_PROJECT_NAME_LENGTH {
+        return Err(AppError::Validation(format!(
+            "Project names are limited to {} characters",
+            MAX_PROJECT_NAME_LENGTH
+        )));
+    }
+    Ok(name.to_string())
+}
+
+fn ensure_project_name_free(conn: &rusqlite::Connection, name: &str, except_id: Option<i64>) -> AppResult<()> {
+    let existing: Option<i64> = conn
+        .query_row(
+            "SELECT id FROM projects WHERE name = ?1 COLLATE NOCASE",
+            [name],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    match existing {
+        Some(id) if Some(id) != except_id => Err(AppError::Conflict(format!(
+            "A project named '{}' already exists",
+            name
+        ))),
+        _ => Ok(()),
+    }
+}
+
+// Stored the same way serde spells them
+fn task_sort_name(sort: TaskSort) -> &'static str {
+    match sort {
+        TaskSort::Manual => "manual",
+        TaskSort::CreatedAt => "created_at",
+        TaskSort::DueDate => "due_date",
+        TaskSort::Priority => "priority",
+        TaskSort::Difficulty => "difficulty",
+        TaskSort::Effort => "effort",
+        TaskSort::Title => "title",
+    }
+}
+
+fn parse_task_sort(name: &str) -> Option<TaskSort> {
+    [
+        TaskSort::Manual,
+        TaskSort::CreatedAt,
+        TaskSort::DueDate,
+        TaskSort::Priority,
+        TaskSort::Difficulty,
+        TaskSort::Effort,
+        TaskSort::Title,
+    ]
+    .into_iter()
+    .find(|sort| task_sort_name(*sort) == name)
+}
+
+// Tag Commands
+
+const DEFAULT_TAG_COLOR: &str = "#6b7280";
+const MAX_TAG_NAME_LENGTH: usize = 50;
+
+#[tauri::command]
+pub fn get_all_tags(state: State<DbState>) -> AppResult<Vec<Tag>> {
+    let conn = state.conn.lock()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT tg.id, tg.name, tg.color, COUNT(tt.task_id)
+         FROM tags tg
+         LEFT JOIN task_tags tt ON tt.tag_id = tg.id
+         GROUP BY tg.id
+         ORDER BY tg.name COLLATE NOCASE",
+    )?;
+
+    let tags = stmt
+        .query_map([], |row| {
+            Ok(Tag {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                color: row.get(2)?,
+                task_count: row.get(3)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(tags)
+}
+
+#[tauri::command]
+pub fn create_tag(state: State<DbState>, name: String, color: Option<String>) -> AppResult<Tag> {
+    let name = validate_tag_name(&name)?;
+    let color = color.unwrap_or_else(|| DEFAULT_TAG_COLOR.to_string());
+    validate_tag_color(&color)?;
+
+    journaled(&state, "Create tag", |conn| {
+        ensure_tag_name_free(conn, &name, None)?;
+
+        conn.execute("INSERT INTO tags (name, color) VALUES (?1, ?2)", (&name, &color))?;
+
+        Ok(Tag {
+            id: conn.last_insert_rowid(),
+            name,
+            color,
+            task_count: 0,
+        })
+    })
+}
+
+#[tauri::command]
+pub fn rename_tag(state: State<DbState>, id: i64, name: String) -> AppResult<()> {
+    let name = validate_tag_name(&name)?;
+
+    journaled(&state, "Rename tag", |conn| {
+        ensure_tag_exists(conn, id)?;
+        ensure_tag_name_free(conn, &name, Some(id))?;
+
+        conn.execute("UPDATE tags SET name = ?1 WHERE id = ?2", (&name, id))?;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn set_tag_color(state: State<DbState>, id: i64, color: String) -> AppResult<()> {
+    validate_tag_color(&color)?;
+
+    journaled(&state, "Change tag color", |conn| {
+        ensure_tag_exists(conn, id)?;
+
+        conn.execute("UPDATE tags SET color = ?1 WHERE id = ?2", (&color, id))?;
+        Ok(())
+    })
+}
+
+/// Moves every task tagged `source_id` over to `target_id`, then deletes the source tag.
+#[tauri::command]
+pub fn merge_tags(state: State<DbState>, source_id: i64, target_id: i64) -> AppResult<()> {
+    if source_id == target_id {
+        return Err(AppError::Validation("Cannot merge a tag into itself".to_string()));
+    }
+
+    journaled(&state, "Merge tags", |tx| {
+        ensure_tag_exists(tx, source_id)?;
+        ensure_tag_exists(tx, target_id)?;
+
+        tx.execute(
+            "INSERT OR IGNORE INTO task_tags (task_id, tag_id)
+             SELECT task_id, ?2 FROM task_tags WHERE tag_id = ?1",
+            (source_id, target_id),
+        )?;
+        tx.execute("DELETE FROM tags WHERE id = ?1", [source_id])?;
+
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn delete_tag(state: State<DbState>, id: i64) -> AppResult<()> {
+    journaled(&state, "Delete tag", |conn| {
+        if conn.execute("DELETE FROM tags WHERE id = ?1", [id])? == 0 {
+            return Err(AppError::NotFound(format!("Tag {} not found", id)));
+        }
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn add_tag_to_task(state: State<DbState>, task_id: i64, tag_id: i64) -> AppResult<()> {
+    journaled(&state, "Tag task", |conn| {
+        ensure_task_exists(conn, task_id)?;
+        ensure_tag_exists(conn, tag_id)?;
+
+        conn.execute(
+            "INSERT OR IGNORE INTO task_tags (task_id, tag_id) VALUES (?1, ?2)",
+            (task_id, tag_id),
+        )?;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn remove_tag_from_task(state: State<DbState>, task_id: i64, tag_id: i64) -> AppResult<()> {
+    journaled(&state, "Untag task", |conn| {
+        conn.execute(
+            "DELETE FROM task_tags WHERE task_id = ?1 AND tag_id = ?2",
+            (task_id, tag_id),
+        )?;
+        Ok(())
+    })
+}
+
+fn validate_tag_name(name: &str) -> AppResult<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::Validation("Tag name cannot be empty".to_string()));
+    }
+    if name.chars().count() > MAX_TAG_NAME_LENGTH {
+        return Err(AppError::Validation(format!(
+            "Tag names are limited to {} characters",
+            MAX_TAG_NAME_LENGTH
+        )));
+    }
+    Ok(name.to_string())
+}
+
+// Colors are stored as `#rrggbb` so the frontend can use them directly
+fn validate_tag_color(color: &str) -> AppResult<()> {
+    let valid = color.len() == 7
+        && color.starts_with('#')
+        && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+    if !valid {
+        return Err(AppError::Validation(format!(
+            "'{}' is not a #rrggbb color",
+            color
+        )));
+    }
+    Ok(())
+}
+
+fn ensure_tag_exists(conn: &rusqlite::Connection, id: i64) -> AppResult<()> {
+    conn.query_row("SELECT 1 FROM tags WHERE id = ?1", [id], |_| Ok(()))
+        .optional()?
+        .ok_or_else(|| AppError::NotFound(format!("Tag {} not found", id)))
+}
+
+// Names are unique regardless of case
+fn ensure_tag_name_free(conn: &rusqlite::Connection, name: &str, except_id: Option<i64>) -> AppResult<()> {
+    let existing: Option<i64> = conn
+        .query_row(
+            "SELECT id FROM tags WHERE name = ?1 COLLATE NOCASE",
+            [name],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    match existing {
+        Some(id) if Some(id) != except_id => Err(AppError::Conflict(format!(
+            "A tag named '{}' already exists",
+            name
+        ))),
+        _ => Ok(()),
+    }
+}
+
+// Tags of every task in `task_ids` (a JSON array), by task
+fn load_tags_by_task(conn: &rusqlite::Connection, task_ids: &str) -> AppResult<HashMap<i64, Vec<Tag>>> {
+    let mut stmt = conn.prepare(
+        "SELECT tt.task_id, tg.id, tg.name, tg.color
+         FROM tags tg
+         JOIN task_tags tt ON tt.tag_id = tg.id
+         WHERE tt.task_id IN (SELECT value FROM json_each(?1))
+         ORDER BY tg.name COLLATE NOCASE",
+    )?;
+
+    let mut tags: HashMap<i64, Vec<Tag>> = HashMap::new();
+    let rows = stmt.query_map([task_ids], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            Tag {
+                id: row.get(1)?,
+                name: row.get(2)?,
+                color: row.get(3)?,
+                task_count: 0,
+            },
+        ))
+    })?;
+    for row in rows {
+        let (task_id, tag) = row?;
+        tags.entry(task_id).or_default().push(tag);
+    }
+
+    Ok(tags)
+}
+
+// Day Tracker Commands
+
+const TRACKER_WINDOW_DAYS: i64 = 60;
+const TRACKER_ANCHOR_SETTING: &str = "tracker_anchor_date";
+
+/// Makes sure the tracker window has a row for every day. The window starts at
+/// `start_date`, the configured anchor, or today, and rolls forward on its own;
+/// days that fall behind it are kept as history.
+#[tauri::command]
+pub fn initialize_60_days(state: State<DbState>, start_date: Option<String>) -> AppResult<()> {
+    let conn = state.conn.lock()?;
+    let start = window_start(&conn, start_date.as_deref())?;
+    ensure_days(&conn, start, TRACKER_WINDOW_DAYS)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_day_tracker_data(
+    state: State<DbState>,
+    start_date: Option<String>,
+    project_id: Option<i64>,
+) -> AppResult<Vec<DayTracker>> {
+    let conn = state.conn.lock()?;
+    let start = window_start(&conn, start_date.as_deref())?;
+    ensure_days(&conn, start, TRACKER_WINDOW_DAYS)?;
+    load_days(&conn, start, start + Duration::days(TRACKER_WINDOW_DAYS - 1), project_id)
+}
+
+/// Tracked days in `[start_date, end_date]`, numbered from `start_date`.
+#[tauri::command]
+pub fn get_tracker_history(
+    state: State<DbState>,
+    start_date: String,
+    end_date: String,
+    project_id: Option<i64>,
+) -> AppResult<Vec<DayTracker>> {
+    let start = parse_day(&start_date)?;
+    let end = parse_day(&end_date)?;
+    if end < start {
+        return Err(AppError::Validation("end_date is before start_date".to_string()));
+    }
+
+    let conn = state.conn.lock()?;
+    load_days(&conn, start, end, project_id)
+}
+
+/// Pins the first day of the tracker window, or with `None` goes back to
+/// starting at today.
+#[tauri::command]
+pub fn set_tracker_anchor(state: State<DbState>, start_date: Option<String>) -> AppResult<()> {
+    journaled(&state, "Move tracker start", |conn| {
+        match start_date {
+            Some(date) => {
+                parse_day(&date)?;
+                database::set_setting(conn, TRACKER_ANCHOR_SETTING, &date)?;
+            }
+            None => database::clear_setting(conn, TRACKER_ANCHOR_SETTING)?,
+        }
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn get_tracker_anchor(state: State<DbState>) -> AppResult<Option<String>> {
+    let conn = state.conn.lock()?;
+    Ok(database::get_setting(&conn, TRACKER_ANCHOR_SETTING)?)
+}
+
+fn window_start(conn: &rusqlite::Connection, start_date: Option<&str>) -> AppResult<NaiveDate> {
+    if let Some(date) = start_date {
+        return parse_day(date);
+    }
+
+    match database::get_setting(conn, TRACKER_ANCHOR_SETTING)? {
+        Some(anchor) => parse_day(&anchor),
+        None => Ok(Local::now().date_naive()),
+    }
+}
+
+fn ensure_days(conn: &rusqlite::Connection, start: NaiveDate, count: i64) -> AppResult<()> {
+    let mut stmt = conn.prepare("INSERT OR IGNORE INTO day_tracker (date) VALUES (?1)")?;
+    for offset in 0..count {
+        stmt.execute([format_day(start + Duration::days(offset))])?;
+    }
+    Ok(())
+}
+
+fn ensure_day(conn: &rusqlite::Connection, date: &str) -> AppResult<()> {
+    conn.execute("INSERT OR IGNORE INTO day_tracker (date) VALUES (?1)", [date])?;
+    Ok(())
+}
+
+// With a project, the counters only cover that project's tasks and are
+// computed here instead of read from `day_tracker`.
+fn load_days(
+    conn: &rusqlite::Connection,
+    start: NaiveDate,
+    end: NaiveDate,
+    project_id: Option<i64>,
+) -> AppResult<Vec<DayTracker>> {
+    let day_from_row = |row: &rusqlite::Row| {
+        let date: String = row.get(1)?;
+        let day_number = recurrence::parse_date(&date)
+            .map(|d| (d - start).num_days() as i32 + 1)
+            .unwrap_or(0);
+        let tasks_completed: i32 = row.get(2)?;
+        let tasks_total: i32 = row.get(3)?;
+        Ok(DayTracker {
+            id: row.get(0)?,
+            day_number,
+            date,
+            completion_status: completion_status(tasks_completed, tasks_total).to_string(),
+            tasks_completed,
+            tasks_total,
+        })
+    };
+    let (start, end) = (format_day(start), format_day(end));
+
+    let days = match project_id {
+        Some(project_id) => conn
+            .prepare(
+                "SELECT dt.id, dt.date,
+                        (SELECT COUNT(*) FROM task_assignments ta
+                         JOIN task_completions tc ON tc.task_id = ta.task_id AND tc.date = ta.date
+                         JOIN tasks t ON t.id = ta.task_id
+                         WHERE ta.date = dt.date AND t.project_id = ?3),
+                        (SELECT COUNT(*) FROM task_assignments ta
+                         JOIN tasks t ON t.id = ta.task_id
+                         WHERE ta.date = dt.date AND t.project_id = ?3)
+                 FROM day_tracker dt
+                 WHERE dt.date BETWEEN ?1 AND ?2
+                 ORDER BY dt.date ASC",
+            )?
+            .query_map((&start, &end, project_id), day_from_row)?
+            .collect::<Result<Vec<_>, _>>()?,
+        None => conn
+            .prepare(
+                "SELECT id, date, tasks_completed, tasks_total
+                 FROM day_tracker
+                 WHERE date BETWEEN ?1 AND ?2
+                 ORDER BY date ASC",
+            )?
+            .query_map((&start, &end), day_from_row)?
+            .collect::<Result<Vec<_>, _>>()?,
+    };
+
+    Ok(days)
+}
+
+#[tauri::command]
+pub fn assign_task_to_day(
+    state: State<DbState>,
+    task_id: i64,
+    date: String,
+    assigned_by: String,
+) -> AppResult<()> {
+    validate_assigned_by(&assigned_by)?;
+    parse_day(&date)?;
+
+    journaled(&state, "Assign task to day", |conn| {
+        ensure_task_exists(conn, task_id)?;
+        ensure_day(conn, &date)?;
+
+        // Check if task is already assigned to this day
+        let exists: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM task_assignments WHERE task_id = ?1 AND date = ?2",
+                (task_id, &date),
+                |row| row.get(0),
+            )?;
+
+        if exists > 0 {
+            return Ok(());
+        }
+
+        conn.execute(
+            "INSERT INTO task_assignments (task_id, date, assigned_by) VALUES (?1, ?2, ?3)",
+            (task_id, &date, &assigned_by),
+        )?;
+
+        // Update day total tasks
+        update_day_status_internal(conn, &date)?;
+
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn unassign_task_from_day(state: State<DbState>, task_id: i64, date: String) -> AppResult<()> {
+    journaled(&state, "Unassign task from day", |conn| {
+        let removed = conn.execute(
+            "DELETE FROM task_assignments WHERE task_id = ?1 AND date = ?2",
+            (task_id, &date),
+        )?;
+        if removed == 0 {
+            return Err(AppError::NotFound(format!(
+                "Task {} is not assigned to {}",
+                task_id, date
+            )));
+        }
+
+        update_day_status_internal(conn, &date)?;
+
+        Ok(())
+    })
+}
+
+/// Moves an assignment to another day. If the task is already on that day the
+/// two are merged. A manual move pins the assignment so the planner leaves it be.
+#[tauri::command]
+pub fn move_task_assignment(
+    state: State<DbState>,
+    task_id: i64,
+    from_date: String,
+    to_date: String,
+    moved_by: String,
+) -> AppResult<()> {
+    validate_assigned_by(&moved_by)?;
+    parse_day(&to_date)?;
+
+    journaled(&state, "Move assignment", |tx| {
+        let assignment_id: i64 = tx
+            .query_row(
+                "SELECT id FROM task_assignments WHERE task_id = ?1 AND date = ?2",
+                (task_id, &from_date),
+                |row| row.get(0),
+            )
+            .optional()?
+            .ok_or_else(|| {
+                AppError::NotFound(format!("Task {} is not assigned to {}", task_id, from_date))
+            })?;
+
+        if from_date == to_date {
+            return Ok(());
+        }
+
+        ensure_day(tx, &to_date)?;
+        move_assignment(tx, assignment_id, task_id, &to_date, &moved_by)?;
+
+        update_day_status_internal(tx, &from_date)?;
+        update_day_status_internal(tx, &to_date)?;
+
+        Ok(())
+    })
+}
+
+/// Moves every assignment left open on a day before `to_date` (today by
+/// default) onto `to_date`. Tasks that are closed for good stay where they are.
+#[tauri::command]
+pub fn reschedule_incomplete_tasks(
+    state: State<DbState>,
+    to_date: Option<String>,
+    moved_by: String,
+) -> AppResult<RescheduleResult> {
+    validate_assigned_by(&moved_by)?;
+    let to_date = match to_date {
+        Some(date) => {
+            parse_day(&date)?;
+            date
+        }
+        None => format_day(Local::now().date_naive()),
+    };
+
+    journaled(&state, "Reschedule open tasks", |tx| {
+        let overdue: Vec<(i64, i64, String)> = {
+            let mut stmt = tx.prepare(
+                "SELECT ta.id, ta.task_id, ta.date FROM task_assignments ta
+                 JOIN tasks t ON t.id = ta.task_id
+                 WHERE ta.date < ?1
+                   AND t.is_completed = 0
+                   AND NOT EXISTS (SELECT 1 FROM task_completions tc
+                                   WHERE tc.task_id = ta.task_id AND tc.date = ta.date)
+                 ORDER BY ta.date, ta.id",
+            )?;
+            let rows = stmt
+                .query_map([&to_date], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+                .collect::<Result<Vec<_>, _>>()?;
+            rows
+        };
+
+        if overdue.is_empty() {
+            return Ok(RescheduleResult {
+                moved_task_ids: vec![],
+                affected_days: vec![],
+            });
+        }
+
+        ensure_day(tx, &to_date)?;
+
+        let mut moved_task_ids = Vec::new();
+        let mut affected_days = vec![to_date.clone()];
+        for (assignment_id, task_id, date) in overdue {
+            move_assignment(tx, assignment_id, task_id, &to_date, &moved_by)?;
+            moved_task_ids.push(task_id);
+            affected_days.push(date);
+        }
+        moved_task_ids.sort_unstable();
+        moved_task_ids.dedup();
+        affected_days.sort_unstable();
+        affected_days.dedup();
+
+        for date in &affected_days {
+            update_day_status_internal(tx, date)?;
+        }
+
+        Ok(RescheduleResult {
+            moved_task_ids,
+            affected_days,
+        })
+    })
+}
+
+fn validate_assigned_by(assigned_by: &str) -> AppResult<()> {
+    if assigned_by != "manual" && assigned_by != "ai" {
+        return Err(AppError::Validation(format!(
+            "assigned_by must be 'manual' or 'ai', got '{}'",
+            assigned_by
+        )));
+    }
+    Ok(())
+}
+
+// Callers recompute the day counters for both dates
+fn move_assignment(
+    conn: &rusqlite::Connection,
+    assignment_id: i64,
+    task_id: i64,
+    to_date: &str,
+    moved_by: &str,
+) -> AppResult<()> {
+    let already_there: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM task_assignments WHERE task_id = ?1 AND date = ?2)",
+        (task_id, to_date),
+        |row| row.get(0),
+    )?;
+
+    if already_there {
+        conn.execute("DELETE FROM task_assignments WHERE id = ?1", [assignment_id])?;
+        return Ok(());
+    }
+
+    conn.execute(
+        "UPDATE task_assignments
+         SET moved_from = date,
+             date = ?1,
+             moved_by = ?2,
+             moved_at = CURRENT_TIMESTAMP,
+             assigned_by = CASE WHEN ?2 = 'manual' THEN 'manual' ELSE assigned_by END
+         WHERE id = ?3",
+        (to_date, moved_by, assignment_id),
+    )?;
+
+    Ok(())
+}
+
+// Color for a day with `tasks_completed` out of `tasks_total` assigned tasks done
+fn completion_status(tasks_completed: i32, tasks_total: i32) -> &'static str {
+    if tasks_total == 0 {
+        "red"
+    } else {
+        let ratio = tasks_completed as f32 / tasks_total as f32;
+        if ratio == 0.0 {
+            "red"
+        } else if ratio < 0.5 {
+            "yellow"
+        } else if ratio < 1.0 {
+            "light_green"
+        } else {
+            "deep_green"
+        }
+    }
+}
+
+// Internal helper function for updating day status
+fn update_day_status_internal(
+    conn: &rusqlite::Connection,
+    date: &str
+) -> AppResult<()> {
+        
+    // Count total assigned tasks
+    let tasks_total: i32 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM task_assignments WHERE date = ?1",
+            [date],
+            |row| row.get(0),
+        )?;
+
+    // Count tasks completed on this date
+    let tasks_completed: i32 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM task_assignments ta 
+             JOIN task_completions tc ON tc.task_id = ta.task_id AND tc.date = ta.date 
+             WHERE ta.date = ?1",
+            [date],
+            |row| row.get(0),
+        )?;
+
+    let completion_status = completion_status(tasks_completed, tasks_total);
+
+    conn.execute(
+        "UPDATE day_tracker SET tasks_completed = ?1, tasks_total = ?2, completion_status = ?3 WHERE date = ?4",
+        (tasks_completed, tasks_total, completion_status, date),
+    )?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn update_day_status(state: State<DbState>, date: String) -> AppResult<()> {
+    parse_day(&date)?;
+    let conn = state.conn.lock()?;
+    ensure_day(&conn, &date)?;
+    update_day_status_internal(&conn, &date)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_tasks_for_day(
+    state: State<DbState>,
+    date: String,
+    project_id: Option<i64>,
+) -> AppResult<Vec<Task>> {
+    let conn = state.conn.lock()?;
+    
+    let mut stmt = conn
+        .prepare(
+            // `is_completed` is whether the task was done on this date
+            "SELECT t.id, t.title, t.description,
+                    EXISTS(SELECT 1 FROM task_completions tc WHERE tc.task_id = t.id AND tc.date = ta.date),
+                    t.parent_id, t.due_date, t.is_recurring, t.recurrence_pattern, t.created_at,
+                    t.priority, t.estimated_minutes, t.difficulty, t.project_id
+             FROM tasks t
+             JOIN task_assignments ta ON t.id = ta.task_id
+             WHERE ta.date = ?1 AND (?2 IS NULL OR t.project_id = ?2)
+             ORDER BY t.created_at ASC",
+        )?;
+
+    let mut tasks = stmt
+        .query_map((date, project_id), task_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let ids = json_id_list(tasks.iter().map(|task| task.id));
+    let mut tags = load_tags_by_task(&conn, &ids)?;
+    let mut progress = load_progress_by_task(&conn, &ids)?;
+    for task in &mut tasks {
+        task.tags = tags.remove(&task.id).unwrap_or_default();
+        task.progress = progress.remove(&task.id).unwrap_or_default();
+    }
+
+    Ok(tasks)
+}
+
+// Planner Commands
+
+const PLANNER_CAPACITY_SETTING: &str = "planner_daily_capacity";
+const MAX_DAILY_CAPACITY: u32 = 50;
+
+/// Shows where the planner would put open tasks, without changing anything.
+#[tauri::command]
+pub fn preview_auto_assign(
+    state: State<DbState>,
+    start_date: Option<String>,
+    capacity: Option<u32>,
+) -> AppResult<Plan> {
+    let conn = state.conn.lock()?;
+    build_plan(&conn, start_date.as_deref(), capacity)
+}
+
+/// Replaces the planner's earlier assignments in the window with a fresh plan.
+/// Manual assignments are never touched.
+#[tauri::command]
+pub fn apply_auto_assign(
+    state: State<DbState>,
+    start_date: Option<String>,
+    capacity: Option<u32>,
+) -> AppResult<Plan> {
+    journaled(&state, "Auto-assign tasks", |tx| auto_assign(tx, start_date.as_deref(), capacity))
+}
+
+// `apply_auto_assign` without the app state
+fn auto_assign(tx: &rusqlite::Connection, start_date: Option<&str>, capacity: Option<u32>) -> AppResult<Plan> {
+    let plan = build_plan(tx, start_date, capacity)?;
+    let start = parse_day(&plan.start_date)?;
+    let end = parse_day(&plan.end_date)?;
+    ensure_days(tx, start, (end - start).num_days() + 1)?;
+
+    let mut affected_days: Vec<String> = {
+        let mut stmt = tx.prepare(
+            "SELECT DISTINCT ta.date FROM task_assignments ta
+             JOIN tasks t ON t.id = ta.task_id
+             WHERE ta.assigned_by = 'ai' AND t.is_completed = 0 AND ta.date BETWEEN ?1 AND ?2
+               AND NOT EXISTS (
+                   SELECT 1 FROM task_completions c WHERE c.task_id = ta.task_id AND c.date = ta.date
+               )",
+        )?;
+        let dates = stmt
+            .query_map([&plan.start_date, &plan.end_date], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        dates
+    };
+
+    // A recurring task done on a day has already rolled on and is open
+    // again, but that day's assignment is history and stays
+    tx.execute(
+        "DELETE FROM task_assignments
+         WHERE assigned_by = 'ai' AND date BETWEEN ?1 AND ?2
+           AND task_id IN (SELECT id FROM tasks WHERE is_completed = 0)
+           AND NOT EXISTS (
+               SELECT 1 FROM task_completions c
+               WHERE c.task_id = task_assignments.task_id AND c.date = task_assignments.date
+           )",
+        [&plan.start_date, &plan.end_date],
+    )?;
+
+    {
+        let mut stmt = tx.prepare(
+            "INSERT OR IGNORE INTO task_assignments (task_id, date, assigned_by) VALUES (?1, ?2, 'ai')",
+        )?;
+        for assignment in &plan.assignments {
+            stmt.execute((assignment.task_id, &assignment.date))?;
+            affected_days.push(assignment.date.clone());
+        }
+    }
+
+    affected_days.sort_unstable();
+    affected_days.dedup();
+    for date in &affected_days {
+        update_day_status_internal(tx, date)?;
+    }
+
+    Ok(plan)
+}
+
+#[tauri::command]
+pub fn get_planner_capacity(state: State<DbState>) -> AppResult<u32> {
+    let conn = state.conn.lock()?;
+    planner_capacity(&conn)
+}
+
+#[tauri::command]
+pub fn set_planner_capacity(state: State<DbState>, capacity: u32) -> AppResult<()> {
+    validate_capacity(capacity)?;
+    journaled(&state, "Change planner capacity", |conn| {
+        database::set_setting(conn, PLANNER_CAPACITY_SETTING, &capacity.to_string())?;
+        Ok(())
+    })
+}
+
+fn validate_capacity(capacity: u32) -> AppResult<()> {
+    if capacity == 0 || capacity > MAX_DAILY_CAPACITY {
+        return Err(AppError::Validation(format!(
+            "Daily capacity must be between 1 and {}",
+            MAX_DAILY_CAPACITY
+        )));
+    }
+    Ok(())
+}
+
+fn planner_capacity(conn: &rusqlite::Connection) -> AppResult<u32> {
+    let stored = database::get_setting(conn, PLANNER_CAPACITY_SETTING)?;
+    Ok(stored
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(planner::DEFAULT_DAILY_CAPACITY))
+}
+
+// Plans the part of the tracker window from today onwards. Only top-level open
+// tasks are planned; subtasks travel with their parent.
+fn build_plan(
+    conn: &rusqlite::Connection,
+    start_date: Option<&str>,
+    capacity: Option<u32>,
+) -> AppResult<Plan> {
+    let capacity = match capacity {
+        Some(capacity) => {
+            validate_capacity(capacity)?;
+            capacity
+        }
+        None => planner_capacity(conn)?,
+    };
+
+    let window = window_start(conn, start_date)?;
+    let end = window + Duration::days(TRACKER_WINDOW_DAYS - 1);
+    let start = window.max(Local::now().date_naive());
+    if start > end {
+        return Err(AppError::Validation(
+            "The tracker window is entirely in the past".to_string(),
+        ));
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT id, due_date, is_recurring, recurrence_pattern, created_at,
+                priority, estimated_minutes, difficulty, recurrence_anchor
+         FROM tasks
+         WHERE is_completed = 0 AND parent_id IS NULL AND deleted_at IS NULL
+         ORDER BY id",
+    )?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, bool>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, i32>(5)?,
+                planner::effort_weight(row.get(6)?, row.get(7)?),
+                row.get::<_, Option<String>>(8)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let tasks: Vec<PlannerTask> = rows
+        .into_iter()
+        .map(|(id, due_date, is_recurring, pattern, created_at, priority, weight, anchor)| {
+            let due_date = due_date.as_deref().and_then(recurrence::parse_date);
+            // A broken pattern shouldn't block planning; plan it as a one-off
+            let recurrence = pattern
+                .filter(|_| is_recurring)
+                .and_then(|p| RecurrenceRule::parse(&p).ok());
+            // Recurring tasks without a due date repeat from when they were created
+            let due_date = match recurrence {
+                Some(_) => due_date.or_else(|| recurrence::parse_date(&created_at)),
+                None => due_date,
+            };
+            PlannerTask {
+                id,
+                due_date,
+                anchor: anchor.as_deref().and_then(recurrence::parse_date),
+                priority,
+                weight,
+                recurrence,
+            }
+        })
+        .collect();
+
+    let mut stmt = conn.prepare(
+        "SELECT ta.task_id, ta.date, t.estimated_minutes, t.difficulty FROM task_assignments ta
+         JOIN tasks t ON t.id = ta.task_id
+         WHERE ta.date BETWEEN ?1 AND ?2
+           AND (ta.assigned_by = 'manual' OR t.is_completed = 1 OR EXISTS (
+               SELECT 1 FROM task_completions c WHERE c.task_id = ta.task_id AND c.date = ta.date
+           ))",
+    )?;
+    let pinned = stmt
+        .query_map([format_day(start), format_day(end)], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                planner::effort_weight(row.get(2)?, row.get(3)?),
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .filter_map(|(task_id, date, weight)| {
+            recurrence::parse_date(&date).map(|date| PinnedAssignment {
+                task_id,
+                date,
+                weight,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(planner::plan(
+        &tasks,
+        &pinned,
+        start,
+        (end - start).num_days() + 1,
+        capacity,
+    ))
+}
+
+// Undo Commands
+
+/// Reverses the latest change still in effect. `None` when there is nothing
+/// to undo.
+#[tauri::command]
+pub fn undo(state: State<DbState>) -> AppResult<Option<UndoResult>> {
+    let mut conn = state.conn.lock()?;
+    step_journal(&mut conn, Direction::Undo)
+}
 
-    let mut affected_task_ids = vec![id];
-    
-    // If completing a parent task, complete all subtasks
-    if new_status {
-        complete_subtasks(&conn, id)?;
-    }
+/// Replays the change undone last. `None` when there is nothing to redo.
+#[tauri::command]
+pub fn redo(state: State<DbState>) -> AppResult<Option<UndoResult>> {
+    let mut conn = state.conn.lock()?;
+    step_journal(&mut conn, Direction::Redo)
+}
 
-    update_day_for_tasks(&conn, &affected_task_ids)?;
+#[tauri::command]
+pub fn get_undo_state(state: State<DbState>) -> AppResult<UndoState> {
+    let conn = state.conn.lock()?;
+    Ok(UndoState {
+        undo_label: journal::next_label(&conn, Direction::Undo)?,
+        redo_label: journal::next_label(&conn, Direction::Redo)?,
+    })
+}
 
-    Ok(new_status)
+// Runs `f` in a transaction and records what it changes as one undo step
+// named `label`, and in the activity log. Every command that writes tasks,
+// their assignments, completions or tags, projects, saved views or settings
+// goes through here.
+fn journaled<T>(
+    state: &State<DbState>,
+    label: &str,
+    f: impl FnOnce(&rusqlite::Transaction) -> AppResult<T>,
+) -> AppResult<T> {
+    let mut conn = state.conn.lock()?;
+    run_journaled(&mut conn, label, f)
 }
 
-fn update_day_for_tasks(
-    conn: &rusqlite::Connection,
-    task_ids: &[i64]
-) -> Result<(), String> {
-    for task_id in task_ids {
-        let mut stmt = conn
-            .prepare("SELECT day_number FROM task_assignments WHERE task_id = ?1")
-            .map_err(|e| e.to_string())?;
-        
-        let day_numbers: Vec<i32> = stmt
-            .query_map([task_id], |row| row.get(0))
-            .map_err(|e| e.to_string())?
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(|e| e.to_string())?;
-        
-        for day_number in day_numbers {
-            update_day_status_internal(conn, day_number)?;
-        }
+// `journaled` on a connection the caller has locked
+fn run_journaled<T>(
+    conn: &mut rusqlite::Connection,
+    label: &str,
+    f: impl FnOnce(&rusqlite::Transaction) -> AppResult<T>,
+) -> AppResult<T> {
+    let tx = conn.transaction()?;
+    let entry_id = journal::begin(&tx, label)?;
+    let result = f(&tx)?;
+    if journal::finish(&tx)? {
+        activity::record_changes(&tx, label, &journal::changes(&tx, entry_id)?)?;
     }
-    Ok(())
+    tx.commit()?;
+    Ok(result)
 }
 
-fn complete_subtasks(conn: &rusqlite::Connection, parent_id: i64) -> Result<(), String> {
-    conn.execute(
-        "UPDATE tasks SET is_completed = 1 WHERE parent_id = ?1",
-        [parent_id],
-    )
-    .map_err(|e| e.to_string())?;
-
-    // Get all subtask IDs and recursively complete their children
-    let mut stmt = conn
-        .prepare("SELECT id FROM tasks WHERE parent_id = ?1")
-        .map_err(|e| e.to_string())?;
+// The journal leaves out the day counters and stats, as they follow from the
+// rows it does cover; they are recomputed here for the days it touched.
+fn step_journal(conn: &mut rusqlite::Connection, direction: Direction) -> AppResult<Option<UndoResult>> {
+    let tx = conn.transaction()?;
+    let Some(step) = journal::apply(&tx, direction)? else {
+        return Ok(None);
+    };
 
-    let subtask_ids: Vec<i64> = stmt
-        .query_map([parent_id], |row| row.get(0))
-        .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+    // An entry that refers to rows deleted outside the journal would fail
+    // the same way every time, and block everything behind it
+    if journal::breaks_references(&tx, &step)? {
+        drop(tx);
+        journal::forget(conn, step.entry_id, direction)?;
+        let verb = match direction {
+            Direction::Undo => "undone",
+            Direction::Redo => "redone",
+        };
+        return Err(AppError::Conflict(format!(
+            "\"{}\" can no longer be {} because something it changed was deleted for good; the history behind it was cleared",
+            step.label, verb
+        )));
+    }
 
-    for subtask_id in subtask_ids {
-        complete_subtasks(conn, subtask_id)?;
+    for date in &step.dates {
+        update_day_status_internal(&tx, date)?;
     }
+    update_user_stats(&tx)?;
 
-    Ok(())
+    let command = match direction {
+        Direction::Undo => format!("Undo: {}", step.label),
+        Direction::Redo => format!("Redo: {}", step.label),
+    };
+    activity::record_changes(&tx, &command, &step.changes)?;
+    tx.commit()?;
+
+    Ok(Some(UndoResult {
+        label: step.label,
+        affected_days: step.dates,
+    }))
 }
 
-// Day Tracker Commands
+// Activity Commands
 
+const DEFAULT_ACTIVITY_LIMIT: u32 = 50;
+const MAX_ACTIVITY_LIMIT: u32 = 500;
+
+/// What happened to a task, newest first. Works for tasks in the trash or
+/// deleted for good too.
 #[tauri::command]
-pub fn initialize_60_days(state: State<DbState>) -> Result<(), String> {
-    let conn = state.conn.lock().unwrap();
-    
-    // Check if already initialized
-    let count: i64 = conn
-        .query_row("SELECT COUNT(*) FROM day_tracker", [], |row| row.get(0))
-        .map_err(|e| e.to_string())?;
+pub fn get_task_activity(state: State<DbState>, task_id: i64, limit: Option<u32>) -> AppResult<Vec<ActivityEntry>> {
+    let limit = activity_limit(limit)?;
+    let conn = state.conn.lock()?;
+    load_activity(
+        &conn,
+        &format!("SELECT {} FROM activity_log WHERE task_id = ?1 ORDER BY id DESC LIMIT ?2", ACTIVITY_COLUMNS),
+        (task_id, limit),
+    )
+}
 
-    if count > 0 {
-        return Ok(());
-    }
+/// Everything that happened lately, newest first. Pass the ID of the last
+/// entry shown as `before_id` for the page after it.
+#[tauri::command]
+pub fn get_recent_activity(
+    state: State<DbState>,
+    limit: Option<u32>,
+    before_id: Option<i64>,
+) -> AppResult<Vec<ActivityEntry>> {
+    let limit = activity_limit(limit)?;
+    let conn = state.conn.lock()?;
+    load_activity(
+        &conn,
+        &format!(
+            "SELECT {} FROM activity_log WHERE ?1 IS NULL OR id < ?1 ORDER BY id DESC LIMIT ?2",
+            ACTIVITY_COLUMNS
+        ),
+        (before_id, limit),
+    )
+}
 
-    // Get current date and insert 60 days
-    for day in 1..=60 {
-        conn.execute(
-            "INSERT INTO day_tracker (day_number, date, completion_status, tasks_completed, tasks_total)
-             VALUES (?1, date('now', '+' || ?2 || ' days'), 'red', 0, 0)",
-            (day, day - 1),
-        )
-        .map_err(|e| e.to_string())?;
+fn activity_limit(limit: Option<u32>) -> AppResult<u32> {
+    let limit = limit.unwrap_or(DEFAULT_ACTIVITY_LIMIT);
+    if limit == 0 || limit > MAX_ACTIVITY_LIMIT {
+        return Err(AppError::Validation(format!(
+            "Activity limit must be between 1 and {}",
+            MAX_ACTIVITY_LIMIT
+        )));
     }
-
-    Ok(())
+    Ok(limit)
 }
 
-#[tauri::command]
-pub fn get_day_tracker_data(state: State<DbState>) -> Result<Vec<DayTracker>, String> {
-    let conn = state.conn.lock().unwrap();
-    
-    let mut stmt = conn
-        .prepare(
-            "SELECT id, day_number, date, completion_status, tasks_completed, tasks_total 
-             FROM day_tracker 
-             ORDER BY day_number ASC",
-        )
-        .map_err(|e| e.to_string())?;
+const ACTIVITY_COLUMNS: &str = "id, occurred_at, command, event_type, task_id, subject, old_value, new_value";
 
-    let days = stmt
-        .query_map([], |row| {
-            Ok(DayTracker {
+fn load_activity(conn: &rusqlite::Connection, sql: &str, params: impl rusqlite::Params) -> AppResult<Vec<ActivityEntry>> {
+    let mut stmt = conn.prepare(sql)?;
+    let entries = stmt
+        .query_map(params, |row| {
+            Ok(ActivityEntry {
                 id: row.get(0)?,
-                day_number: row.get(1)?,
-                date: row.get(2)?,
-                completion_status: row.get(3)?,
-                tasks_completed: row.get(4)?,
-                tasks_total: row.get(5)?,
+                occurred_at: row.get(1)?,
+                command: row.get(2)?,
+                event_type: row.get(3)?,
+                task_id: row.get(4)?,
+                subject: row.get(5)?,
+                old_value: row.get(6)?,
+                new_value: row.get(7)?,
             })
-        })
-        .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
-
-    Ok(days)
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(entries)
 }
 
+// Pomodoro Commands
+
 #[tauri::command]
-pub fn assign_task_to_day(
+pub fn start_pomodoro_session(
     state: State<DbState>,
-    task_id: i64,
-    day_number: i32,
-    assigned_by: String,
-) -> Result<(), String> {
-    let conn = state.conn.lock().unwrap();
-    
-    // Check if task is already assigned to this day
-    let exists: i64 = conn
-        .query_row(
-            "SELECT COUNT(*) FROM task_assignments WHERE task_id = ?1 AND day_number = ?2",
-            (task_id, day_number),
-            |row| row.get(0),
-        )
-        .map_err(|e| e.to_string())?;
-
-    if exists > 0 {
-        return Ok(());
+    timer: State<PomodoroTimer>,
+    task_id: Option<i64>,
+    duration_minutes: i32,
+    break_minutes: Option<i32>,
+) -> AppResult<TimerSnapshot> {
+    let break_minutes = break_minutes.unwrap_or(5);
+    if duration_minutes <= 0 {
+        return Err(AppError::Validation(
+            "Session duration must be at least one minute".to_string(),
+        ));
+    }
+    if break_minutes < 0 {
+        return Err(AppError::Validation("Break length cannot be negative".to_string()));
     }
 
-    conn.execute(
-        "INSERT INTO task_assignments (task_id, day_number, assigned_by) VALUES (?1, ?2, ?3)",
-        (task_id, day_number, &assigned_by),
-    )
-    .map_err(|e| e.to_string())?;
+    let mut conn = state.conn.lock()?;
+    let tx = conn.transaction()?;
+    if let Some(task_id) = task_id {
+        ensure_task_exists(&tx, task_id)?;
+    }
 
-    // Update day total tasks
-    update_day_status_internal(&conn, day_number)?;
+    let now = pomodoro::now();
+    let started = timer.start(&tx, task_id, duration_minutes, break_minutes, now)?;
+    activity::record_session(&tx, "Start focus session", "focus_started", *started.outcome())?;
+    tx.commit()?;
+    timer.apply(started)?;
 
-    Ok(())
+    timer.snapshot(now)
 }
 
-// Internal helper function for updating day status
-fn update_day_status_internal(
-    conn: &rusqlite::Connection,
-    day_number: i32
-) -> Result<(), String> {
-        
-    // Count total assigned tasks
-    let tasks_total: i32 = conn
-        .query_row(
-            "SELECT COUNT(*) FROM task_assignments WHERE day_number = ?1",
-            [day_number],
-            |row| row.get(0),
-        )
-        .map_err(|e| e.to_string())?;
-
-    // Count completed tasks
-    let tasks_completed: i32 = conn
-        .query_row(
-            "SELECT COUNT(*) FROM task_assignments ta 
-             JOIN tasks t ON ta.task_id = t.id 
-             WHERE ta.day_number = ?1 AND t.is_completed = 1",
-            [day_number],
-            |row| row.get(0),
-        )
-        .map_err(|e| e.to_string())?;
-
-    // Calculate color status
-    let completion_status = if tasks_total == 0 {
-        "red"
-    } else {
-        let ratio = tasks_completed as f32 / tasks_total as f32;
-        if ratio == 0.0 {
-            "red"
-        } else if ratio < 0.5 {
-            "yellow"
-        } else if ratio < 1.0 {
-            "light_green"
-        } else {
-            "deep_green"
-        }
-    };
+#[tauri::command]
+pub fn pause_pomodoro_session(state: State<DbState>, timer: State<PomodoroTimer>) -> AppResult<TimerSnapshot> {
+    let mut conn = state.conn.lock()?;
+    let tx = conn.transaction()?;
+    let now = pomodoro::now();
 
-    conn.execute(
-        "UPDATE day_tracker SET tasks_completed = ?1, tasks_total = ?2, completion_status = ?3 WHERE day_number = ?4",
-        (tasks_completed, tasks_total, completion_status, day_number),
-    )
-    .map_err(|e| e.to_string())?;
+    let paused = timer.pause(&tx, now)?;
+    tx.commit()?;
+    timer.apply(paused)?;
 
-    Ok(())
+    timer.snapshot(now)
 }
 
 #[tauri::command]
-pub fn update_day_status(state: State<DbState>, day_number: i32) -> Result<(), String> {
-    let conn = state.conn.lock().unwrap();
-    update_day_status_internal(&conn, day_number)?;
-    Ok(())
+pub fn resume_pomodoro_session(state: State<DbState>, timer: State<PomodoroTimer>) -> AppResult<TimerSnapshot> {
+    let mut conn = state.conn.lock()?;
+    let tx = conn.transaction()?;
+    let now = pomodoro::now();
+
+    let resumed = timer.resume(&tx, now)?;
+    tx.commit()?;
+    timer.apply(resumed)?;
+
+    timer.snapshot(now)
 }
 
+/// Stops the current focus session without counting it, or skips a break.
 #[tauri::command]
-pub fn get_tasks_for_day(state: State<DbState>, day_number: i32) -> Result<Vec<Task>, String> {
-    let conn = state.conn.lock().unwrap();
-    
-    let mut stmt = conn
-        .prepare(
-            "SELECT t.id, t.title, t.description, t.is_completed, t.parent_id, t.due_date, t.is_recurring, t.recurrence_pattern, t.created_at 
-             FROM tasks t
-             JOIN task_assignments ta ON t.id = ta.task_id
-             WHERE ta.day_number = ?1
-             ORDER BY t.created_at ASC",
-        )
-        .map_err(|e| e.to_string())?;
+pub fn abort_pomodoro_session(state: State<DbState>, timer: State<PomodoroTimer>) -> AppResult<TimerSnapshot> {
+    let mut conn = state.conn.lock()?;
+    let tx = conn.transaction()?;
+    let now = pomodoro::now();
 
-    let tasks = stmt
-        .query_map([day_number], |row| {
-            Ok(Task {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                description: row.get(2)?,
-                is_completed: row.get(3)?,
-                parent_id: row.get(4)?,
-                due_date: row.get(5)?,
-                is_recurring: row.get(6)?,
-                recurrence_pattern: row.get(7)?,
-                created_at: row.get(8)?,
-                subtasks: vec![],
-            })
-        })
-        .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+    // Skipping a break isn't worth logging
+    let aborted = timer.abort(&tx, now)?;
+    if let Some(session_id) = *aborted.outcome() {
+        activity::record_session(&tx, "Stop focus session", "focus_aborted", session_id)?;
+    }
+    tx.commit()?;
+    timer.apply(aborted)?;
 
-    Ok(tasks)
+    timer.snapshot(now)
 }
 
-// Pomodoro Commands
+#[tauri::command]
+pub fn get_pomodoro_timer(timer: State<PomodoroTimer>) -> AppResult<TimerSnapshot> {
+    timer.snapshot(pomodoro::now())
+}
 
 #[tauri::command]
-pub fn start_pomodoro_session(
+pub fn complete_pomodoro_session(
     state: State<DbState>,
-    task_id: Option<i64>,
-    duration_minutes: i32,
-) -> Result<i64, String> {
-    let conn = state.conn.lock().unwrap();
-    
-    conn.execute(
-        "INSERT INTO pomodoro_sessions (task_id, start_time, duration_minutes, date)
-         VALUES (?1, datetime('now'), ?2, date('now'))",
-        (task_id, duration_minutes),
-    )
-    .map_err(|e| e.to_string())?;
-
-    Ok(conn.last_insert_rowid())
+    timer: State<PomodoroTimer>,
+    session_id: i64,
+) -> AppResult<()> {
+    let mut conn = state.conn.lock()?;
+    complete_session(&mut conn, &timer, session_id)
 }
 
-#[tauri::command]
-pub fn complete_pomodoro_session(state: State<DbState>, session_id: i64) -> Result<(), String> {
-    let conn = state.conn.lock().unwrap();
-    
-    conn.execute(
-        "UPDATE pomodoro_sessions SET completed = 1, end_time = datetime('now') WHERE id = ?1",
+/// `complete_pomodoro_session` on a connection the caller has locked, as the
+/// stats are recomputed in the same transaction rather than by locking again.
+pub(crate) fn complete_session(
+    conn: &mut rusqlite::Connection,
+    timer: &PomodoroTimer,
+    session_id: i64,
+) -> AppResult<()> {
+    let tx = conn.transaction()?;
+
+    let updated = tx.execute(
+        "UPDATE pomodoro_sessions SET completed = 1, status = 'completed', end_time = datetime('now')
+         WHERE id = ?1 AND status IN ('running', 'paused')",
         [session_id],
-    )
-    .map_err(|e| e.to_string())?;
+    )?;
+
+    if updated == 0 {
+        let status: Option<String> = tx
+            .query_row("SELECT status FROM pomodoro_sessions WHERE id = ?1", [session_id], |row| row.get(0))
+            .optional()?;
+        return Err(match status {
+            Some(status) => AppError::Conflict(format!("Session {} is already {}", session_id, status)),
+            None => AppError::NotFound(format!("Session {} not found", session_id)),
+        });
+    }
 
     // Update user stats
-    update_user_stats(&state)?;
+    update_user_stats(&tx)?;
+
+    let finished = timer.finish(&tx, session_id, pomodoro::now())?;
+    activity::record_session(&tx, "Complete focus session", "focus_completed", session_id)?;
+    tx.commit()?;
+    timer.apply(finished)?;
 
     Ok(())
 }
 
 #[tauri::command]
-pub fn get_pomodoro_stats(state: State<DbState>) -> Result<UserStats, String> {
-    let conn = state.conn.lock().unwrap();
+pub fn get_pomodoro_stats(state: State<DbState>) -> AppResult<UserStats> {
+    let conn = state.conn.lock()?;
     
     let stats = conn
         .query_row(
@@ -495,48 +3517,125 @@ pub fn get_pomodoro_stats(state: State<DbState>) -> Result<UserStats, String> {
                     last_study_date: row.get(5)?,
                 })
             },
-        )
-        .map_err(|e| e.to_string())?;
+        )?;
 
-    Ok(stats)
+    // The stored streak was computed at the last session or completion;
+    // recount it for today so a missed day shows up before the next one.
+    // Only the write paths store it.
+    let streaks = streaks::calculate_streaks(&load_study_days(&conn)?, Local::now().date_naive());
+
+    Ok(UserStats {
+        current_streak: streaks.current,
+        ..stats
+    })
 }
 
 #[tauri::command]
-pub fn get_session_history(state: State<DbState>, days: Option<i32>) -> Result<Vec<PomodoroSession>, String> {
-    let conn = state.conn.lock().unwrap();
-    
-    let days_limit = days.unwrap_or(7);
+pub fn get_session_history(
+    state: State<DbState>,
+    days: Option<i32>,
+    tag_id: Option<i64>,
+    project_id: Option<i64>,
+) -> AppResult<Vec<PomodoroSession>> {
+    let conn = state.conn.lock()?;
+    let since = history_cutoff(days)?;
     
     let mut stmt = conn
         .prepare(
-            "SELECT id, task_id, start_time, end_time, duration_minutes, completed, date 
+            "SELECT id, task_id, start_time, end_time, duration_minutes, break_minutes, completed, status, date 
              FROM pomodoro_sessions 
-             WHERE date >= date('now', '-' || ?1 || ' days')
+             WHERE date >= ?1
+               AND (?2 IS NULL OR task_id IN (SELECT task_id FROM task_tags WHERE tag_id = ?2))
+               AND (?3 IS NULL OR task_id IN (SELECT id FROM tasks WHERE project_id = ?3))
              ORDER BY start_time DESC",
-        )
-        .map_err(|e| e.to_string())?;
+        )?;
 
     let sessions = stmt
-        .query_map([days_limit], |row| {
+        .query_map((since, tag_id, project_id), |row| {
             Ok(PomodoroSession {
                 id: row.get(0)?,
                 task_id: row.get(1)?,
                 start_time: row.get(2)?,
                 end_time: row.get(3)?,
                 duration_minutes: row.get(4)?,
-                completed: row.get(5)?,
-                date: row.get(6)?,
+                break_minutes: row.get(5)?,
+                completed: row.get(6)?,
+                status: row.get(7)?,
+                date: row.get(8)?,
             })
-        })
-        .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
 
     Ok(sessions)
 }
 
-fn update_user_stats(state: &State<DbState>) -> Result<(), String> {
-    let conn = state.conn.lock().unwrap();
+/// Completed focus time per tag over the last `days` days (7 by default). A
+/// session counts towards every tag of the task it was for.
+#[tauri::command]
+pub fn get_pomodoro_tag_stats(
+    state: State<DbState>,
+    days: Option<i32>,
+    project_id: Option<i64>,
+) -> AppResult<Vec<TagPomodoroStats>> {
+    let conn = state.conn.lock()?;
+    let since = history_cutoff(days)?;
+
+    let mut stmt = conn.prepare(
+        "SELECT tg.id, tg.name, tg.color, COUNT(ps.id), COALESCE(SUM(ps.duration_minutes), 0)
+         FROM tags tg
+         JOIN task_tags tt ON tt.tag_id = tg.id
+         JOIN pomodoro_sessions ps ON ps.task_id = tt.task_id
+         WHERE ps.completed = 1 AND ps.date >= ?1
+           AND (?2 IS NULL OR ps.task_id IN (SELECT id FROM tasks WHERE project_id = ?2))
+         GROUP BY tg.id
+         ORDER BY 5 DESC, tg.name COLLATE NOCASE",
+    )?;
+
+    let stats = stmt
+        .query_map((since, project_id), |row| {
+            Ok(TagPomodoroStats {
+                tag_id: row.get(0)?,
+                name: row.get(1)?,
+                color: row.get(2)?,
+                sessions_completed: row.get(3)?,
+                study_minutes: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(stats)
+}
+
+// First local date included in a `days`-long history
+fn history_cutoff(days: Option<i32>) -> AppResult<String> {
+    let days_limit = days.unwrap_or(7);
+    if days_limit < 0 {
+        return Err(AppError::Validation("days cannot be negative".to_string()));
+    }
+    Ok(format_day(Local::now().date_naive() - Duration::days(days_limit as i64)))
+}
+
+// Settings Commands
+
+#[tauri::command]
+pub fn get_database_location(location: State<DatabaseLocation>) -> AppResult<DatabaseLocation> {
+    Ok(location.inner().clone())
+}
+
+/// Stores a custom database path in settings.json (or clears it with `None`).
+/// The new location is used from the next launch.
+#[tauri::command]
+pub fn set_database_path(location: State<DatabaseLocation>, path: Option<String>) -> AppResult<()> {
+    let mut settings = config::load_settings(&location.settings_file)?;
+    settings.database_path = path.filter(|p| !p.trim().is_empty()).map(PathBuf::from);
+    config::save_settings(&location.settings_file, &settings)?;
+    Ok(())
+}
+
+/// Recomputes `user_stats` from the session history. Takes the caller's
+/// connection (usually a transaction) so it commits together with the
+/// change that triggered it.
+pub(crate) fn update_user_stats(conn: &rusqlite::Connection) -> AppResult<()> {
     
     // Get total completed sessions
     let total_completed: i32 = conn
@@ -544,8 +3643,7 @@ fn update_user_stats(state: &State<DbState>) -> Result<(), String> {
             "SELECT COUNT(*) FROM pomodoro_sessions WHERE completed = 1",
             [],
             |row| row.get(0),
-        )
-        .map_err(|e| e.to_string())?;
+        )?;
 
     // Get total study minutes
     let total_minutes: i32 = conn
@@ -553,25 +3651,14 @@ fn update_user_stats(state: &State<DbState>) -> Result<(), String> {
             "SELECT COALESCE(SUM(duration_minutes), 0) FROM pomodoro_sessions WHERE completed = 1",
             [],
             |row| row.get(0),
-        )
-        .map_err(|e| e.to_string())?;
+        )?;
 
     // Calculate streaks
-    let mut stmt = conn
-        .prepare(
-            "SELECT DISTINCT date FROM pomodoro_sessions 
-             WHERE completed = 1 
-             ORDER BY date DESC",
-        )
-        .map_err(|e| e.to_string())?;
-
-    let dates: Vec<String> = stmt
-        .query_map([], |row| row.get(0))
-        .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
-
-    let (current_streak, longest_streak) = calculate_streaks(&dates);
+    let study_days = load_study_days(conn)?;
+    let streaks = streaks::calculate_streaks(&study_days, Local::now().date_naive());
+    let last_study_date = study_days
+        .last()
+        .map(|date| date.format("%Y-%m-%d").to_string());
 
     // Update stats
     conn.execute(
@@ -580,56 +3667,434 @@ fn update_user_stats(state: &State<DbState>) -> Result<(), String> {
          longest_streak = ?2, 
          total_tasks_completed = ?3, 
          total_study_minutes = ?4, 
-         last_study_date = date('now')
+         last_study_date = ?5
          WHERE id = 1",
-        (current_streak, longest_streak, total_completed, total_minutes),
-    )
-    .map_err(|e| e.to_string())?;
+        (streaks.current, streaks.longest, total_completed, total_minutes, last_study_date),
+    )?;
 
     Ok(())
 }
 
-fn calculate_streaks(dates: &[String]) -> (i32, i32) {
-    if dates.is_empty() {
-        return (0, 0);
+// Local dates with at least one completed session or task, oldest first
+fn load_study_days(conn: &rusqlite::Connection) -> AppResult<Vec<NaiveDate>> {
+    let mut stmt = conn.prepare(
+        "SELECT date FROM pomodoro_sessions WHERE completed = 1
+         UNION
+         SELECT date FROM task_completions
+         WHERE task_id IN (SELECT id FROM tasks WHERE deleted_at IS NULL)
+         ORDER BY date ASC",
+    )?;
+
+    let dates = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(dates.iter().filter_map(|d| recurrence::parse_date(d)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use std::sync::{mpsc, Mutex};
+
+    fn open_database() -> rusqlite::Connection {
+        database::initialize_database(Path::new(":memory:")).unwrap()
     }
 
-    let mut current_streak = 0;
-    let mut longest_streak = 0;
-    let mut temp_streak = 1;
+    fn due_date(conn: &rusqlite::Connection, id: i64) -> Option<String> {
+        conn.query_row("SELECT due_date FROM tasks WHERE id = ?1", [id], |row| row.get(0))
+            .unwrap()
+    }
 
-    for i in 0..dates.len() {
-        if i == 0 {
-            current_streak = 1;
-        } else {
-            // Check if dates are consecutive
-            let prev_date = &dates[i - 1];
-            let curr_date = &dates[i];
-            
-            // Simple consecutive check (would need proper date parsing in production)
-            if is_consecutive_day(prev_date, curr_date) {
-                temp_streak += 1;
-                if i == 1 {
-                    current_streak += 1;
-                }
-            } else {
-                if i == 1 {
-                    current_streak = 0;
-                }
-                longest_streak = longest_streak.max(temp_streak);
-                temp_streak = 1;
-            }
+    // Completes a recurring task on its due date `times` times and returns
+    // the due dates it rolled on to
+    fn roll(conn: &rusqlite::Connection, id: i64, times: usize) -> Vec<String> {
+        let rules = CompletionRules::default();
+        (0..times)
+            .map(|_| {
+                let due = due_date(conn, id).and_then(|d| recurrence::parse_date(&d));
+                let date = due.unwrap_or_else(|| Local::now().date_naive());
+                complete_on(conn, id, date, "task_list", &rules, &mut CompletionChanges::default()).unwrap();
+                due_date(conn, id).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn recurring_task_keeps_its_day_after_a_short_month() {
+        let conn = open_database();
+        for pattern in ["monthly", "RRULE:FREQ=MONTHLY"] {
+            conn.execute(
+                "INSERT INTO tasks (title, due_date, is_recurring, recurrence_pattern, recurrence_anchor)
+                 VALUES ('Pay rent', '2026-01-31', 1, ?1, '2026-01-31')",
+                [pattern],
+            )
+            .unwrap();
+            let id = conn.last_insert_rowid();
+            assert_eq!(roll(&conn, id, 3), ["2026-02-28", "2026-03-31", "2026-04-30"], "{}", pattern);
         }
+
+        conn.execute(
+            "INSERT INTO tasks (title, due_date, is_recurring, recurrence_pattern, recurrence_anchor)
+             VALUES ('Leap day', '2024-02-29', 1, 'yearly', '2024-02-29')",
+            [],
+        )
+        .unwrap();
+        let id = conn.last_insert_rowid();
+        assert_eq!(roll(&conn, id, 4), ["2025-02-28", "2026-02-28", "2027-02-28", "2028-02-29"]);
     }
 
-    longest_streak = longest_streak.max(temp_streak);
-    longest_streak = longest_streak.max(current_streak);
+    #[test]
+    fn recurring_task_cannot_be_completed_twice_on_one_day() {
+        let conn = open_database();
+        let today = Local::now().date_naive();
+        conn.execute(
+            "INSERT INTO tasks (title, due_date, is_recurring, recurrence_pattern, recurrence_anchor)
+             VALUES ('Journal', ?1, 1, 'daily', ?1)",
+            [format_day(today)],
+        )
+        .unwrap();
+        let id = conn.last_insert_rowid();
+        let rules = CompletionRules::default();
+
+        complete_on(&conn, id, today, "task_list", &rules, &mut CompletionChanges::default()).unwrap();
+        let next_due = due_date(&conn, id);
+        assert!(matches!(
+            complete_on(&conn, id, today, "task_list", &rules, &mut CompletionChanges::default()),
+            Err(AppError::Conflict(_))
+        ));
+        assert_eq!(due_date(&conn, id), next_due);
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM task_completions"), 1);
+    }
 
-    (current_streak, longest_streak)
-}
+    #[test]
+    fn recurring_task_without_a_due_date_is_anchored_when_it_first_rolls() {
+        let conn = open_database();
+        conn.execute(
+            "INSERT INTO tasks (title, is_recurring, recurrence_pattern) VALUES ('Stretch', 1, 'daily')",
+            [],
+        )
+        .unwrap();
+        let id = conn.last_insert_rowid();
+        roll(&conn, id, 1);
+
+        let anchor: Option<String> = conn
+            .query_row("SELECT recurrence_anchor FROM tasks WHERE id = ?1", [id], |row| row.get(0))
+            .unwrap();
+        assert_eq!(anchor, Some(format_day(Local::now().date_naive())));
+    }
+
+    #[test]
+    fn days_count_every_task_or_only_the_projects() {
+        let conn = open_database();
+        conn.execute_batch(
+            "INSERT INTO projects (name) VALUES ('Garden');
+             INSERT INTO tasks (title, project_id) VALUES ('Weed', 1);
+             INSERT INTO tasks (title) VALUES ('Shop');
+             INSERT INTO day_tracker (date, tasks_completed, tasks_total) VALUES ('2026-03-02', 0, 2);
+             INSERT INTO task_assignments (task_id, date) VALUES (1, '2026-03-02'), (2, '2026-03-02');",
+        )
+        .unwrap();
+        let start = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2026, 3, 7).unwrap();
+
+        let days = load_days(&conn, start, end, None).unwrap();
+        assert_eq!((days.len(), days[0].day_number, days[0].tasks_total), (1, 2, 2));
+        let days = load_days(&conn, start, end, Some(1)).unwrap();
+        assert_eq!((days.len(), days[0].tasks_total), (1, 1));
+    }
+
+    #[test]
+    fn batched_progress_matches_each_subtree() {
+        let conn = open_database();
+        conn.execute_batch(
+            "INSERT INTO tasks (id, title, estimated_minutes) VALUES (1, 'Move house', NULL);
+             INSERT INTO tasks (id, title, parent_id, is_completed, estimated_minutes) VALUES
+                (2, 'Pack', 1, 0, NULL),
+                (3, 'Books', 2, 1, 30),
+                (4, 'Kitchen', 2, 0, 90),
+                (5, 'Book van', 1, 1, 10),
+                (6, 'Old plan', 1, 0, 60);
+             UPDATE tasks SET deleted_at = CURRENT_TIMESTAMP WHERE id = 6;
+             INSERT INTO tasks (id, title, is_completed) VALUES (7, 'Water plants', 1);",
+        )
+        .unwrap();
+
+        let progress = load_progress_by_task(&conn, &json_id_list([1, 2, 4, 7].into_iter())).unwrap();
+        for id in [1, 2, 4, 7] {
+            assert_eq!(progress[&id], load_subtree_progress(&conn, id).unwrap()[0].progress, "task {}", id);
+        }
+        assert_eq!(progress[&1].completed_leaves, 2);
+    }
+
+    #[test]
+    fn new_subtask_reopens_a_completed_parent() {
+        let conn = open_database();
+        let today = format_day(Local::now().date_naive());
+        conn.execute_batch(
+            "INSERT INTO tasks (id, title, is_completed) VALUES (1, 'Plan trip', 1);
+             INSERT INTO tasks (id, title, parent_id, is_completed) VALUES (2, 'Book flights', 1, 1);",
+        )
+        .unwrap();
+        ensure_day(&conn, &today).unwrap();
+        conn.execute("INSERT INTO task_assignments (task_id, date) VALUES (1, ?1)", [&today]).unwrap();
+        conn.execute(
+            "INSERT INTO task_completions (task_id, date, source) VALUES (1, ?1, ?2)",
+            (&today, COMPLETION_SOURCE_SUBTASKS),
+        )
+        .unwrap();
+        update_day_status_internal(&conn, &today).unwrap();
+
+        insert_task(&conn, "Book hotel", None, None, false, None, Some(1), None, None, None, None).unwrap();
+
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM tasks WHERE id = 1 AND is_completed = 0"), 1);
+        let days = load_days(&conn, Local::now().date_naive(), Local::now().date_naive(), None).unwrap();
+        assert_eq!((days[0].tasks_completed, days[0].tasks_total), (0, 1));
+    }
+
+    #[test]
+    fn auto_assign_keeps_the_day_a_recurring_task_was_done() {
+        let conn = open_database();
+        let today = Local::now().date_naive();
+        conn.execute(
+            "INSERT INTO tasks (title, due_date, is_recurring, recurrence_pattern, recurrence_anchor)
+             VALUES ('Walk the dog', ?1, 1, 'daily', ?1)",
+            [format_day(today)],
+        )
+        .unwrap();
+        let id = conn.last_insert_rowid();
+        let counts = |conn: &rusqlite::Connection| {
+            let day = load_days(conn, today, today, None).unwrap().remove(0);
+            (day.tasks_completed, day.tasks_total)
+        };
+
+        auto_assign(&conn, None, None).unwrap();
+        assert_eq!(counts(&conn), (0, 1));
+        let rules = CompletionRules::default();
+        let mut changes = CompletionChanges::default();
+        complete_on(&conn, id, today, "task_list", &rules, &mut changes).unwrap();
+        changes.finish(&conn).unwrap();
+        assert_eq!(counts(&conn), (1, 1));
+
+        auto_assign(&conn, None, None).unwrap();
+        assert_eq!(counts(&conn), (1, 1));
+        let kept = conn
+            .query_row(
+                "SELECT COUNT(*) FROM task_assignments WHERE task_id = ?1 AND date = ?2",
+                (id, format_day(today)),
+                |row| row.get::<_, i64>(0),
+            )
+            .unwrap();
+        assert_eq!(kept, 1);
+    }
+
+    #[test]
+    fn only_a_running_session_can_be_completed() {
+        let mut conn = open_database();
+        let timer = PomodoroTimer::default();
+        conn.execute_batch(
+            "INSERT INTO pomodoro_sessions (id, start_time, duration_minutes, date, status, completed) VALUES
+                (1, '2026-03-02 09:00:00', 25, '2026-03-02', 'aborted', 0),
+                (2, '2026-03-02 10:00:00', 25, '2026-03-02', 'completed', 1);",
+        )
+        .unwrap();
+        let stats = |conn: &rusqlite::Connection| {
+            conn.query_row("SELECT total_study_minutes FROM user_stats WHERE id = 1", [], |row| {
+                row.get::<_, i32>(0)
+            })
+            .unwrap()
+        };
+        let minutes = stats(&conn);
+
+        for session_id in [1, 2] {
+            assert!(matches!(
+                complete_session(&mut conn, &timer, session_id),
+                Err(AppError::Conflict(_))
+            ));
+        }
+        assert!(matches!(complete_session(&mut conn, &timer, 3), Err(AppError::NotFound(_))));
+        assert_eq!(stats(&conn), minutes);
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM activity_log"), 0);
+    }
 
-fn is_consecutive_day(date1: &str, date2: &str) -> bool {
-    // Simplified check - in production, use proper date parsing
-    // For now, just return true if dates are different
-    date1 != date2
+    #[test]
+    fn completing_a_session_updates_it_and_the_stats() {
+        let state = DbState {
+            conn: Mutex::new(open_database()),
+        };
+        let timer = PomodoroTimer::default();
+
+        let session_id = {
+            let mut conn = state.conn.lock().unwrap();
+            let tx = conn.transaction().unwrap();
+            let started = timer.start(&tx, None, 25, 5, pomodoro::now()).unwrap();
+            tx.commit().unwrap();
+            timer.apply(started).unwrap()
+        };
+
+        // Taking the lock again from inside used to hang here
+        let (done, finished) = mpsc::channel();
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                let mut conn = state.conn.lock().unwrap();
+                done.send(complete_session(&mut conn, &timer, session_id)).unwrap();
+            });
+            finished
+                .recv_timeout(std::time::Duration::from_secs(10))
+                .expect("completing the session deadlocked")
+                .unwrap();
+        });
+
+        let conn = state.conn.lock().unwrap();
+        let (status, completed, ended): (String, bool, Option<String>) = conn
+            .query_row(
+                "SELECT status, completed, end_time FROM pomodoro_sessions WHERE id = ?1",
+                [session_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!((status.as_str(), completed), ("completed", true));
+        assert!(ended.is_some());
+
+        let (minutes, streak, last_study_date): (i32, i32, Option<String>) = conn
+            .query_row(
+                "SELECT total_study_minutes, current_streak, last_study_date FROM user_stats WHERE id = 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!((minutes, streak), (25, 1));
+        assert_eq!(last_study_date, Some(format_day(Local::now().date_naive())));
+
+        assert_eq!(timer.snapshot(pomodoro::now()).unwrap().phase, pomodoro::TimerPhase::Break);
+    }
+
+    #[test]
+    fn expired_trash_is_purged_as_an_undo_step() {
+        let mut conn = open_database();
+        conn.execute_batch(
+            "INSERT INTO tasks (id, title, deleted_at) VALUES
+                (1, 'Long gone', strftime('%Y-%m-%d %H:%M:%f', 'now', '-31 days')),
+                (2, 'Just deleted', strftime('%Y-%m-%d %H:%M:%f', 'now', '-1 days')),
+                (3, 'Still here', NULL);",
+        )
+        .unwrap();
+
+        assert_eq!(purge_expired_trash(&mut conn).unwrap(), 1);
+        let ids: Vec<i64> = conn
+            .prepare("SELECT id FROM tasks ORDER BY id")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(ids, [2, 3]);
+        assert_eq!(
+            journal::next_label(&conn, Direction::Undo).unwrap().as_deref(),
+            Some("Purge expired trash")
+        );
+
+        // Nothing left to purge takes no undo step
+        conn.execute("DELETE FROM journal_entries", []).unwrap();
+        assert_eq!(purge_expired_trash(&mut conn).unwrap(), 0);
+        assert_eq!(journal::next_label(&conn, Direction::Undo).unwrap(), None);
+    }
+
+    // A task assigned to today and deleted `days_ago` days ago, each step
+    // journaled as the commands do it
+    fn trashed_assigned_task(conn: &mut rusqlite::Connection, days_ago: i64) -> i64 {
+        let today = format_day(Local::now().date_naive());
+        let id = run_journaled(conn, "Create task", |tx| {
+            tx.execute("INSERT INTO tasks (title) VALUES ('Revise')", [])?;
+            Ok(tx.last_insert_rowid())
+        })
+        .unwrap();
+        run_journaled(conn, "Assign task", |tx| {
+            ensure_day(tx, &today)?;
+            tx.execute("INSERT INTO task_assignments (task_id, date) VALUES (?1, ?2)", (id, &today))?;
+            Ok(())
+        })
+        .unwrap();
+        run_journaled(conn, "Delete task", |tx| {
+            tx.execute(
+                "UPDATE tasks SET deleted_at = strftime('%Y-%m-%d %H:%M:%f', 'now', ?2) WHERE id = ?1",
+                (id, format!("-{} days", days_ago)),
+            )?;
+            tx.execute(
+                "INSERT INTO trashed_assignments (task_id, date, assigned_by)
+                 SELECT task_id, date, assigned_by FROM task_assignments WHERE task_id = ?1",
+                [id],
+            )?;
+            tx.execute("DELETE FROM task_assignments WHERE task_id = ?1", [id])?;
+            Ok(())
+        })
+        .unwrap();
+        id
+    }
+
+    fn count(conn: &rusqlite::Connection, sql: &str) -> i64 {
+        conn.query_row(sql, [], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn undo_brings_back_a_task_purged_from_the_trash() {
+        let mut conn = open_database();
+        let id = trashed_assigned_task(&mut conn, 40);
+        assert_eq!(purge_expired_trash(&mut conn).unwrap(), 1);
+
+        let undone = step_journal(&mut conn, Direction::Undo).unwrap().unwrap();
+        assert_eq!(undone.label, "Purge expired trash");
+        let undone = step_journal(&mut conn, Direction::Undo).unwrap().unwrap();
+        assert_eq!(undone.label, "Delete task");
+
+        let sql = format!("SELECT COUNT(*) FROM tasks WHERE id = {} AND deleted_at IS NULL", id);
+        assert_eq!(count(&conn, &sql), 1);
+        let sql = format!("SELECT COUNT(*) FROM task_assignments WHERE task_id = {}", id);
+        assert_eq!(count(&conn, &sql), 1);
+    }
+
+    #[test]
+    fn undo_is_not_stuck_on_rows_deleted_outside_the_journal() {
+        let mut conn = open_database();
+        let id = trashed_assigned_task(&mut conn, 1);
+        conn.execute("DELETE FROM tasks WHERE id = ?1", [id]).unwrap();
+
+        match step_journal(&mut conn, Direction::Undo) {
+            Err(AppError::Conflict(_)) => {}
+            Err(e) => panic!("expected a conflict, got {}", e),
+            Ok(_) => panic!("expected a conflict"),
+        }
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM task_assignments"), 0);
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM journal_entries"), 0);
+        assert!(step_journal(&mut conn, Direction::Undo).unwrap().is_none());
+
+        // Later changes undo as usual
+        run_journaled(&mut conn, "Create task", |tx| {
+            tx.execute("INSERT INTO tasks (title) VALUES ('Next')", [])?;
+            Ok(())
+        })
+        .unwrap();
+        assert!(step_journal(&mut conn, Direction::Undo).unwrap().is_some());
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM tasks"), 0);
+    }
+
+    #[test]
+    fn retention_purge_is_in_the_activity_log() {
+        let mut conn = open_database();
+        let id = trashed_assigned_task(&mut conn, 40);
+        purge_expired_trash(&mut conn).unwrap();
+
+        let latest = load_activity(
+            &conn,
+            &format!("SELECT {} FROM activity_log WHERE task_id = ?1 ORDER BY id DESC LIMIT 1", ACTIVITY_COLUMNS),
+            [id],
+        )
+        .unwrap();
+        let latest = &latest[0];
+        assert_eq!(
+            (latest.command.as_str(), latest.event_type.as_str(), latest.subject.as_str()),
+            ("Purge expired trash", "purged", "Revise")
+        );
+    }
 }
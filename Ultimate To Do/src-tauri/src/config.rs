@@ -0,0 +1,140 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const DB_FILE_NAME: &str = "ultimate_todo.db";
+pub const DB_PATH_ENV: &str = "ULTIMATE_TODO_DB";
+pub const DB_PATH_FLAG: &str = "--db-path";
+const SETTINGS_FILE_NAME: &str = "settings.json";
+
+/// Settings read from `settings.json` in the app config directory.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub database_path: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DatabasePathSource {
+    CommandLine,
+    Environment,
+    Settings,
+    Default,
+}
+
+/// Where the database lives and why, kept in app state for the settings commands.
+#[derive(Debug, Clone, Serialize)]
+pub struct DatabaseLocation {
+    pub path: PathBuf,
+    pub source: DatabasePathSource,
+    #[serde(skip)]
+    pub settings_file: PathBuf,
+}
+
+pub fn settings_file(app_config_dir: &Path) -> PathBuf {
+    app_config_dir.join(SETTINGS_FILE_NAME)
+}
+
+pub fn load_settings(path: &Path) -> io::Result<Settings> {
+    match fs::read_to_string(path) {
+        Ok(contents) => serde_json::from_str(&contents)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Settings::default()),
+        Err(e) => Err(e),
+    }
+}
+
+pub fn save_settings(path: &Path, settings: &Settings) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let contents = serde_json::to_string_pretty(settings)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    fs::write(path, contents)
+}
+
+/// Picks the database file, in order of precedence: the `--db-path` flag, the
+/// `ULTIMATE_TODO_DB` environment variable, `database_path` in settings.json,
+/// and finally `ultimate_todo.db` in the platform app data directory.
+pub fn resolve_database_location(
+    args: impl IntoIterator<Item = String>,
+    env_path: Option<String>,
+    app_data_dir: &Path,
+    app_config_dir: &Path,
+) -> io::Result<DatabaseLocation> {
+    let settings_file = settings_file(app_config_dir);
+
+    let (path, source) = if let Some(path) = path_from_args(args) {
+        (path, DatabasePathSource::CommandLine)
+    } else if let Some(path) = env_path.filter(|p| !p.trim().is_empty()) {
+        (PathBuf::from(path), DatabasePathSource::Environment)
+    } else if let Some(path) = load_settings(&settings_file)?.database_path {
+        (path, DatabasePathSource::Settings)
+    } else {
+        (app_data_dir.join(DB_FILE_NAME), DatabasePathSource::Default)
+    };
+
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+
+    Ok(DatabaseLocation {
+        path,
+        source,
+        settings_file,
+    })
+}
+
+fn path_from_args(args: impl IntoIterator<Item = String>) -> Option<PathBuf> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == DB_PATH_FLAG {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(value) = arg.strip_prefix(DB_PATH_FLAG).and_then(|v| v.strip_prefix('=')) {
+            return Some(PathBuf::from(value));
+        }
+    }
+    None
+}
+
+/// Older builds opened `ultimate_todo.db` relative to the working directory.
+/// On the first run with the default location, move that file (and any WAL
+/// side files) into place so existing tasks are not left behind. Returns the
+/// path that was migrated, if any.
+pub fn migrate_legacy_database(location: &DatabaseLocation) -> io::Result<Option<PathBuf>> {
+    if location.source != DatabasePathSource::Default || location.path.exists() {
+        return Ok(None);
+    }
+
+    let legacy = std::env::current_dir()?.join(DB_FILE_NAME);
+    if !legacy.is_file() {
+        return Ok(None);
+    }
+
+    for suffix in ["", "-wal", "-shm"] {
+        let from = append_to_path(&legacy, suffix);
+        if from.exists() {
+            move_file(&from, &append_to_path(&location.path, suffix))?;
+        }
+    }
+
+    Ok(Some(legacy))
+}
+
+fn append_to_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+// `rename` fails across filesystems, so fall back to copy-then-delete
+fn move_file(from: &Path, to: &Path) -> io::Result<()> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    fs::copy(from, to)?;
+    fs::remove_file(from)
+}
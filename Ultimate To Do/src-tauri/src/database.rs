@@ -1,5 +1,9 @@
-use rusqlite::{Connection, Result};
+use crate::migrations::{self, MigrationError};
+use crate::progress::TaskProgress;
+use crate::search::TextPart;
+use rusqlite::{Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
+use std::path::Path;
 use std::sync::Mutex;
 
 pub struct DbState {
@@ -17,12 +21,180 @@ pub struct Task {
     pub is_recurring: bool,
     pub recurrence_pattern: Option<String>,
     pub created_at: String,
+    /// 0 (none) to 3 (high)
+    pub priority: i32,
+    pub estimated_minutes: Option<i32>,
+    /// 1 (easy) to 5 (hard)
+    pub difficulty: Option<i32>,
+    pub project_id: Option<i64>,
+    /// Completed share of the task's leaves, see `TaskProgress`
+    pub progress: TaskProgress,
+    pub tags: Vec<Tag>,
     pub subtasks: Vec<Task>,
 }
 
+/// A named list of tasks. Subtasks are always in their root task's project.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Project {
+    pub id: i64,
+    pub name: String,
+    pub color: String,
+    /// Position among the projects, from 0
+    pub position: i64,
+    pub is_archived: bool,
+    /// Used for new tasks that don't set their own
+    pub default_priority: i32,
+    pub default_estimated_minutes: Option<i32>,
+    /// How `get_all_tasks` orders this project's tasks unless told otherwise
+    pub sort_by: Option<TaskSort>,
+    pub open_task_count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Tag {
+    pub id: i64,
+    pub name: String,
+    /// `#rrggbb`
+    pub color: String,
+    /// Only filled in by `get_all_tags`
+    pub task_count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TagPomodoroStats {
+    pub tag_id: i64,
+    pub name: String,
+    pub color: String,
+    pub sessions_completed: i64,
+    pub study_minutes: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskSort {
+    /// The order set with `move_task`
+    Manual,
+    CreatedAt,
+    DueDate,
+    Priority,
+    Difficulty,
+    Effort,
+    Title,
+}
+
+/// A project setting `update_project` can put back to its default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectField {
+    Color,
+    DefaultEstimatedMinutes,
+    SortBy,
+}
+
+/// Filters and sort order for `get_all_tasks`. Every field is optional.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TaskQuery {
+    pub sort_by: Option<TaskSort>,
+    /// Defaults to descending for creation date, priority, difficulty and
+    /// effort, ascending otherwise
+    pub descending: Option<bool>,
+    pub min_priority: Option<i32>,
+    pub min_difficulty: Option<i32>,
+    pub max_difficulty: Option<i32>,
+    pub max_estimated_minutes: Option<i32>,
+    pub include_completed: Option<bool>,
+    /// Tasks with any of these tags, or all of them with `match_all_tags`
+    pub tag_ids: Option<Vec<i64>>,
+    pub match_all_tags: Option<bool>,
+    pub project_id: Option<i64>,
+    /// Without a `project_id`, tasks in archived projects are left out unless this is set
+    pub include_archived: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeleteTaskResult {
+    pub deleted_task_ids: Vec<i64>,
+    pub removed_assignments: i64,
+    /// Parents closed because only done subtasks were left
+    pub changed_task_ids: Vec<i64>,
+    pub affected_days: Vec<String>,
+}
+
+/// A task in the trash, as listed by `get_trash`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TrashedTask {
+    /// Without its subtasks
+    pub task: Task,
+    /// UTC, `YYYY-MM-DD HH:MM:SS.SSS`
+    pub deleted_at: String,
+    /// Subtasks at any depth that were deleted along with it
+    pub subtask_count: i64,
+    /// Title of the parent the task goes back under when restored; `None`
+    /// for top-level tasks and tasks whose parent is in the trash too
+    pub restores_under: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RestoreTaskResult {
+    pub task_id: i64,
+    /// Where the task went back to
+    pub parent_id: Option<i64>,
+    pub restored_task_ids: Vec<i64>,
+    pub restored_assignments: i64,
+    /// Parents reopened because an open subtask came back
+    pub changed_task_ids: Vec<i64>,
+    pub affected_days: Vec<String>,
+}
+
+/// What `undo` or `redo` just reversed or replayed.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UndoResult {
+    pub label: String,
+    /// Tracker days whose counters were recomputed
+    pub affected_days: Vec<String>,
+}
+
+/// Labels of the steps `undo` and `redo` would take next, if any.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UndoState {
+    pub undo_label: Option<String>,
+    pub redo_label: Option<String>,
+}
+
+/// A line of the activity log.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ActivityEntry {
+    pub id: i64,
+    /// UTC, `YYYY-MM-DD HH:MM:SS.SSS`
+    pub occurred_at: String,
+    /// The command it came from, e.g. "Edit task" or "Undo: Edit task"
+    pub command: String,
+    /// e.g. `retitled`, `rescheduled`, `assigned`, `completed`; see `activity.rs`
+    pub event_type: String,
+    /// Unset for events about projects, tags, views and settings
+    pub task_id: Option<i64>,
+    /// Title of the task at the time, or the name of what changed
+    pub subject: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+
+/// A task being done on a given date. Recurring tasks have one per occurrence.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaskCompletion {
+    pub id: i64,
+    pub task_id: i64,
+    pub date: String,
+    pub completed_at: String,
+    /// `task_list`, `tracker`, `parent` or `legacy`
+    pub source: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DayTracker {
     pub id: i64,
+    /// Position within the requested range, starting at 1
     pub day_number: i32,
     pub date: String,
     pub completion_status: String,
@@ -34,8 +206,96 @@ pub struct DayTracker {
 pub struct TaskAssignment {
     pub id: i64,
     pub task_id: i64,
-    pub day_number: i32,
+    pub date: String,
     pub assigned_by: String,
+    /// Set once the assignment has been moved: where from, by whom and when
+    pub moved_from: Option<String>,
+    pub moved_by: Option<String>,
+    pub moved_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RescheduleResult {
+    pub moved_task_ids: Vec<i64>,
+    pub affected_days: Vec<String>,
+}
+
+/// What changed when a task was checked off or reopened.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CompletionResult {
+    pub task_id: i64,
+    /// Whether the task is closed now; a recurring task that moved on to its
+    /// next occurrence stays open
+    pub is_completed: bool,
+    /// Every task whose completion changed, starting with `task_id`
+    pub changed_task_ids: Vec<i64>,
+    /// Tracker days whose counts were recomputed
+    pub affected_days: Vec<String>,
+}
+
+/// How completing or reopening a task spreads through its tree. Kept in
+/// `app_settings`; everything is on by default.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompletionRules {
+    /// Completing a task completes its subtasks
+    pub complete_subtasks: bool,
+    /// Reopening a task reopens its completed subtasks
+    pub reopen_subtasks: bool,
+    /// Completing the last open subtask completes the parent
+    pub complete_parent: bool,
+    /// Reopening a subtask (or adding an open one) reopens a completed parent
+    pub reopen_parent: bool,
+}
+
+impl Default for CompletionRules {
+    fn default() -> Self {
+        CompletionRules {
+            complete_subtasks: true,
+            reopen_subtasks: true,
+            complete_parent: true,
+            reopen_parent: true,
+        }
+    }
+}
+
+/// What changed when a task was moved to another parent.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReparentResult {
+    pub task_id: i64,
+    pub parent_id: Option<i64>,
+    /// Parents that were closed or reopened because their subtasks changed
+    pub changed_task_ids: Vec<i64>,
+    pub affected_days: Vec<String>,
+}
+
+/// A named filter, see `filters.rs` for the language.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SavedView {
+    pub id: i64,
+    pub name: String,
+    pub filter: String,
+    /// Defaults to the due date
+    pub sort_by: Option<TaskSort>,
+}
+
+/// A task found by `search_tasks`, best match first.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaskSearchResult {
+    /// Without its subtasks, which are results of their own if they match
+    pub task: Task,
+    /// The whole title, with the matching terms marked
+    pub title: Vec<TextPart>,
+    /// An excerpt of the description around the matches; empty without one
+    pub description: Vec<TextPart>,
+    /// The task's ancestors from the top-level task down to its parent
+    pub path: Vec<TaskPathEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaskPathEntry {
+    pub id: i64,
+    pub title: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -45,7 +305,9 @@ pub struct PomodoroSession {
     pub start_time: String,
     pub end_time: Option<String>,
     pub duration_minutes: i32,
+    pub break_minutes: i32,
     pub completed: bool,
+    pub status: String,
     pub date: String,
 }
 
@@ -59,86 +321,35 @@ pub struct UserStats {
     pub last_study_date: Option<String>,
 }
 
-pub fn initialize_database() -> Result<Connection> {
-    let conn = Connection::open("ultimate_todo.db")?;
+pub fn initialize_database(path: &Path) -> Result<Connection, MigrationError> {
+    let mut conn = Connection::open(path)?;
 
-    // Create tasks table
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS tasks (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            title TEXT NOT NULL,
-            description TEXT,
-            is_completed BOOLEAN NOT NULL DEFAULT 0,
-            parent_id INTEGER,
-            due_date TEXT,
-            is_recurring BOOLEAN NOT NULL DEFAULT 0,
-            recurrence_pattern TEXT,
-            created_at TEXT NOT NULL DEFAULT (datetime('now')),
-            FOREIGN KEY (parent_id) REFERENCES tasks(id) ON DELETE CASCADE
-        )",
-        [],
-    )?;
-
-    // Create day_tracker table
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS day_tracker (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            day_number INTEGER NOT NULL UNIQUE,
-            date TEXT NOT NULL,
-            completion_status TEXT NOT NULL DEFAULT 'red',
-            tasks_completed INTEGER NOT NULL DEFAULT 0,
-            tasks_total INTEGER NOT NULL DEFAULT 0
-        )",
-        [],
-    )?;
+    migrations::migrate(&mut conn)?;
+    configure_connection(&conn)?;
 
-    // Create task_assignments table
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS task_assignments (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            task_id INTEGER NOT NULL,
-            day_number INTEGER NOT NULL,
-            assigned_by TEXT NOT NULL DEFAULT 'manual',
-            FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
-            FOREIGN KEY (day_number) REFERENCES day_tracker(day_number) ON DELETE CASCADE
-        )",
-        [],
-    )?;
+    Ok(conn)
+}
 
-    // Create pomodoro_sessions table
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS pomodoro_sessions (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            task_id INTEGER,
-            start_time TEXT NOT NULL,
-            end_time TEXT,
-            duration_minutes INTEGER NOT NULL,
-            completed BOOLEAN NOT NULL DEFAULT 0,
-            date TEXT NOT NULL,
-            FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE SET NULL
-        )",
-        [],
-    )?;
+/// Per-connection settings that SQLite does not persist in the file.
+pub fn configure_connection(conn: &Connection) -> rusqlite::Result<()> {
+    conn.pragma_update(None, "foreign_keys", true)
+}
 
-    // Create user_stats table
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS user_stats (
-            id INTEGER PRIMARY KEY DEFAULT 1,
-            current_streak INTEGER NOT NULL DEFAULT 0,
-            longest_streak INTEGER NOT NULL DEFAULT 0,
-            total_tasks_completed INTEGER NOT NULL DEFAULT 0,
-            total_study_minutes INTEGER NOT NULL DEFAULT 0,
-            last_study_date TEXT
-        )",
-        [],
-    )?;
+pub fn get_setting(conn: &Connection, key: &str) -> rusqlite::Result<Option<String>> {
+    conn.query_row("SELECT value FROM app_settings WHERE key = ?1", [key], |row| row.get(0))
+        .optional()
+}
 
-    // Initialize user_stats if not exists
+pub fn set_setting(conn: &Connection, key: &str, value: &str) -> rusqlite::Result<()> {
     conn.execute(
-        "INSERT OR IGNORE INTO user_stats (id, current_streak, longest_streak, total_tasks_completed, total_study_minutes)
-         VALUES (1, 0, 0, 0, 0)",
-        [],
+        "INSERT INTO app_settings (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        [key, value],
     )?;
+    Ok(())
+}
 
-    Ok(conn)
+pub fn clear_setting(conn: &Connection, key: &str) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM app_settings WHERE key = ?1", [key])?;
+    Ok(())
 }
@@ -0,0 +1,86 @@
+use rusqlite::ErrorCode;
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::fmt;
+use std::sync::PoisonError;
+
+/// Error returned by every Tauri command.
+///
+/// Serialized as `{ "code": "NOT_FOUND", "message": "..." }`. The codes are
+/// part of the frontend contract (see `AppErrorCode` in `src/utils/tauri.ts`),
+/// so don't rename them.
+#[derive(Debug)]
+pub enum AppError {
+    NotFound(String),
+    Validation(String),
+    Conflict(String),
+    Database(rusqlite::Error),
+    Io(std::io::Error),
+    Poisoned,
+}
+
+pub type AppResult<T> = Result<T, AppError>;
+
+impl AppError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::NotFound(_) => "NOT_FOUND",
+            AppError::Validation(_) => "VALIDATION",
+            AppError::Conflict(_) => "CONFLICT",
+            AppError::Database(_) => "DATABASE",
+            AppError::Io(_) => "IO",
+            AppError::Poisoned => "POISONED",
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::NotFound(msg) | AppError::Validation(msg) | AppError::Conflict(msg) => {
+                write!(f, "{}", msg)
+            }
+            AppError::Database(e) => write!(f, "Database error: {}", e),
+            AppError::Io(e) => write!(f, "I/O error: {}", e),
+            AppError::Poisoned => write!(
+                f,
+                "The database connection is unavailable after an earlier failure; please restart the app"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(e: rusqlite::Error) -> Self {
+        match &e {
+            rusqlite::Error::QueryReturnedNoRows => AppError::NotFound("Record not found".to_string()),
+            rusqlite::Error::SqliteFailure(err, msg) if err.code == ErrorCode::ConstraintViolation => {
+                AppError::Conflict(msg.clone().unwrap_or_else(|| e.to_string()))
+            }
+            _ => AppError::Database(e),
+        }
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> Self {
+        AppError::Io(e)
+    }
+}
+
+impl<T> From<PoisonError<T>> for AppError {
+    fn from(_: PoisonError<T>) -> Self {
+        AppError::Poisoned
+    }
+}
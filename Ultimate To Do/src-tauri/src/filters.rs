@@ -0,0 +1,525 @@
+//! A small filter language for task lists, compiled to parameterized SQL.
+//!
+//! A filter is a list of terms that all have to match, e.g.
+//! `due:<today done:false project:Work tag:urgent`. Terms can be combined
+//! with `or`, negated with `not` or a leading `-`, and grouped in
+//! parentheses. Values with spaces go in double quotes (`tag:"deep work"`).
+//!
+//! | Term | Matches |
+//! |------|---------|
+//! | `due:<today`, `due:>=2026-01-01`, `due:today+7` | due date compared to a date (`=` if no operator) |
+//! | `due:none`, `due:any` | tasks without / with a due date |
+//! | `done:true`, `recurring:false` | completed / recurring tasks or not |
+//! | `tag:name`, `project:name`, `project:none` | by tag or project name |
+//! | `parent:none`, `parent:any`, `parent:12`, `parent:"Title"` | top-level tasks, subtasks, or subtasks of a given task |
+//! | `priority:>=medium`, `difficulty:<3`, `estimate:<=30`, `estimate:none` | compared to a number |
+//! | a word or `"a phrase"` | titles and descriptions, as in the search box |
+//!
+//! Dates are `today`, `yesterday`, `tomorrow`, `today+N` / `today-N` days, or
+//! `YYYY-MM-DD`. Priorities can be numbers (0-3) or `none`, `low`, `medium`
+//! and `high`.
+
+use crate::search;
+use chrono::{Duration, NaiveDate};
+use rusqlite::types::Value;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    /// Matches everything when empty
+    All(Vec<Filter>),
+    Any(Vec<Filter>),
+    Not(Box<Filter>),
+    Term(Term),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Term {
+    Due(Presence<(Comparison, DateValue)>),
+    Done(bool),
+    Recurring(bool),
+    Tag(String),
+    Project(Option<String>),
+    Parent(ParentRef),
+    Priority(Comparison, i64),
+    Difficulty(Comparison, i64),
+    Estimate(Presence<(Comparison, i64)>),
+    Text(String),
+}
+
+/// A condition on a column that can be empty.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Presence<T> {
+    None,
+    Any,
+    Is(T),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater,
+}
+
+/// Relative dates are resolved when the filter runs, so a saved view keeps
+/// meaning "overdue" from one day to the next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateValue {
+    Today(i64),
+    On(NaiveDate),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParentRef {
+    None,
+    Any,
+    Id(i64),
+    Title(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    Not,
+    Or,
+    And,
+    Word {
+        key: Option<String>,
+        value: String,
+        /// Quoted text is never read as an operator or keyword
+        quoted: bool,
+    },
+}
+
+pub fn parse(input: &str) -> Result<Filter, String> {
+    let tokens = tokenize(input)?;
+    let mut parser = Parser { tokens, next: 0 };
+    let filter = parser.any()?;
+    match parser.tokens.get(parser.next) {
+        None => Ok(filter),
+        Some(Token::Close) => Err("Unmatched ')' in filter".to_string()),
+        Some(_) => unreachable!("`any` stops only at ')' or the end"),
+    }
+}
+
+impl Filter {
+    /// A SQL condition on the task aliased `t`, with a `?` for each parameter.
+    pub fn to_sql(&self, today: NaiveDate) -> (String, Vec<Value>) {
+        let mut params = Vec::new();
+        let sql = self.write_sql(today, &mut params);
+        (sql, params)
+    }
+
+    /// Whether any term picks tasks by project, in which case archived
+    /// projects shouldn't be left out.
+    pub fn names_project(&self) -> bool {
+        match self {
+            Filter::All(filters) | Filter::Any(filters) => filters.iter().any(Filter::names_project),
+            Filter::Not(filter) => filter.names_project(),
+            Filter::Term(term) => matches!(term, Term::Project(_)),
+        }
+    }
+
+    fn write_sql(&self, today: NaiveDate, params: &mut Vec<Value>) -> String {
+        match self {
+            Filter::All(filters) if filters.is_empty() => "1".to_string(),
+            Filter::All(filters) => join_sql(filters, " AND ", today, params),
+            Filter::Any(filters) => join_sql(filters, " OR ", today, params),
+            Filter::Not(filter) => format!("NOT ({})", filter.write_sql(today, params)),
+            Filter::Term(term) => term.write_sql(today, params),
+        }
+    }
+}
+
+fn join_sql(filters: &[Filter], separator: &str, today: NaiveDate, params: &mut Vec<Value>) -> String {
+    let parts: Vec<String> = filters
+        .iter()
+        .map(|filter| format!("({})", filter.write_sql(today, params)))
+        .collect();
+    parts.join(separator)
+}
+
+impl Term {
+    // Every condition is true or false, never NULL, so `NOT` flips it cleanly
+    fn write_sql(&self, today: NaiveDate, params: &mut Vec<Value>) -> String {
+        match self {
+            Term::Due(Presence::None) => "t.due_date IS NULL".to_string(),
+            Term::Due(Presence::Any) => "t.due_date IS NOT NULL".to_string(),
+            Term::Due(Presence::Is((comparison, date))) => {
+                let date = match date {
+                    DateValue::Today(offset) => today + Duration::days(*offset),
+                    DateValue::On(date) => *date,
+                };
+                params.push(Value::Text(date.format("%Y-%m-%d").to_string()));
+                format!("COALESCE(date(t.due_date) {} ?, 0)", comparison.operator())
+            }
+            Term::Done(done) => {
+                params.push(Value::Integer(i64::from(*done)));
+                "t.is_completed = ?".to_string()
+            }
+            Term::Recurring(recurring) => {
+                params.push(Value::Integer(i64::from(*recurring)));
+                "t.is_recurring = ?".to_string()
+            }
+            Term::Tag(name) => {
+                params.push(Value::Text(name.clone()));
+                "EXISTS (SELECT 1 FROM task_tags tt JOIN tags tg ON tg.id = tt.tag_id \
+                 WHERE tt.task_id = t.id AND tg.name = ? COLLATE NOCASE)"
+                    .to_string()
+            }
+            Term::Project(None) => "t.project_id IS NULL".to_string(),
+            Term::Project(Some(name)) => {
+                params.push(Value::Text(name.clone()));
+                "EXISTS (SELECT 1 FROM projects p WHERE p.id = t.project_id AND p.name = ? COLLATE NOCASE)"
+                    .to_string()
+            }
+            Term::Parent(ParentRef::None) => "t.parent_id IS NULL".to_string(),
+            Term::Parent(ParentRef::Any) => "t.parent_id IS NOT NULL".to_string(),
+            Term::Parent(ParentRef::Id(id)) => {
+                params.push(Value::Integer(*id));
+                "COALESCE(t.parent_id = ?, 0)".to_string()
+            }
+            Term::Parent(ParentRef::Title(title)) => {
+                params.push(Value::Text(title.clone()));
+                "EXISTS (SELECT 1 FROM tasks pt WHERE pt.id = t.parent_id AND pt.title = ? COLLATE NOCASE)"
+                    .to_string()
+            }
+            Term::Priority(comparison, value) => {
+                params.push(Value::Integer(*value));
+                format!("t.priority {} ?", comparison.operator())
+            }
+            Term::Difficulty(comparison, value) => {
+                params.push(Value::Integer(*value));
+                format!("COALESCE(t.difficulty {} ?, 0)", comparison.operator())
+            }
+            Term::Estimate(Presence::None) => "t.estimated_minutes IS NULL".to_string(),
+            Term::Estimate(Presence::Any) => "t.estimated_minutes IS NOT NULL".to_string(),
+            Term::Estimate(Presence::Is((comparison, minutes))) => {
+                params.push(Value::Integer(*minutes));
+                format!("COALESCE(t.estimated_minutes {} ?, 0)", comparison.operator())
+            }
+            Term::Text(expression) => {
+                params.push(Value::Text(expression.clone()));
+                "t.id IN (SELECT rowid FROM tasks_fts WHERE tasks_fts MATCH ?)".to_string()
+            }
+        }
+    }
+}
+
+impl Comparison {
+    fn operator(self) -> &'static str {
+        match self {
+            Comparison::Less => "<",
+            Comparison::LessOrEqual => "<=",
+            Comparison::Equal => "=",
+            Comparison::GreaterOrEqual => ">=",
+            Comparison::Greater => ">",
+        }
+    }
+
+    // Splits a leading operator off `value`
+    fn split(value: &str) -> (Comparison, &str) {
+        for (prefix, comparison) in [
+            ("<=", Comparison::LessOrEqual),
+            (">=", Comparison::GreaterOrEqual),
+            ("<", Comparison::Less),
+            (">", Comparison::Greater),
+            ("=", Comparison::Equal),
+        ] {
+            if let Some(rest) = value.strip_prefix(prefix) {
+                return (comparison, rest);
+            }
+        }
+        (Comparison::Equal, value)
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            _ if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            _ => {
+                let mut key = None;
+                let mut text = String::new();
+                let mut quoted = false;
+                let mut negated = false;
+                if c == '-' {
+                    chars.next();
+                    negated = true;
+                }
+
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || c == '(' || c == ')' {
+                        break;
+                    }
+                    chars.next();
+                    match c {
+                        '"' => {
+                            quoted = true;
+                            let mut closed = false;
+                            for c in chars.by_ref() {
+                                if c == '"' {
+                                    closed = true;
+                                    break;
+                                }
+                                text.push(c);
+                            }
+                            if !closed {
+                                return Err("Unclosed '\"' in filter".to_string());
+                            }
+                        }
+                        ':' if key.is_none() && !quoted => key = Some(std::mem::take(&mut text)),
+                        _ => text.push(c),
+                    }
+                }
+
+                if negated {
+                    tokens.push(Token::Not);
+                    // `-(...)` negates the whole group
+                    if key.is_none() && text.is_empty() && !quoted {
+                        if chars.peek() == Some(&'(') {
+                            continue;
+                        }
+                        return Err("'-' must be followed by a term".to_string());
+                    }
+                }
+                let operator = match (&key, quoted) {
+                    (None, false) => match text.to_ascii_lowercase().as_str() {
+                        "and" => Some(Token::And),
+                        "or" => Some(Token::Or),
+                        "not" => Some(Token::Not),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                tokens.push(operator.unwrap_or(Token::Word {
+                    key: key.map(|key| key.to_ascii_lowercase()),
+                    value: text,
+                    quoted,
+                }));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    next: usize,
+}
+
+impl Parser {
+    // Terms joined by `or`, up to a `)` or the end
+    fn any(&mut self) -> Result<Filter, String> {
+        let mut alternatives = vec![self.all()?];
+        while self.tokens.get(self.next) == Some(&Token::Or) {
+            self.next += 1;
+            alternatives.push(self.all()?);
+        }
+        Ok(if alternatives.len() == 1 {
+            alternatives.remove(0)
+        } else {
+            Filter::Any(alternatives)
+        })
+    }
+
+    // Terms next to each other, or joined by `and`
+    fn all(&mut self) -> Result<Filter, String> {
+        let mut filters = Vec::new();
+        loop {
+            match self.tokens.get(self.next) {
+                None | Some(Token::Close) | Some(Token::Or) => break,
+                Some(Token::And) => {
+                    self.next += 1;
+                    let followed_by_term = !matches!(
+                        self.tokens.get(self.next),
+                        None | Some(Token::Close) | Some(Token::Or) | Some(Token::And)
+                    );
+                    if filters.is_empty() || !followed_by_term {
+                        return Err("'and' must come between two terms".to_string());
+                    }
+                }
+                _ => filters.push(self.unary()?),
+            }
+        }
+
+        match self.tokens.get(self.next) {
+            Some(Token::Or) if filters.is_empty() => Err("'or' must come between two terms".to_string()),
+            _ if filters.is_empty() && self.next > 0 => Err("Expected a term".to_string()),
+            _ if filters.len() == 1 => Ok(filters.remove(0)),
+            _ => Ok(Filter::All(filters)),
+        }
+    }
+
+    fn unary(&mut self) -> Result<Filter, String> {
+        let token = self.tokens.get(self.next).cloned();
+        self.next += 1;
+        match token {
+            Some(Token::Not) => Ok(Filter::Not(Box::new(self.unary()?))),
+            Some(Token::Open) => {
+                let filter = self.any()?;
+                if self.tokens.get(self.next) != Some(&Token::Close) {
+                    return Err("Missing ')' in filter".to_string());
+                }
+                self.next += 1;
+                Ok(filter)
+            }
+            Some(Token::Word { key, value, quoted }) => parse_term(key.as_deref(), &value, quoted).map(Filter::Term),
+            _ => Err("Expected a term".to_string()),
+        }
+    }
+}
+
+fn parse_term(key: Option<&str>, value: &str, quoted: bool) -> Result<Term, String> {
+    let Some(key) = key else {
+        let text = if quoted { format!("\"{}\"", value) } else { value.to_string() };
+        return search::match_expression(&text)
+            .map(Term::Text)
+            .ok_or_else(|| format!("Nothing to search for in '{}'", value));
+    };
+    if value.is_empty() {
+        return Err(format!("'{}:' needs a value", key));
+    }
+
+    match key {
+        "due" => Ok(Term::Due(parse_presence(value, quoted, |value| {
+            let (comparison, date) = Comparison::split(value);
+            Ok((comparison, parse_date_value(date)?))
+        })?)),
+        "done" => parse_bool(key, value).map(Term::Done),
+        "recurring" => parse_bool(key, value).map(Term::Recurring),
+        "tag" => Ok(Term::Tag(value.to_string())),
+        "project" if !quoted && value.eq_ignore_ascii_case("none") => Ok(Term::Project(None)),
+        "project" => Ok(Term::Project(Some(value.to_string()))),
+        "parent" => Ok(Term::Parent(match parse_presence(value, quoted, |value| Ok(value.to_string()))? {
+            Presence::None => ParentRef::None,
+            Presence::Any => ParentRef::Any,
+            Presence::Is(value) => match value.parse() {
+                Ok(id) if !quoted => ParentRef::Id(id),
+                _ => ParentRef::Title(value),
+            },
+        })),
+        "priority" => {
+            let (comparison, priority) = Comparison::split(value);
+            let priority = match priority.to_ascii_lowercase().as_str() {
+                "none" => 0,
+                "low" => 1,
+                "medium" => 2,
+                "high" => 3,
+                other => parse_number(key, other)?,
+            };
+            Ok(Term::Priority(comparison, priority))
+        }
+        "difficulty" => {
+            let (comparison, difficulty) = Comparison::split(value);
+            Ok(Term::Difficulty(comparison, parse_number(key, difficulty)?))
+        }
+        "estimate" => Ok(Term::Estimate(parse_presence(value, quoted, |value| {
+            let (comparison, minutes) = Comparison::split(value);
+            Ok((comparison, parse_number(key, minutes)?))
+        })?)),
+        _ => Err(format!(
+            "Unknown filter '{}:'; use due, done, recurring, tag, project, parent, priority, difficulty or estimate",
+            key
+        )),
+    }
+}
+
+fn parse_presence<T>(
+    value: &str,
+    quoted: bool,
+    parse: impl FnOnce(&str) -> Result<T, String>,
+) -> Result<Presence<T>, String> {
+    match value.to_ascii_lowercase().as_str() {
+        "none" if !quoted => Ok(Presence::None),
+        "any" if !quoted => Ok(Presence::Any),
+        _ => parse(value).map(Presence::Is),
+    }
+}
+
+fn parse_bool(key: &str, value: &str) -> Result<bool, String> {
+    match value.to_ascii_lowercase().as_str() {
+        "true" | "yes" => Ok(true),
+        "false" | "no" => Ok(false),
+        _ => Err(format!("'{}:' takes true or false, got '{}'", key, value)),
+    }
+}
+
+fn parse_number(key: &str, value: &str) -> Result<i64, String> {
+    value
+        .parse()
+        .map_err(|_| format!("'{}:' takes a number, got '{}'", key, value))
+}
+
+fn parse_date_value(value: &str) -> Result<DateValue, String> {
+    let lower = value.to_ascii_lowercase();
+    match lower.as_str() {
+        "today" => return Ok(DateValue::Today(0)),
+        "tomorrow" => return Ok(DateValue::Today(1)),
+        "yesterday" => return Ok(DateValue::Today(-1)),
+        _ => {}
+    }
+
+    if let Some(offset) = lower.strip_prefix("today") {
+        let days = match offset.strip_prefix('+') {
+            Some(days) => days.parse::<i64>().ok(),
+            None => offset.strip_prefix('-').and_then(|days| days.parse::<i64>().ok()).map(|days| -days),
+        };
+        // Far enough for any due date, and keeps the addition from overflowing
+        return days
+            .filter(|days| days.abs() <= 100 * 366)
+            .map(DateValue::Today)
+            .ok_or_else(|| format!("Unrecognized date '{}'", value));
+    }
+
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(DateValue::On)
+        .map_err(|_| format!("Unrecognized date '{}'; use YYYY-MM-DD, today, today+N or today-N", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(name: &str) -> Filter {
+        Filter::Term(Term::Tag(name.to_string()))
+    }
+
+    #[test]
+    fn and_joins_terms_like_a_space() {
+        let both = Filter::All(vec![tag("milk"), tag("eggs")]);
+        assert_eq!(parse("tag:milk and tag:eggs"), Ok(both.clone()));
+        assert_eq!(parse("tag:milk tag:eggs"), Ok(both));
+    }
+
+    #[test]
+    fn and_needs_a_term_on_each_side() {
+        for input in ["and milk", "milk and", "milk and and eggs", "(milk and) eggs", "milk and or eggs"] {
+            assert_eq!(parse(input), Err("'and' must come between two terms".to_string()), "{}", input);
+        }
+    }
+
+    #[test]
+    fn or_needs_a_term_on_each_side() {
+        assert!(parse("or milk").is_err());
+        assert!(parse("milk or").is_err());
+        assert_eq!(parse("tag:milk or tag:eggs"), Ok(Filter::Any(vec![tag("milk"), tag("eggs")])));
+    }
+}
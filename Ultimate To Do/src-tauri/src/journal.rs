@@ -0,0 +1,272 @@
+//! Undo and redo for the mutating commands.
+//!
+//! Each command runs as one journal entry. While the entry is open, triggers
+//! on the journaled tables (see the `undo_journal` migration) copy every row
+//! the command changes into `journal_changes`, as JSON before and after.
+//! Undoing an entry writes the old copies back, newest change first; redoing
+//! it writes the new copies again, oldest first. Entries are undone strictly
+//! newest first, and a new change drops whatever was undone before it.
+
+use rusqlite::types::Value;
+use rusqlite::{Connection, OptionalExtension};
+use serde_json::{Map, Value as Json};
+use std::collections::BTreeSet;
+
+/// Entries kept for undo; the oldest go first.
+const MAX_ENTRIES: i64 = 200;
+
+/// Tables whose rows say which tracker days count a task.
+const DAY_TABLES: &[&str] = &["task_assignments", "task_completions"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Undo,
+    Redo,
+}
+
+/// A row as it was before and after a change; `None` where it didn't exist.
+pub struct Change {
+    pub table: String,
+    pub row_id: i64,
+    pub old_row: Option<Map<String, Json>>,
+    pub new_row: Option<Map<String, Json>>,
+}
+
+/// An entry that was just undone or redone.
+pub struct Step {
+    pub entry_id: i64,
+    pub label: String,
+    /// What was written, in order: for an undo, the entry's changes reversed
+    /// and each one turned around.
+    pub changes: Vec<Change>,
+    /// Dates whose assignments or completions changed, whose tracker
+    /// counters are now stale.
+    pub dates: Vec<String>,
+}
+
+/// Opens an entry described by `label` and returns its ID; changes are
+/// recorded until `finish`.
+pub fn begin(conn: &Connection, label: &str) -> rusqlite::Result<i64> {
+    conn.execute("INSERT INTO journal_entries (label) VALUES (?1)", [label])?;
+    let entry_id = conn.last_insert_rowid();
+    conn.execute("UPDATE journal_state SET entry_id = ?1", [entry_id])?;
+    Ok(entry_id)
+}
+
+/// Closes the open entry and returns whether it was kept. An entry that
+/// changed nothing is dropped, so it takes no undo step and doesn't cut off
+/// redo.
+pub fn finish(conn: &Connection) -> rusqlite::Result<bool> {
+    let entry_id: i64 = conn.query_row("SELECT entry_id FROM journal_state", [], |row| row.get(0))?;
+    conn.execute("UPDATE journal_state SET entry_id = NULL", [])?;
+
+    // Updates that wrote back the values a row already had
+    conn.execute(
+        "DELETE FROM journal_changes WHERE entry_id = ?1 AND old_row IS new_row",
+        [entry_id],
+    )?;
+    let changed: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM journal_changes WHERE entry_id = ?1)",
+        [entry_id],
+        |row| row.get(0),
+    )?;
+    if !changed {
+        conn.execute("DELETE FROM journal_entries WHERE id = ?1", [entry_id])?;
+        return Ok(false);
+    }
+
+    conn.execute("DELETE FROM journal_entries WHERE undone = 1", [])?;
+    conn.execute(
+        "DELETE FROM journal_entries
+         WHERE id NOT IN (SELECT id FROM journal_entries ORDER BY id DESC LIMIT ?1)",
+        [MAX_ENTRIES],
+    )?;
+    Ok(true)
+}
+
+/// The changes of an entry, in the order they were made.
+pub fn changes(conn: &Connection, entry_id: i64) -> rusqlite::Result<Vec<Change>> {
+    let mut stmt = conn.prepare(
+        "SELECT table_name, row_id, old_row, new_row FROM journal_changes
+         WHERE entry_id = ?1
+         ORDER BY id",
+    )?;
+    let rows = stmt.query_map([entry_id], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, i64>(1)?,
+            row.get::<_, Option<String>>(2)?,
+            row.get::<_, Option<String>>(3)?,
+        ))
+    })?;
+
+    let mut changes = Vec::new();
+    for row in rows {
+        let (table, row_id, old_row, new_row) = row?;
+        changes.push(Change {
+            table,
+            row_id,
+            old_row: old_row.as_deref().map(parse_image).transpose()?,
+            new_row: new_row.as_deref().map(parse_image).transpose()?,
+        });
+    }
+    Ok(changes)
+}
+
+/// Label of the entry the next undo or redo would apply.
+pub fn next_label(conn: &Connection, direction: Direction) -> rusqlite::Result<Option<String>> {
+    next_entry(conn, direction).map(|entry| entry.map(|(_, label)| label))
+}
+
+/// Undoes the newest entry still in effect, or redoes the oldest undone one.
+/// `None` when there is nothing to undo or redo.
+///
+/// Foreign keys are checked at commit, since rows come back one at a time:
+/// a subtask can be restored before its parent. Check `breaks_references`
+/// before committing.
+pub fn apply(conn: &Connection, direction: Direction) -> rusqlite::Result<Option<Step>> {
+    let Some((entry_id, label)) = next_entry(conn, direction)? else {
+        return Ok(None);
+    };
+
+    conn.pragma_update(None, "defer_foreign_keys", true)?;
+
+    let mut changes = changes(conn, entry_id)?;
+    if direction == Direction::Undo {
+        changes.reverse();
+        for change in &mut changes {
+            std::mem::swap(&mut change.old_row, &mut change.new_row);
+        }
+    }
+
+    let mut dates = BTreeSet::new();
+    for change in &changes {
+        match (&change.old_row, &change.new_row) {
+            (_, None) => delete_row(conn, &change.table, change.row_id)?,
+            (None, Some(image)) => insert_row(conn, &change.table, change.row_id, image)?,
+            (Some(_), Some(image)) => update_row(conn, &change.table, change.row_id, image)?,
+        }
+
+        if DAY_TABLES.contains(&change.table.as_str()) {
+            for image in [&change.old_row, &change.new_row].into_iter().flatten() {
+                if let Some(Json::String(date)) = image.get("date") {
+                    dates.insert(date.clone());
+                }
+            }
+        }
+    }
+
+    conn.execute(
+        "UPDATE journal_entries SET undone = ?1 WHERE id = ?2",
+        (direction == Direction::Undo, entry_id),
+    )?;
+
+    Ok(Some(Step {
+        entry_id,
+        label,
+        changes,
+        dates: dates.into_iter().collect(),
+    }))
+}
+
+/// Whether applying `step` left rows pointing at rows that are gone, which
+/// happens when something that isn't journaled deleted them since. The
+/// journaled tables' foreign keys all cascade or set null on delete, so only
+/// the tables the step wrote can have such rows.
+pub fn breaks_references(conn: &Connection, step: &Step) -> rusqlite::Result<bool> {
+    let tables: BTreeSet<&str> = step.changes.iter().map(|change| change.table.as_str()).collect();
+    for table in tables {
+        let broken: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM pragma_foreign_key_check(?1))",
+            [table],
+            |row| row.get(0),
+        )?;
+        if broken {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Drops an entry that can't be applied any more, with the entries that can
+/// only be reached through it: the older ones for an undo, the undone ones
+/// after it for a redo.
+pub fn forget(conn: &Connection, entry_id: i64, direction: Direction) -> rusqlite::Result<()> {
+    let sql = match direction {
+        Direction::Undo => "DELETE FROM journal_entries WHERE id <= ?1",
+        Direction::Redo => "DELETE FROM journal_entries WHERE id >= ?1",
+    };
+    conn.execute(sql, [entry_id])?;
+    Ok(())
+}
+
+fn next_entry(conn: &Connection, direction: Direction) -> rusqlite::Result<Option<(i64, String)>> {
+    let sql = match direction {
+        Direction::Undo => "SELECT id, label FROM journal_entries WHERE undone = 0 ORDER BY id DESC LIMIT 1",
+        Direction::Redo => "SELECT id, label FROM journal_entries WHERE undone = 1 ORDER BY id ASC LIMIT 1",
+    };
+    conn.query_row(sql, [], |row| Ok((row.get(0)?, row.get(1)?)))
+        .optional()
+}
+
+fn parse_image(text: &str) -> rusqlite::Result<Map<String, Json>> {
+    serde_json::from_str(text)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e)))
+}
+
+fn delete_row(conn: &Connection, table: &str, row_id: i64) -> rusqlite::Result<()> {
+    conn.execute(&format!("DELETE FROM {} WHERE rowid = ?1", quote(table)), [row_id])?;
+    Ok(())
+}
+
+fn insert_row(conn: &Connection, table: &str, row_id: i64, image: &Map<String, Json>) -> rusqlite::Result<()> {
+    let columns: Vec<String> = image.keys().map(|column| quote(column)).collect();
+    let placeholders: Vec<String> = (2..=image.len() + 1).map(|i| format!("?{}", i)).collect();
+    let sql = format!(
+        "INSERT INTO {} (rowid, {}) VALUES (?1, {})",
+        quote(table),
+        columns.join(", "),
+        placeholders.join(", ")
+    );
+
+    let mut params = vec![Value::Integer(row_id)];
+    params.extend(image.values().map(sql_value));
+    conn.execute(&sql, rusqlite::params_from_iter(params))?;
+    Ok(())
+}
+
+fn update_row(conn: &Connection, table: &str, row_id: i64, image: &Map<String, Json>) -> rusqlite::Result<()> {
+    let assignments: Vec<String> = image
+        .keys()
+        .enumerate()
+        .map(|(i, column)| format!("{} = ?{}", quote(column), i + 2))
+        .collect();
+    let sql = format!(
+        "UPDATE {} SET {} WHERE rowid = ?1",
+        quote(table),
+        assignments.join(", ")
+    );
+
+    let mut params = vec![Value::Integer(row_id)];
+    params.extend(image.values().map(sql_value));
+    conn.execute(&sql, rusqlite::params_from_iter(params))?;
+    Ok(())
+}
+
+fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+// `json_object` keeps SQLite's storage classes apart, so they map back as-is
+fn sql_value(value: &Json) -> Value {
+    match value {
+        Json::Null => Value::Null,
+        Json::Bool(b) => Value::Integer(*b as i64),
+        Json::Number(n) => match n.as_i64() {
+            Some(i) => Value::Integer(i),
+            None => Value::Real(n.as_f64().unwrap_or_default()),
+        },
+        Json::String(s) => Value::Text(s.clone()),
+        other => Value::Text(other.to_string()),
+    }
+}
@@ -1,16 +1,62 @@
+mod activity;
 mod database;
 mod commands;
+mod config;
+mod error;
+mod filters;
+mod journal;
+mod migrations;
+mod ordering;
+mod planner;
+mod pomodoro;
+mod progress;
+mod recurrence;
+mod search;
+mod streaks;
 
 use database::{initialize_database, DbState};
+
+/// Entry points for `benches/`, which can only reach the public API.
+#[doc(hidden)]
+pub mod bench {
+    pub use crate::commands::load_tasks;
+    pub use crate::database::{initialize_database, Task, TaskQuery};
+}
+use pomodoro::PomodoroTimer;
 use std::sync::Mutex;
+use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    let conn = initialize_database().expect("Failed to initialize database");
-    
     tauri::Builder::default()
-        .manage(DbState {
-            conn: Mutex::new(conn),
+        .setup(|app| {
+            let location = config::resolve_database_location(
+                std::env::args().skip(1),
+                std::env::var(config::DB_PATH_ENV).ok(),
+                &app.path().app_data_dir()?,
+                &app.path().app_config_dir()?,
+            )?;
+            config::migrate_legacy_database(&location)?;
+
+            let mut conn = initialize_database(&location.path)?;
+
+            let timer = PomodoroTimer::default();
+            let tx = conn.transaction()?;
+            if let Some(session_id) = timer.recover(&tx, pomodoro::now())? {
+                activity::record_session(&tx, activity::TIMER_COMMAND, "focus_completed", session_id)?;
+                commands::update_user_stats(&tx)?;
+            }
+            tx.commit()?;
+            commands::purge_expired_trash(&mut conn)?;
+
+            app.manage(DbState {
+                conn: Mutex::new(conn),
+            });
+            app.manage(timer);
+            app.manage(location);
+
+            pomodoro::spawn_ticker(app.handle().clone());
+            Ok(())
         })
         .plugin(tauri_plugin_opener::init())
         .invoke_handler(tauri::generate_handler![
@@ -18,16 +64,76 @@ pub fn run() {
             commands::get_all_tasks,
             commands::update_task,
             commands::delete_task,
+            commands::get_trash,
+            commands::restore_task,
+            commands::purge_task,
+            commands::empty_trash,
+            commands::get_trash_retention,
+            commands::set_trash_retention,
+            commands::move_task,
+            commands::indent_task,
+            commands::outdent_task,
+            commands::promote_task,
+            commands::get_subtree_progress,
             commands::toggle_task_completion,
+            commands::complete_task_for_date,
+            commands::uncomplete_task_for_date,
+            commands::get_task_completions,
+            commands::get_completion_rules,
+            commands::set_completion_rules,
             commands::initialize_60_days,
             commands::get_day_tracker_data,
+            commands::get_tracker_history,
+            commands::set_tracker_anchor,
+            commands::get_tracker_anchor,
             commands::assign_task_to_day,
+            commands::unassign_task_from_day,
+            commands::move_task_assignment,
+            commands::reschedule_incomplete_tasks,
             commands::update_day_status,
             commands::get_tasks_for_day,
+            commands::preview_auto_assign,
+            commands::apply_auto_assign,
+            commands::get_planner_capacity,
+            commands::set_planner_capacity,
             commands::start_pomodoro_session,
+            commands::pause_pomodoro_session,
+            commands::resume_pomodoro_session,
+            commands::abort_pomodoro_session,
+            commands::get_pomodoro_timer,
             commands::complete_pomodoro_session,
             commands::get_pomodoro_stats,
             commands::get_session_history,
+            commands::get_pomodoro_tag_stats,
+            commands::undo,
+            commands::redo,
+            commands::get_undo_state,
+            commands::get_task_activity,
+            commands::get_recent_activity,
+            commands::search_tasks,
+            commands::filter_tasks,
+            commands::get_saved_views,
+            commands::create_saved_view,
+            commands::update_saved_view,
+            commands::delete_saved_view,
+            commands::run_saved_view,
+            commands::get_projects,
+            commands::create_project,
+            commands::update_project,
+            commands::set_project_archived,
+            commands::reorder_projects,
+            commands::delete_project,
+            commands::move_task_to_project,
+            commands::get_all_tags,
+            commands::create_tag,
+            commands::rename_tag,
+            commands::set_tag_color,
+            commands::merge_tags,
+            commands::delete_tag,
+            commands::add_tag_to_task,
+            commands::remove_tag_from_task,
+            commands::get_database_location,
+            commands::set_database_path,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,865 @@
+use rusqlite::{Connection, Transaction};
+use std::fmt;
+
+/// A single schema upgrade. `up` runs inside a transaction together with the
+/// `user_version` bump, so a failed step leaves the database untouched.
+struct Migration {
+    version: i32,
+    description: &'static str,
+    up: fn(&Transaction) -> rusqlite::Result<()>,
+}
+
+// Append new steps to the end; never edit or reorder a step that has shipped.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "initial schema",
+        up: initial_schema,
+    },
+    Migration {
+        version: 2,
+        description: "repair rows orphaned while foreign keys were not enforced",
+        up: repair_orphans,
+    },
+    Migration {
+        version: 3,
+        description: "pomodoro timer state and pause intervals",
+        up: pomodoro_timer_state,
+    },
+    Migration {
+        version: 4,
+        description: "file pomodoro sessions under local dates",
+        up: local_session_dates,
+    },
+    Migration {
+        version: 5,
+        description: "key the day tracker and assignments by calendar date",
+        up: date_keyed_tracker,
+    },
+    Migration {
+        version: 6,
+        description: "per-date task completions",
+        up: task_completions,
+    },
+    Migration {
+        version: 7,
+        description: "track moved assignments",
+        up: assignment_moves,
+    },
+    Migration {
+        version: 8,
+        description: "task priority, effort and difficulty",
+        up: task_effort,
+    },
+    Migration {
+        version: 9,
+        description: "tags",
+        up: tags,
+    },
+    Migration {
+        version: 10,
+        description: "projects",
+        up: projects,
+    },
+    Migration {
+        version: 11,
+        description: "manual task order",
+        up: task_positions,
+    },
+    Migration {
+        version: 12,
+        description: "index assignments by date",
+        up: tracker_lookup_indexes,
+    },
+    Migration {
+        version: 13,
+        description: "full-text task search",
+        up: task_search,
+    },
+    Migration {
+        version: 14,
+        description: "saved views",
+        up: saved_views,
+    },
+    Migration {
+        version: 15,
+        description: "task trash",
+        up: task_trash,
+    },
+    Migration {
+        version: 16,
+        description: "undo history",
+        up: undo_journal,
+    },
+    Migration {
+        version: 17,
+        description: "activity log",
+        up: activity_log,
+    },
+    Migration {
+        version: 18,
+        description: "anchor recurring tasks on their first due date",
+        up: recurrence_anchors,
+    },
+];
+
+/// The schema version this binary writes.
+pub const SCHEMA_VERSION: i32 = MIGRATIONS[MIGRATIONS.len() - 1].version;
+
+#[derive(Debug)]
+pub enum MigrationError {
+    Sqlite(rusqlite::Error),
+    DatabaseTooNew { found: i32, supported: i32 },
+    Failed { version: i32, description: &'static str, source: rusqlite::Error },
+    ForeignKeyViolation { version: i32, table: String },
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrationError::Sqlite(e) => write!(f, "{}", e),
+            MigrationError::DatabaseTooNew { found, supported } => write!(
+                f,
+                "Database schema version {} is newer than this version of the app supports ({}); please update the app",
+                found, supported
+            ),
+            MigrationError::Failed { version, description, source } => write!(
+                f,
+                "Migration {} ({}) failed: {}",
+                version, description, source
+            ),
+            MigrationError::ForeignKeyViolation { version, table } => write!(
+                f,
+                "Migration {} left rows in '{}' that violate a foreign key",
+                version, table
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+impl From<rusqlite::Error> for MigrationError {
+    fn from(e: rusqlite::Error) -> Self {
+        MigrationError::Sqlite(e)
+    }
+}
+
+pub fn schema_version(conn: &Connection) -> rusqlite::Result<i32> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+}
+
+/// Brings the database up to `SCHEMA_VERSION`, applying each pending step in
+/// order. Refuses to touch a database written by a newer build.
+///
+/// Foreign keys are switched off while migrating so steps can rebuild tables;
+/// each step is checked for violations before it commits. The caller is
+/// responsible for turning enforcement back on.
+pub fn migrate(conn: &mut Connection) -> Result<(), MigrationError> {
+    let current = schema_version(conn)?;
+
+    if current > SCHEMA_VERSION {
+        return Err(MigrationError::DatabaseTooNew {
+            found: current,
+            supported: SCHEMA_VERSION,
+        });
+    }
+
+    conn.pragma_update(None, "foreign_keys", false)?;
+
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        let failed = |source| MigrationError::Failed {
+            version: migration.version,
+            description: migration.description,
+            source,
+        };
+
+        let tx = conn.transaction().map_err(failed)?;
+        (migration.up)(&tx).map_err(failed)?;
+
+        if let Some(table) = first_foreign_key_violation(&tx).map_err(failed)? {
+            return Err(MigrationError::ForeignKeyViolation {
+                version: migration.version,
+                table,
+            });
+        }
+
+        tx.pragma_update(None, "user_version", migration.version)
+            .map_err(failed)?;
+        tx.commit().map_err(failed)?;
+    }
+
+    Ok(())
+}
+
+fn first_foreign_key_violation(conn: &Connection) -> rusqlite::Result<Option<String>> {
+    let mut stmt = conn.prepare("PRAGMA foreign_key_check")?;
+    let mut rows = stmt.query([])?;
+    match rows.next()? {
+        Some(row) => Ok(Some(row.get(0)?)),
+        None => Ok(None),
+    }
+}
+
+// Version 1 is the schema that shipped before migrations existed. It keeps
+// `IF NOT EXISTS` so databases created by those builds (user_version 0 but
+// with every table present) are adopted as-is.
+fn initial_schema(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE IF NOT EXISTS tasks (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            title TEXT NOT NULL,
+            description TEXT,
+            is_completed BOOLEAN NOT NULL DEFAULT 0,
+            parent_id INTEGER,
+            due_date TEXT,
+            is_recurring BOOLEAN NOT NULL DEFAULT 0,
+            recurrence_pattern TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (parent_id) REFERENCES tasks(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS day_tracker (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            day_number INTEGER NOT NULL UNIQUE,
+            date TEXT NOT NULL,
+            completion_status TEXT NOT NULL DEFAULT 'red',
+            tasks_completed INTEGER NOT NULL DEFAULT 0,
+            tasks_total INTEGER NOT NULL DEFAULT 0
+        );
+
+        CREATE TABLE IF NOT EXISTS task_assignments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            task_id INTEGER NOT NULL,
+            day_number INTEGER NOT NULL,
+            assigned_by TEXT NOT NULL DEFAULT 'manual',
+            FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+            FOREIGN KEY (day_number) REFERENCES day_tracker(day_number) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS pomodoro_sessions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            task_id INTEGER,
+            start_time TEXT NOT NULL,
+            end_time TEXT,
+            duration_minutes INTEGER NOT NULL,
+            completed BOOLEAN NOT NULL DEFAULT 0,
+            date TEXT NOT NULL,
+            FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE SET NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS user_stats (
+            id INTEGER PRIMARY KEY DEFAULT 1,
+            current_streak INTEGER NOT NULL DEFAULT 0,
+            longest_streak INTEGER NOT NULL DEFAULT 0,
+            total_tasks_completed INTEGER NOT NULL DEFAULT 0,
+            total_study_minutes INTEGER NOT NULL DEFAULT 0,
+            last_study_date TEXT
+        );
+
+        INSERT OR IGNORE INTO user_stats (id, current_streak, longest_streak, total_tasks_completed, total_study_minutes)
+        VALUES (1, 0, 0, 0, 0);",
+    )
+}
+
+// Foreign keys were never enabled before version 2, so deleting a task left
+// its subtasks and assignments behind. Drop the orphans and recount every day.
+fn repair_orphans(tx: &Transaction) -> rusqlite::Result<()> {
+    // Each pass removes one level of a detached subtree
+    loop {
+        let removed = tx.execute(
+            "DELETE FROM tasks
+             WHERE parent_id IS NOT NULL AND parent_id NOT IN (SELECT id FROM tasks)",
+            [],
+        )?;
+        if removed == 0 {
+            break;
+        }
+    }
+
+    tx.execute_batch(
+        "DELETE FROM task_assignments
+         WHERE task_id NOT IN (SELECT id FROM tasks)
+            OR day_number NOT IN (SELECT day_number FROM day_tracker);
+
+        UPDATE pomodoro_sessions SET task_id = NULL
+        WHERE task_id IS NOT NULL AND task_id NOT IN (SELECT id FROM tasks);
+
+        UPDATE day_tracker SET
+            tasks_total = (
+                SELECT COUNT(*) FROM task_assignments ta
+                WHERE ta.day_number = day_tracker.day_number
+            ),
+            tasks_completed = (
+                SELECT COUNT(*) FROM task_assignments ta
+                JOIN tasks t ON ta.task_id = t.id
+                WHERE ta.day_number = day_tracker.day_number AND t.is_completed = 1
+            );
+
+        UPDATE day_tracker SET completion_status = CASE
+            WHEN tasks_total = 0 OR tasks_completed = 0 THEN 'red'
+            WHEN tasks_completed * 2 < tasks_total THEN 'yellow'
+            WHEN tasks_completed < tasks_total THEN 'light_green'
+            ELSE 'deep_green'
+        END;",
+    )
+}
+
+// The timer moved into the backend. Sessions now record their state and
+// break length, and pauses are stored so a session survives a restart.
+// Unfinished rows from older builds can't be resumed, so they are aborted.
+fn pomodoro_timer_state(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE pomodoro_sessions ADD COLUMN status TEXT NOT NULL DEFAULT 'running';
+        ALTER TABLE pomodoro_sessions ADD COLUMN break_minutes INTEGER NOT NULL DEFAULT 5;
+
+        UPDATE pomodoro_sessions
+        SET status = CASE WHEN completed = 1 THEN 'completed' ELSE 'aborted' END;
+
+        CREATE TABLE pomodoro_pauses (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            session_id INTEGER NOT NULL,
+            paused_at TEXT NOT NULL,
+            resumed_at TEXT,
+            FOREIGN KEY (session_id) REFERENCES pomodoro_sessions(id) ON DELETE CASCADE
+        );",
+    )
+}
+
+// Session dates used to be `date('now')`, i.e. the UTC day, which splits or
+// merges study days for anyone far from UTC. Recompute them from the (UTC)
+// start time in the machine's local time zone.
+fn local_session_dates(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute(
+        "UPDATE pomodoro_sessions SET date = date(start_time, 'localtime')",
+        [],
+    )?;
+    Ok(())
+}
+
+// `day_number` was an offset from the first launch, frozen forever. Days are
+// now identified by their date, the window position is computed on read, and
+// assignments point at the date they were made for. Also adds the key/value
+// `app_settings` table the tracker anchor is stored in.
+fn date_keyed_tracker(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE day_tracker_new (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            date TEXT NOT NULL UNIQUE,
+            completion_status TEXT NOT NULL DEFAULT 'red',
+            tasks_completed INTEGER NOT NULL DEFAULT 0,
+            tasks_total INTEGER NOT NULL DEFAULT 0
+        );
+
+        INSERT OR IGNORE INTO day_tracker_new (id, date, completion_status, tasks_completed, tasks_total)
+        SELECT id, date, completion_status, tasks_completed, tasks_total
+        FROM day_tracker
+        ORDER BY day_number;
+
+        CREATE TABLE task_assignments_new (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            task_id INTEGER NOT NULL,
+            date TEXT NOT NULL,
+            assigned_by TEXT NOT NULL DEFAULT 'manual',
+            UNIQUE (task_id, date),
+            FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+            FOREIGN KEY (date) REFERENCES day_tracker(date) ON DELETE CASCADE
+        );
+
+        INSERT OR IGNORE INTO task_assignments_new (id, task_id, date, assigned_by)
+        SELECT ta.id, ta.task_id, dt.date, ta.assigned_by
+        FROM task_assignments ta
+        JOIN day_tracker dt ON dt.day_number = ta.day_number
+        ORDER BY ta.id;
+
+        DROP TABLE task_assignments;
+        DROP TABLE day_tracker;
+        ALTER TABLE day_tracker_new RENAME TO day_tracker;
+        ALTER TABLE task_assignments_new RENAME TO task_assignments;
+
+        CREATE TABLE app_settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );",
+    )
+}
+
+// `tasks.is_completed` said nothing about when a task was done, so a task
+// counted as done on every day it was assigned. Completions are now recorded
+// per date. Tasks already checked off get one on each day they were assigned
+// to, or on the day they were created if they never were, so existing day
+// colours don't change. `rolled_from` keeps the due date a recurring task had
+// before the completion moved it on, so it can be undone.
+fn task_completions(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE task_completions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            task_id INTEGER NOT NULL,
+            date TEXT NOT NULL,
+            completed_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            source TEXT NOT NULL DEFAULT 'task_list',
+            rolled_from TEXT,
+            UNIQUE (task_id, date),
+            FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+        );
+
+        CREATE INDEX idx_task_completions_date ON task_completions(date);
+
+        INSERT INTO task_completions (task_id, date, source)
+        SELECT ta.task_id, ta.date, 'legacy'
+        FROM task_assignments ta
+        JOIN tasks t ON t.id = ta.task_id
+        WHERE t.is_completed = 1;
+
+        INSERT INTO task_completions (task_id, date, source)
+        SELECT t.id, date(t.created_at, 'localtime'), 'legacy'
+        FROM tasks t
+        WHERE t.is_completed = 1
+          AND NOT EXISTS (SELECT 1 FROM task_assignments ta WHERE ta.task_id = t.id);",
+    )
+}
+
+// Assignments can now be moved between days; remember the last move.
+fn assignment_moves(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE task_assignments ADD COLUMN moved_from TEXT;
+         ALTER TABLE task_assignments ADD COLUMN moved_by TEXT;
+         ALTER TABLE task_assignments ADD COLUMN moved_at TEXT;",
+    )
+}
+
+// Priority, estimated effort and difficulty, used for sorting and by the planner.
+fn task_effort(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE tasks ADD COLUMN priority INTEGER NOT NULL DEFAULT 0
+             CHECK (priority BETWEEN 0 AND 3);
+         ALTER TABLE tasks ADD COLUMN estimated_minutes INTEGER
+             CHECK (estimated_minutes IS NULL OR estimated_minutes > 0);
+         ALTER TABLE tasks ADD COLUMN difficulty INTEGER
+             CHECK (difficulty IS NULL OR difficulty BETWEEN 1 AND 5);",
+    )
+}
+
+// Tags are a flat, many-to-many way to group tasks across the tree.
+fn tags(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE tags (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            color TEXT NOT NULL DEFAULT '#6b7280',
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        );
+
+        CREATE TABLE task_tags (
+            task_id INTEGER NOT NULL,
+            tag_id INTEGER NOT NULL,
+            PRIMARY KEY (task_id, tag_id),
+            FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+            FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
+        );
+
+        CREATE INDEX idx_task_tags_tag ON task_tags(tag_id);",
+    )
+}
+
+// Projects group top-level tasks (and their subtrees) into separate lists.
+// Existing tasks start out without a project.
+fn projects(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE projects (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            color TEXT NOT NULL DEFAULT '#6b7280',
+            position INTEGER NOT NULL DEFAULT 0,
+            is_archived INTEGER NOT NULL DEFAULT 0,
+            default_priority INTEGER NOT NULL DEFAULT 0 CHECK (default_priority BETWEEN 0 AND 3),
+            default_estimated_minutes INTEGER CHECK (default_estimated_minutes IS NULL OR default_estimated_minutes > 0),
+            sort_by TEXT,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        );
+
+        ALTER TABLE tasks ADD COLUMN project_id INTEGER REFERENCES projects(id) ON DELETE SET NULL;
+
+        CREATE INDEX idx_tasks_project ON tasks(project_id);",
+    )
+}
+
+// Manual order of tasks among their siblings, as keys from `ordering.rs`.
+// Existing lists keep the order they were shown in: top-level tasks newest
+// first, subtasks oldest first. Fixed-width numbers ending in 'V' are valid
+// keys and sort like the numbers.
+fn task_positions(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE tasks ADD COLUMN position TEXT NOT NULL DEFAULT '';
+
+        UPDATE tasks SET position = printf('%08dV', ranked.rank)
+        FROM (
+            SELECT id, ROW_NUMBER() OVER (
+                PARTITION BY parent_id
+                ORDER BY
+                    CASE WHEN parent_id IS NULL THEN created_at END DESC,
+                    CASE WHEN parent_id IS NULL THEN id END DESC,
+                    created_at ASC,
+                    id ASC
+            ) AS rank
+            FROM tasks
+        ) ranked
+        WHERE ranked.id = tasks.id;
+
+        CREATE INDEX idx_tasks_parent_position ON tasks(parent_id, position);",
+    )
+}
+
+// Tracker days look up their assignments by date, which the
+// UNIQUE (task_id, date) index can't serve. Lookups by task already use that
+// index, and subtasks by parent use idx_tasks_parent_position.
+fn tracker_lookup_indexes(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch("CREATE INDEX idx_task_assignments_date ON task_assignments(date);")
+}
+
+// Full-text index over task titles and descriptions. It reads the text from
+// `tasks` itself (an external content table), and the triggers keep it in step
+// with every insert, edit and delete, including cascaded subtask deletes.
+// `remove_diacritics` lets "cafe" find "café".
+fn task_search(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE VIRTUAL TABLE tasks_fts USING fts5(
+            title,
+            description,
+            content = 'tasks',
+            content_rowid = 'id',
+            tokenize = 'unicode61 remove_diacritics 2'
+        );
+
+        CREATE TRIGGER tasks_fts_insert AFTER INSERT ON tasks BEGIN
+            INSERT INTO tasks_fts (rowid, title, description)
+            VALUES (new.id, new.title, new.description);
+        END;
+
+        CREATE TRIGGER tasks_fts_delete AFTER DELETE ON tasks BEGIN
+            INSERT INTO tasks_fts (tasks_fts, rowid, title, description)
+            VALUES ('delete', old.id, old.title, old.description);
+        END;
+
+        CREATE TRIGGER tasks_fts_update AFTER UPDATE OF title, description ON tasks BEGIN
+            INSERT INTO tasks_fts (tasks_fts, rowid, title, description)
+            VALUES ('delete', old.id, old.title, old.description);
+            INSERT INTO tasks_fts (rowid, title, description)
+            VALUES (new.id, new.title, new.description);
+        END;
+
+        INSERT INTO tasks_fts (tasks_fts) VALUES ('rebuild');",
+    )
+}
+
+// Named filters in the language of `filters.rs`, kept as typed and parsed
+// each time they run.
+fn saved_views(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE saved_views (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            filter TEXT NOT NULL,
+            sort_by TEXT,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        );",
+    )
+}
+
+// Deleted tasks go to the trash: `deleted_at` is set on the task and the
+// subtasks deleted with it, all to the same time, which is how a restore
+// finds them again. Their tracker assignments wait in `trashed_assignments`,
+// so trashed tasks don't count towards any day.
+fn task_trash(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE tasks ADD COLUMN deleted_at TEXT;
+
+        CREATE INDEX idx_tasks_deleted_at ON tasks(deleted_at) WHERE deleted_at IS NOT NULL;
+
+        CREATE TABLE trashed_assignments (
+            task_id INTEGER NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+            date TEXT NOT NULL,
+            assigned_by TEXT NOT NULL,
+            moved_from TEXT,
+            moved_by TEXT,
+            moved_at TEXT,
+            UNIQUE (task_id, date)
+        );",
+    )
+}
+
+// Undo history, read and written by `journal.rs`. While a command runs,
+// `journal_state.entry_id` names its entry and the triggers below copy every
+// row it inserts, updates or deletes in the journaled tables, before and
+// after, into `journal_changes`. `day_tracker` and `user_stats` are derived
+// and get recomputed instead; pomodoro sessions are a record of time spent
+// and aren't undoable.
+//
+// The triggers list each table's columns, so a later migration that adds a
+// column to one of these tables must call `create_journal_triggers` again.
+fn undo_journal(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE journal_entries (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            label TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            undone INTEGER NOT NULL DEFAULT 0
+        );
+
+        CREATE TABLE journal_changes (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            entry_id INTEGER NOT NULL REFERENCES journal_entries(id) ON DELETE CASCADE,
+            table_name TEXT NOT NULL,
+            row_id INTEGER NOT NULL,
+            old_row TEXT,
+            new_row TEXT
+        );
+
+        CREATE INDEX idx_journal_changes_entry ON journal_changes(entry_id);
+
+        CREATE TABLE journal_state (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            entry_id INTEGER
+        );
+
+        INSERT INTO journal_state (id) VALUES (1);",
+    )?;
+
+    for table in [
+        "tasks",
+        "task_assignments",
+        "trashed_assignments",
+        "task_completions",
+        "tags",
+        "task_tags",
+        "projects",
+        "saved_views",
+        "app_settings",
+    ] {
+        create_journal_triggers(tx, table)?;
+    }
+    Ok(())
+}
+
+// (Re)creates the insert, update and delete triggers that journal `table`,
+// with a JSON object of every column it has now as the row image
+fn create_journal_triggers(tx: &Transaction, table: &str) -> rusqlite::Result<()> {
+    let columns = {
+        let mut stmt = tx.prepare("SELECT name FROM pragma_table_info(?1) ORDER BY cid")?;
+        let names = stmt.query_map([table], |row| row.get::<_, String>(0))?;
+        names.collect::<rusqlite::Result<Vec<_>>>()?
+    };
+    let image = |row: &str| {
+        let pairs: Vec<String> = columns
+            .iter()
+            .map(|column| format!("'{}', {}.\"{}\"", column, row, column))
+            .collect();
+        format!("json_object({})", pairs.join(", "))
+    };
+
+    for (event, row_ref, old_row, new_row) in [
+        ("insert", "new", "NULL".to_string(), image("new")),
+        ("update", "new", image("old"), image("new")),
+        ("delete", "old", image("old"), "NULL".to_string()),
+    ] {
+        tx.execute_batch(&format!(
+            "DROP TRIGGER IF EXISTS journal_{table}_{event};
+            CREATE TRIGGER journal_{table}_{event} AFTER {verb} ON {table}
+            WHEN (SELECT entry_id FROM journal_state) IS NOT NULL BEGIN
+                INSERT INTO journal_changes (entry_id, table_name, row_id, old_row, new_row)
+                VALUES ((SELECT entry_id FROM journal_state), '{table}', {row_ref}.rowid, {old_row}, {new_row});
+            END;",
+            verb = event.to_uppercase(),
+        ))?;
+    }
+    Ok(())
+}
+
+// What happened to each task, and to projects, tags, views and settings, as
+// written by `activity.rs`. Unlike the undo journal it is never rewritten or
+// pruned: undoing a change adds events instead of removing any. `task_id` has
+// no foreign key so a task's history outlives it, and `subject` keeps the
+// title it had at the time.
+fn activity_log(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE activity_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            occurred_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%d %H:%M:%f', 'now')),
+            command TEXT NOT NULL,
+            event_type TEXT NOT NULL,
+            task_id INTEGER,
+            subject TEXT NOT NULL,
+            old_value TEXT,
+            new_value TEXT
+        );
+
+        CREATE INDEX idx_activity_log_task ON activity_log(task_id, id) WHERE task_id IS NOT NULL;
+
+        CREATE TRIGGER activity_log_no_update BEFORE UPDATE ON activity_log BEGIN
+            SELECT RAISE(ABORT, 'activity_log is append-only');
+        END;
+
+        CREATE TRIGGER activity_log_no_delete BEFORE DELETE ON activity_log BEGIN
+            SELECT RAISE(ABORT, 'activity_log is append-only');
+        END;",
+    )
+}
+
+// A recurring task's due date moves on with each completion and is clamped
+// to short months, so it can't also say which day the series falls on: a
+// monthly task from the 31st would stay on the 28th after February.
+// `recurrence_anchor` keeps the due date the series started from. Existing
+// series start from their current due date.
+fn recurrence_anchors(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE tasks ADD COLUMN recurrence_anchor TEXT;
+
+        UPDATE tasks SET recurrence_anchor = due_date WHERE is_recurring = 1;",
+    )?;
+    create_journal_triggers(tx, "tasks")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A database as the build that wrote `version` left it: every step up to
+    // it, with the rows a user of that build would have. Version 0 is a
+    // database from before migrations, which already had the initial tables.
+    fn fixture(version: i32) -> Connection {
+        let mut conn = Connection::open_in_memory().unwrap();
+        // As in `migrate`, and as old builds never turned them on
+        conn.pragma_update(None, "foreign_keys", false).unwrap();
+        let tx = conn.transaction().unwrap();
+        initial_schema(&tx).unwrap();
+        tx.execute_batch(
+            "INSERT INTO tasks (id, title, is_completed, parent_id, due_date, is_recurring, recurrence_pattern)
+             VALUES (1, 'Read chapter 3', 1, NULL, NULL, 0, NULL),
+                    (2, 'Write notes', 0, 1, NULL, 0, NULL),
+                    (3, 'Weekly review', 0, NULL, '2025-03-03', 1, 'weekly');
+
+             INSERT INTO day_tracker (day_number, date) VALUES (1, '2025-03-01'), (2, '2025-03-02');
+             INSERT INTO task_assignments (task_id, day_number) VALUES (1, 2), (2, 1);
+
+             INSERT INTO pomodoro_sessions (task_id, start_time, end_time, duration_minutes, completed, date)
+             VALUES (1, '2025-03-02 12:00:00', '2025-03-02 12:25:00', 25, 1, '2025-03-02');
+
+             UPDATE user_stats SET total_study_minutes = 25 WHERE id = 1;",
+        )
+        .unwrap();
+        tx.commit().unwrap();
+
+        for migration in MIGRATIONS.iter().filter(|m| m.version <= version) {
+            let tx = conn.transaction().unwrap();
+            if migration.version > 1 {
+                (migration.up)(&tx).unwrap();
+            }
+            tx.pragma_update(None, "user_version", migration.version).unwrap();
+            tx.commit().unwrap();
+        }
+
+        // Rows in tables added along the way
+        if version >= 9 {
+            conn.execute_batch(
+                "INSERT INTO tags (id, name) VALUES (1, 'exam');
+                 INSERT INTO task_tags (task_id, tag_id) VALUES (1, 1);",
+            )
+            .unwrap();
+        }
+        if version >= 10 {
+            conn.execute_batch(
+                "INSERT INTO projects (id, name) VALUES (1, 'Study');
+                 UPDATE tasks SET project_id = 1 WHERE id IN (1, 2);",
+            )
+            .unwrap();
+        }
+        conn
+    }
+
+    fn strings(conn: &Connection, sql: &str) -> Vec<String> {
+        let mut stmt = conn.prepare(sql).unwrap();
+        let rows = stmt.query_map([], |row| row.get(0)).unwrap();
+        rows.collect::<rusqlite::Result<_>>().unwrap()
+    }
+
+    #[test]
+    fn upgrades_every_previous_version() {
+        for version in 0..SCHEMA_VERSION {
+            let mut conn = fixture(version);
+            migrate(&mut conn).unwrap_or_else(|e| panic!("from version {}: {}", version, e));
+
+            assert_eq!(schema_version(&conn).unwrap(), SCHEMA_VERSION, "from version {}", version);
+            assert_eq!(first_foreign_key_violation(&conn).unwrap(), None, "from version {}", version);
+
+            assert_eq!(
+                strings(&conn, "SELECT title || ':' || IFNULL(parent_id, '-') FROM tasks ORDER BY id"),
+                ["Read chapter 3:-", "Write notes:1", "Weekly review:-"],
+                "tasks from version {}",
+                version
+            );
+            assert_eq!(
+                strings(&conn, "SELECT task_id || '@' || date FROM task_assignments ORDER BY task_id"),
+                ["1@2025-03-02", "2@2025-03-01"],
+                "assignments from version {}",
+                version
+            );
+            assert_eq!(
+                strings(&conn, "SELECT task_id || '@' || date FROM task_completions"),
+                ["1@2025-03-02"],
+                "completions from version {}",
+                version
+            );
+            assert_eq!(
+                strings(&conn, "SELECT status FROM pomodoro_sessions"),
+                ["completed"],
+                "sessions from version {}",
+                version
+            );
+            let minutes: i64 = conn
+                .query_row("SELECT total_study_minutes FROM user_stats WHERE id = 1", [], |row| row.get(0))
+                .unwrap();
+            assert_eq!(minutes, 25, "stats from version {}", version);
+            assert_eq!(
+                strings(&conn, "SELECT CAST(rowid AS TEXT) FROM tasks_fts WHERE tasks_fts MATCH 'notes'"),
+                ["2"],
+                "search index from version {}",
+                version
+            );
+
+            if version >= 9 {
+                assert_eq!(strings(&conn, "SELECT CAST(task_id AS TEXT) FROM task_tags"), ["1"]);
+            }
+            if version >= 10 {
+                assert_eq!(
+                    strings(&conn, "SELECT CAST(id AS TEXT) FROM tasks WHERE project_id = 1 ORDER BY id"),
+                    ["1", "2"]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn creates_a_new_database_and_does_nothing_the_second_time() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn).unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), SCHEMA_VERSION);
+
+        migrate(&mut conn).unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn refuses_a_newer_database() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION + 1).unwrap();
+
+        match migrate(&mut conn) {
+            Err(MigrationError::DatabaseTooNew { found, supported }) => {
+                assert_eq!(found, SCHEMA_VERSION + 1);
+                assert_eq!(supported, SCHEMA_VERSION);
+            }
+            other => panic!("expected DatabaseTooNew, got {:?}", other),
+        }
+        assert!(strings(&conn, "SELECT name FROM sqlite_master").is_empty());
+    }
+}
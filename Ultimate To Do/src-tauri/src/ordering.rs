@@ -0,0 +1,99 @@
+//! Sort keys for manually ordered tasks.
+//!
+//! Keys are strings of base-62 digits compared byte by byte, read as the
+//! digits of a fraction between 0 and 1. There is always room for another key
+//! between two different ones, so moving a task only rewrites its own key.
+//! Keys never end in `0`, which keeps room before every key as well.
+
+const DIGITS: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+const BASE: usize = DIGITS.len();
+
+/// Repeatedly adding at the same end of a list grows keys by about one digit
+/// every six tasks. Past this length the list is renumbered instead.
+pub const MAX_KEY_LENGTH: usize = 24;
+
+/// A key that sorts after `before` and before `after`; `None` stands for the
+/// start or end of the list. Returns `None` if the keys are out of order, not
+/// valid keys, or too close to fit a key of at most `MAX_KEY_LENGTH`, in which
+/// case the list needs `spread` out again.
+pub fn key_between(before: Option<&str>, after: Option<&str>) -> Option<String> {
+    let before = before.unwrap_or("");
+    if !is_valid(before, true) || !after.is_none_or(|after| is_valid(after, false)) {
+        return None;
+    }
+    if after.is_some_and(|after| before >= after) {
+        return None;
+    }
+
+    let key = midpoint(before.as_bytes(), after.map(str::as_bytes));
+    if key.len() > MAX_KEY_LENGTH {
+        return None;
+    }
+    Some(String::from_utf8(key).expect("keys are ASCII"))
+}
+
+/// `count` evenly spaced keys in ascending order, for (re)numbering a whole list.
+pub fn spread(count: usize) -> Vec<String> {
+    let mut width = 1;
+    while BASE.pow(width) <= count {
+        width += 1;
+    }
+    let step = BASE.pow(width) / (count + 1);
+
+    (1..=count)
+        .map(|i| {
+            let mut value = i * step;
+            let mut digits = vec![b'0'; width as usize];
+            for digit in digits.iter_mut().rev() {
+                *digit = DIGITS[value % BASE];
+                value /= BASE;
+            }
+            while digits.last() == Some(&b'0') {
+                digits.pop();
+            }
+            String::from_utf8(digits).expect("keys are ASCII")
+        })
+        .collect()
+}
+
+fn is_valid(key: &str, allow_empty: bool) -> bool {
+    (allow_empty || !key.is_empty())
+        && !key.ends_with('0')
+        && key.bytes().all(|b| b.is_ascii_alphanumeric())
+}
+
+fn digit_value(digit: u8) -> usize {
+    DIGITS.iter().position(|d| *d == digit).expect("validated digit")
+}
+
+// `a < b`, where a missing `b` is 1 and digits missing from `a` are 0
+fn midpoint(a: &[u8], b: Option<&[u8]>) -> Vec<u8> {
+    if let Some(b) = b {
+        let common = b
+            .iter()
+            .enumerate()
+            .take_while(|(i, digit)| a.get(*i).copied().unwrap_or(b'0') == **digit)
+            .count();
+        if common > 0 {
+            let mut key = b[..common].to_vec();
+            key.extend(midpoint(a.get(common..).unwrap_or(&[]), Some(&b[common..])));
+            return key;
+        }
+    }
+
+    let low = a.first().copied().map(digit_value).unwrap_or(0);
+    let high = b.map(|b| digit_value(b[0])).unwrap_or(BASE);
+    if high - low > 1 {
+        return vec![DIGITS[(low + high) / 2]];
+    }
+
+    match b {
+        // The first digit of a longer `b` already fits
+        Some(b) if b.len() > 1 => vec![b[0]],
+        _ => {
+            let mut key = vec![DIGITS[low]];
+            key.extend(midpoint(a.get(1..).unwrap_or(&[]), None));
+            key
+        }
+    }
+}
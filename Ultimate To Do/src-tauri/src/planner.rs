@@ -0,0 +1,174 @@
+use crate::recurrence::RecurrenceRule;
+use chrono::{Duration, NaiveDate};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// Effort points a day can take; a point is about an hour of work.
+pub const DEFAULT_DAILY_CAPACITY: u32 = 6;
+
+/// An open task the planner may place on the tracker.
+#[derive(Debug, Clone)]
+pub struct PlannerTask {
+    pub id: i64,
+    pub due_date: Option<NaiveDate>,
+    /// The date a recurring series counts from; `due_date` when unset
+    pub anchor: Option<NaiveDate>,
+    /// 0 (none) to 3 (high); breaks ties between tasks due the same day
+    pub priority: i32,
+    /// How much of a day's capacity the task takes up, see `effort_weight`
+    pub weight: u32,
+    /// Recurring tasks are placed on each of their occurrences from `due_date` on
+    pub recurrence: Option<RecurrenceRule>,
+}
+
+/// An assignment that stays where it is: a manual one, or one for a task that
+/// can't be moved. It counts towards its day's capacity.
+#[derive(Debug, Clone)]
+pub struct PinnedAssignment {
+    pub task_id: i64,
+    pub date: NaiveDate,
+    pub weight: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanReason {
+    /// An occurrence of a recurring task
+    Recurring,
+    /// Due before the window starts, so it goes on the first day
+    Overdue,
+    /// The least busy day on or before the due date
+    DueDate,
+    /// No due date within the window; the least busy day overall
+    Balanced,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedAssignment {
+    pub task_id: i64,
+    pub date: String,
+    pub reason: PlanReason,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Plan {
+    pub start_date: String,
+    pub end_date: String,
+    pub capacity: u32,
+    pub assignments: Vec<PlannedAssignment>,
+    /// Tasks that did not fit before their due date (or anywhere in the window)
+    pub unscheduled: Vec<i64>,
+}
+
+/// Effort points for a task: its difficulty or its estimate in started hours,
+/// whichever is larger, and 1 for a task with neither.
+pub fn effort_weight(estimated_minutes: Option<i32>, difficulty: Option<i32>) -> u32 {
+    let hours = estimated_minutes.map(|m| (m.max(1) as u32).div_ceil(60)).unwrap_or(1);
+    let difficulty = difficulty.map(|d| d.max(1) as u32).unwrap_or(1);
+    hours.max(difficulty)
+}
+
+/// Distributes `tasks` over the `days` days starting at `start`.
+///
+/// Recurring tasks go on every occurrence in the window whatever the load,
+/// since moving them would change what they mean. Everything else is placed in
+/// order of due date (undated last), then priority, heaviest first, on the
+/// least loaded day that still has room and is not after the task's due date;
+/// ties go to the earlier day. A task heavier than a whole day needs an empty
+/// one. Overdue tasks go on the first day if it has room. A task with a
+/// pinned assignment in the window is left alone.
+pub fn plan(
+    tasks: &[PlannerTask],
+    pinned: &[PinnedAssignment],
+    start: NaiveDate,
+    days: i64,
+    capacity: u32,
+) -> Plan {
+    let end = start + Duration::days(days.max(1) - 1);
+    let dates: Vec<NaiveDate> = (0..days.max(1)).map(|offset| start + Duration::days(offset)).collect();
+
+    let mut load: HashMap<NaiveDate, u32> = HashMap::new();
+    let mut taken: HashSet<(i64, NaiveDate)> = HashSet::new();
+    let mut pinned_tasks: HashSet<i64> = HashSet::new();
+    for pin in pinned.iter().filter(|p| p.date >= start && p.date <= end) {
+        *load.entry(pin.date).or_default() += pin.weight.max(1);
+        taken.insert((pin.task_id, pin.date));
+        pinned_tasks.insert(pin.task_id);
+    }
+
+    let mut assignments = Vec::new();
+    let mut unscheduled = Vec::new();
+
+    for task in tasks {
+        let (Some(rule), Some(due_date)) = (&task.recurrence, task.due_date) else {
+            continue;
+        };
+        let anchor = task.anchor.unwrap_or(due_date);
+        for date in dates.iter().copied().filter(|d| *d >= due_date && rule.matches(anchor, *d)) {
+            if taken.insert((task.id, date)) {
+                *load.entry(date).or_default() += task.weight.max(1);
+                assignments.push(PlannedAssignment {
+                    task_id: task.id,
+                    date: format_date(date),
+                    reason: PlanReason::Recurring,
+                });
+            }
+        }
+    }
+
+    let mut one_off: Vec<&PlannerTask> = tasks
+        .iter()
+        .filter(|t| t.recurrence.is_none() && !pinned_tasks.contains(&t.id))
+        .collect();
+    one_off.sort_by_key(|t| {
+        (
+            t.due_date.is_none(),
+            t.due_date,
+            std::cmp::Reverse(t.priority),
+            std::cmp::Reverse(t.weight),
+            t.id,
+        )
+    });
+
+    for task in one_off {
+        let weight = task.weight.clamp(1, capacity.max(1));
+        let (last, reason) = match task.due_date {
+            Some(due) if due < start => (start, PlanReason::Overdue),
+            Some(due) if due <= end => (due, PlanReason::DueDate),
+            _ => (end, PlanReason::Balanced),
+        };
+
+        let best = dates
+            .iter()
+            .copied()
+            .take_while(|d| *d <= last)
+            .filter(|d| load.get(d).copied().unwrap_or(0) + weight <= capacity)
+            .min_by_key(|d| (load.get(d).copied().unwrap_or(0), *d));
+
+        match best {
+            Some(date) => {
+                *load.entry(date).or_default() += weight;
+                assignments.push(PlannedAssignment {
+                    task_id: task.id,
+                    date: format_date(date),
+                    reason,
+                });
+            }
+            None => unscheduled.push(task.id),
+        }
+    }
+
+    assignments.sort_by(|a, b| a.date.cmp(&b.date).then(a.task_id.cmp(&b.task_id)));
+
+    Plan {
+        start_date: format_date(start),
+        end_date: format_date(end),
+        capacity,
+        assignments,
+        unscheduled,
+    }
+}
+
+fn format_date(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
@@ -0,0 +1,478 @@
+use crate::database::DbState;
+use crate::error::{AppError, AppResult};
+use chrono::{Local, NaiveDateTime, TimeZone, Utc};
+use rusqlite::{Connection, OptionalExtension};
+use serde::Serialize;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+pub const TICK_EVENT: &str = "pomodoro-tick";
+pub const PHASE_CHANGED_EVENT: &str = "pomodoro-phase-changed";
+pub const SESSION_COMPLETED_EVENT: &str = "pomodoro-session-completed";
+/// Sent with an `AppError` when a tick fails; the timer tries again next second.
+pub const ERROR_EVENT: &str = "pomodoro-error";
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// idle → focus ⇄ paused → break → idle. Aborting from any phase returns to idle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimerPhase {
+    Idle,
+    Focus,
+    Paused,
+    Break,
+}
+
+/// What the frontend renders; sent with every tick and returned by the timer commands.
+#[derive(Debug, Clone, Serialize)]
+pub struct TimerSnapshot {
+    pub phase: TimerPhase,
+    pub session_id: Option<i64>,
+    pub task_id: Option<i64>,
+    pub duration_seconds: i64,
+    pub remaining_seconds: i64,
+}
+
+#[derive(Debug, Clone)]
+struct ActiveSession {
+    id: i64,
+    task_id: Option<i64>,
+    duration_seconds: i64,
+    break_minutes: i32,
+    started_at: NaiveDateTime,
+    paused_seconds: i64,
+    paused_at: Option<NaiveDateTime>,
+}
+
+impl ActiveSession {
+    fn remaining_seconds(&self, now: NaiveDateTime) -> i64 {
+        let current_pause = self
+            .paused_at
+            .map(|paused_at| (now - paused_at).num_seconds())
+            .unwrap_or(0);
+        let focused = (now - self.started_at).num_seconds() - self.paused_seconds - current_pause;
+        (self.duration_seconds - focused).max(0)
+    }
+
+    fn planned_end(&self) -> NaiveDateTime {
+        self.started_at + chrono::Duration::seconds(self.duration_seconds + self.paused_seconds)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ActiveBreak {
+    started_at: NaiveDateTime,
+    duration_seconds: i64,
+}
+
+#[derive(Debug, Clone)]
+enum TimerState {
+    Idle,
+    Focus(ActiveSession),
+    Paused(ActiveSession),
+    Break(ActiveBreak),
+}
+
+/// Result of advancing the timer to the current time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TickOutcome {
+    Unchanged,
+    PhaseChanged,
+    SessionCompleted(i64),
+}
+
+/// A change to the timer whose rows have been written but which the timer
+/// doesn't show yet. Pass it to `PomodoroTimer::apply` once the transaction
+/// holding those rows has committed, so a failed commit leaves the timer as
+/// it was.
+#[must_use = "the timer only changes once the transition is applied"]
+pub struct Transition<T> {
+    next: Option<TimerState>,
+    outcome: T,
+}
+
+impl<T> Transition<T> {
+    fn to(next: TimerState, outcome: T) -> Self {
+        Transition {
+            next: Some(next),
+            outcome,
+        }
+    }
+
+    fn unchanged(outcome: T) -> Self {
+        Transition { next: None, outcome }
+    }
+
+    /// What the change will be, e.g. the session it completes.
+    pub fn outcome(&self) -> &T {
+        &self.outcome
+    }
+}
+
+/// The Pomodoro timer. Session rows and pause intervals are written to the
+/// database as the state changes, so an in-flight session can be rebuilt with
+/// `recover` after a restart. Breaks only live in memory.
+///
+/// Lock order: always take `DbState::conn` before the timer.
+pub struct PomodoroTimer {
+    state: Mutex<TimerState>,
+}
+
+impl Default for PomodoroTimer {
+    fn default() -> Self {
+        PomodoroTimer {
+            state: Mutex::new(TimerState::Idle),
+        }
+    }
+}
+
+pub fn now() -> NaiveDateTime {
+    Utc::now().naive_utc()
+}
+
+fn format_timestamp(time: NaiveDateTime) -> String {
+    time.format(TIMESTAMP_FORMAT).to_string()
+}
+
+// Sessions are filed under the user's local calendar day, which is what
+// history and streaks are counted in
+fn local_date(now: NaiveDateTime) -> String {
+    Local.from_utc_datetime(&now).format("%Y-%m-%d").to_string()
+}
+
+fn parse_timestamp(value: &str) -> rusqlite::Result<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(value, TIMESTAMP_FORMAT)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e)))
+}
+
+impl PomodoroTimer {
+    pub fn snapshot(&self, now: NaiveDateTime) -> AppResult<TimerSnapshot> {
+        let state = self.state.lock()?;
+        Ok(snapshot_of(&state, now))
+    }
+
+    /// Shows a committed transition and returns its outcome. The caller still
+    /// holds `DbState::conn`, so nothing else moved the timer in between.
+    pub fn apply<T>(&self, transition: Transition<T>) -> AppResult<T> {
+        if let Some(next) = transition.next {
+            *self.state.lock()? = next;
+        }
+        Ok(transition.outcome)
+    }
+
+    /// Starts a focus session; the outcome is the new session's id.
+    pub fn start(
+        &self,
+        conn: &Connection,
+        task_id: Option<i64>,
+        duration_minutes: i32,
+        break_minutes: i32,
+        now: NaiveDateTime,
+    ) -> AppResult<Transition<i64>> {
+        let state = self.state.lock()?;
+
+        if matches!(*state, TimerState::Focus(_) | TimerState::Paused(_)) {
+            return Err(AppError::Conflict(
+                "A Pomodoro session is already in progress".to_string(),
+            ));
+        }
+
+        conn.execute(
+            "INSERT INTO pomodoro_sessions (task_id, start_time, duration_minutes, break_minutes, status, date)
+             VALUES (?1, ?2, ?3, ?4, 'running', ?5)",
+            (task_id, format_timestamp(now), duration_minutes, break_minutes, local_date(now)),
+        )?;
+
+        let id = conn.last_insert_rowid();
+        let session = ActiveSession {
+            id,
+            task_id,
+            duration_seconds: duration_minutes as i64 * 60,
+            break_minutes,
+            started_at: now,
+            paused_seconds: 0,
+            paused_at: None,
+        };
+        Ok(Transition::to(TimerState::Focus(session), id))
+    }
+
+    pub fn pause(&self, conn: &Connection, now: NaiveDateTime) -> AppResult<Transition<()>> {
+        let state = self.state.lock()?;
+
+        let TimerState::Focus(session) = &*state else {
+            return Err(AppError::Conflict("The timer is not running".to_string()));
+        };
+        let mut session = session.clone();
+
+        conn.execute(
+            "INSERT INTO pomodoro_pauses (session_id, paused_at) VALUES (?1, ?2)",
+            (session.id, format_timestamp(now)),
+        )?;
+        conn.execute(
+            "UPDATE pomodoro_sessions SET status = 'paused' WHERE id = ?1",
+            [session.id],
+        )?;
+
+        session.paused_at = Some(now);
+        Ok(Transition::to(TimerState::Paused(session), ()))
+    }
+
+    pub fn resume(&self, conn: &Connection, now: NaiveDateTime) -> AppResult<Transition<()>> {
+        let state = self.state.lock()?;
+
+        let TimerState::Paused(session) = &*state else {
+            return Err(AppError::Conflict("The timer is not paused".to_string()));
+        };
+        let mut session = session.clone();
+
+        close_open_pause(conn, session.id, now)?;
+        conn.execute(
+            "UPDATE pomodoro_sessions SET status = 'running' WHERE id = ?1",
+            [session.id],
+        )?;
+
+        if let Some(paused_at) = session.paused_at.take() {
+            session.paused_seconds += (now - paused_at).num_seconds();
+        }
+        Ok(Transition::to(TimerState::Focus(session), ()))
+    }
+
+    /// Abandons the current focus session (it is kept in history as aborted),
+    /// or skips the current break. The outcome is the aborted session's id,
+    /// or `None` for a break.
+    pub fn abort(&self, conn: &Connection, now: NaiveDateTime) -> AppResult<Transition<Option<i64>>> {
+        let state = self.state.lock()?;
+
+        let aborted = match &*state {
+            TimerState::Focus(session) | TimerState::Paused(session) => {
+                close_open_pause(conn, session.id, now)?;
+                conn.execute(
+                    "UPDATE pomodoro_sessions SET status = 'aborted', end_time = ?1 WHERE id = ?2",
+                    (format_timestamp(now), session.id),
+                )?;
+                Some(session.id)
+            }
+            TimerState::Break(_) => None,
+            TimerState::Idle => {
+                return Err(AppError::Conflict("The timer is not running".to_string()));
+            }
+        };
+
+        Ok(Transition::to(TimerState::Idle, aborted))
+    }
+
+    /// Marks the current session as completed right away and moves on to its break.
+    pub fn finish(&self, conn: &Connection, session_id: i64, now: NaiveDateTime) -> AppResult<Transition<()>> {
+        let state = self.state.lock()?;
+
+        if let TimerState::Focus(session) | TimerState::Paused(session) = &*state {
+            if session.id == session_id {
+                close_open_pause(conn, session.id, now)?;
+                return Ok(Transition::to(break_after(session, now), ()));
+            }
+        }
+
+        Ok(Transition::unchanged(()))
+    }
+
+    /// Advances the timer to `now`, completing the focus session or ending
+    /// the break once its countdown runs out.
+    pub fn tick(&self, conn: &Connection, now: NaiveDateTime) -> AppResult<Transition<TickOutcome>> {
+        let state = self.state.lock()?;
+
+        match &*state {
+            TimerState::Focus(session) if session.remaining_seconds(now) == 0 => {
+                complete_session(conn, session, session.planned_end())?;
+                Ok(Transition::to(break_after(session, now), TickOutcome::SessionCompleted(session.id)))
+            }
+            TimerState::Break(active) if (now - active.started_at).num_seconds() >= active.duration_seconds => {
+                Ok(Transition::to(TimerState::Idle, TickOutcome::PhaseChanged))
+            }
+            _ => Ok(Transition::unchanged(TickOutcome::Unchanged)),
+        }
+    }
+
+    /// Rebuilds the timer from a session left running or paused when the app
+    /// last closed. A session whose countdown ran out in the meantime is
+    /// completed at the time it would have finished; its id is returned so
+    /// stats can be refreshed.
+    pub fn recover(&self, conn: &Connection, now: NaiveDateTime) -> AppResult<Option<i64>> {
+        let mut state = self.state.lock()?;
+
+        let in_flight = conn
+            .query_row(
+                "SELECT id, task_id, start_time, duration_minutes, break_minutes, status
+                 FROM pomodoro_sessions
+                 WHERE status IN ('running', 'paused')
+                 ORDER BY start_time DESC, id DESC
+                 LIMIT 1",
+                [],
+                |row| {
+                    let start_time: String = row.get(2)?;
+                    let duration_minutes: i64 = row.get(3)?;
+                    let status: String = row.get(5)?;
+                    Ok((
+                        ActiveSession {
+                            id: row.get(0)?,
+                            task_id: row.get(1)?,
+                            duration_seconds: duration_minutes * 60,
+                            break_minutes: row.get(4)?,
+                            started_at: parse_timestamp(&start_time)?,
+                            paused_seconds: 0,
+                            paused_at: None,
+                        },
+                        status,
+                    ))
+                },
+            )
+            .optional()?;
+
+        let Some((mut session, status)) = in_flight else {
+            return Ok(None);
+        };
+
+        // Only one session can be in flight; anything older was orphaned by a crash
+        conn.execute(
+            "UPDATE pomodoro_sessions SET status = 'aborted'
+             WHERE status IN ('running', 'paused') AND id != ?1",
+            [session.id],
+        )?;
+
+        let mut stmt = conn.prepare(
+            "SELECT paused_at, resumed_at FROM pomodoro_pauses WHERE session_id = ?1 ORDER BY paused_at",
+        )?;
+        let pauses = stmt
+            .query_map([session.id], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (paused_at, resumed_at) in pauses {
+            let paused_at = parse_timestamp(&paused_at)?;
+            match resumed_at {
+                Some(resumed_at) => {
+                    session.paused_seconds += (parse_timestamp(&resumed_at)? - paused_at).num_seconds();
+                }
+                None => session.paused_at = Some(paused_at),
+            }
+        }
+
+        if status == "paused" && session.paused_at.is_some() {
+            *state = TimerState::Paused(session);
+            return Ok(None);
+        }
+
+        // A running session never has an open pause; close any left by a crash mid-resume
+        session.paused_at = None;
+        close_open_pause(conn, session.id, now)?;
+
+        if session.remaining_seconds(now) > 0 {
+            *state = TimerState::Focus(session);
+            Ok(None)
+        } else {
+            complete_session(conn, &session, session.planned_end())?;
+            *state = TimerState::Idle;
+            Ok(Some(session.id))
+        }
+    }
+}
+
+fn snapshot_of(state: &TimerState, now: NaiveDateTime) -> TimerSnapshot {
+    match state {
+        TimerState::Idle => TimerSnapshot {
+            phase: TimerPhase::Idle,
+            session_id: None,
+            task_id: None,
+            duration_seconds: 0,
+            remaining_seconds: 0,
+        },
+        TimerState::Focus(session) | TimerState::Paused(session) => TimerSnapshot {
+            phase: if session.paused_at.is_some() {
+                TimerPhase::Paused
+            } else {
+                TimerPhase::Focus
+            },
+            session_id: Some(session.id),
+            task_id: session.task_id,
+            duration_seconds: session.duration_seconds,
+            remaining_seconds: session.remaining_seconds(now),
+        },
+        TimerState::Break(active) => TimerSnapshot {
+            phase: TimerPhase::Break,
+            session_id: None,
+            task_id: None,
+            duration_seconds: active.duration_seconds,
+            remaining_seconds: (active.duration_seconds - (now - active.started_at).num_seconds()).max(0),
+        },
+    }
+}
+
+fn break_after(session: &ActiveSession, now: NaiveDateTime) -> TimerState {
+    if session.break_minutes > 0 {
+        TimerState::Break(ActiveBreak {
+            started_at: now,
+            duration_seconds: session.break_minutes as i64 * 60,
+        })
+    } else {
+        TimerState::Idle
+    }
+}
+
+fn close_open_pause(conn: &Connection, session_id: i64, now: NaiveDateTime) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE pomodoro_pauses SET resumed_at = ?1 WHERE session_id = ?2 AND resumed_at IS NULL",
+        (format_timestamp(now), session_id),
+    )?;
+    Ok(())
+}
+
+fn complete_session(conn: &Connection, session: &ActiveSession, end_time: NaiveDateTime) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE pomodoro_sessions SET completed = 1, status = 'completed', end_time = ?1 WHERE id = ?2",
+        (format_timestamp(end_time), session.id),
+    )?;
+    Ok(())
+}
+
+/// Drives the timer once a second and pushes its state to the frontend.
+pub fn spawn_ticker(app: AppHandle) {
+    thread::spawn(move || loop {
+        thread::sleep(Duration::from_secs(1));
+        if let Err(e) = tick_and_emit(&app) {
+            let _ = app.emit(ERROR_EVENT, &e);
+        }
+    });
+}
+
+fn tick_and_emit(app: &AppHandle) -> AppResult<()> {
+    let db = app.state::<DbState>();
+    let timer = app.state::<PomodoroTimer>();
+
+    let now = now();
+    let (outcome, snapshot) = {
+        let mut conn = db.conn.lock()?;
+        let tx = conn.transaction()?;
+        let transition = timer.tick(&tx, now)?;
+        if let TickOutcome::SessionCompleted(session_id) = *transition.outcome() {
+            crate::activity::record_session(&tx, crate::activity::TIMER_COMMAND, "focus_completed", session_id)?;
+            crate::commands::update_user_stats(&tx)?;
+        }
+        tx.commit()?;
+        (timer.apply(transition)?, timer.snapshot(now)?)
+    };
+
+    if let TickOutcome::SessionCompleted(session_id) = outcome {
+        let _ = app.emit(SESSION_COMPLETED_EVENT, session_id);
+    }
+    if outcome != TickOutcome::Unchanged {
+        let _ = app.emit(PHASE_CHANGED_EVENT, &snapshot);
+    }
+    if snapshot.phase != TimerPhase::Idle {
+        let _ = app.emit(TICK_EVENT, &snapshot);
+    }
+
+    Ok(())
+}
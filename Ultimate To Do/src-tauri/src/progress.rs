@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+
+/// How much of a task's subtree is done, counted over its leaves. A task
+/// without subtasks is its own single leaf.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct TaskProgress {
+    pub completed_leaves: i64,
+    pub total_leaves: i64,
+    /// Share of the work done, from 0 to 1. Leaves weigh their estimated
+    /// minutes; a leaf without an estimate weighs the average of those with
+    /// one, so without any estimates this is the share of leaves done.
+    pub ratio: f64,
+}
+
+/// Progress of one task in a subtree returned by `get_subtree_progress`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubtreeProgress {
+    pub task_id: i64,
+    pub parent_id: Option<i64>,
+    /// 0 for the task the subtree was asked for
+    pub depth: i64,
+    pub progress: TaskProgress,
+}
+
+/// Running totals over a set of leaves. Tallies of sibling subtrees add up to
+/// their parent's, so progress can be rolled up without keeping the leaves.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LeafTally {
+    leaves: i64,
+    completed: i64,
+    estimated: i64,
+    estimated_minutes: i64,
+    completed_estimated_minutes: i64,
+    completed_unestimated: i64,
+}
+
+impl LeafTally {
+    pub fn leaf(is_completed: bool, estimated_minutes: Option<i32>) -> Self {
+        let done = i64::from(is_completed);
+        match estimated_minutes {
+            Some(minutes) => LeafTally {
+                leaves: 1,
+                completed: done,
+                estimated: 1,
+                estimated_minutes: i64::from(minutes),
+                completed_estimated_minutes: done * i64::from(minutes),
+                completed_unestimated: 0,
+            },
+            None => LeafTally {
+                leaves: 1,
+                completed: done,
+                completed_unestimated: done,
+                ..LeafTally::default()
+            },
+        }
+    }
+
+    pub fn add(&mut self, other: LeafTally) {
+        self.leaves += other.leaves;
+        self.completed += other.completed;
+        self.estimated += other.estimated;
+        self.estimated_minutes += other.estimated_minutes;
+        self.completed_estimated_minutes += other.completed_estimated_minutes;
+        self.completed_unestimated += other.completed_unestimated;
+    }
+
+    pub fn progress(&self) -> TaskProgress {
+        let ratio = if self.leaves == 0 {
+            0.0
+        } else if self.estimated == 0 || self.estimated_minutes == 0 {
+            self.completed as f64 / self.leaves as f64
+        } else {
+            let average = self.estimated_minutes as f64 / self.estimated as f64;
+            let unestimated = (self.leaves - self.estimated) as f64;
+            let done = self.completed_estimated_minutes as f64 + self.completed_unestimated as f64 * average;
+            done / (self.estimated_minutes as f64 + unestimated * average)
+        };
+
+        TaskProgress {
+            completed_leaves: self.completed,
+            total_leaves: self.leaves,
+            ratio,
+        }
+    }
+}
@@ -0,0 +1,451 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+/// How often a recurring task repeats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// A parsed `recurrence_pattern`.
+///
+/// Accepted forms:
+/// - `daily`, `weekly`, `monthly`, `yearly`
+/// - `every N days` / `every N weeks` / `every N months` / `every N years`
+/// - `weekly:mon,wed,fri` and `monthly:15` (`monthly:-1` is the last day of the month)
+/// - a subset of RFC 5545 RRULE, e.g. `RRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH;UNTIL=20261231`
+///   (supported parts: FREQ, INTERVAL, BYDAY, BYMONTHDAY, UNTIL)
+///
+/// Occurrences are anchored on the due date the series started from (the
+/// task's `recurrence_anchor`), not its current one. Days of month that do not
+/// exist (e.g. the 31st in April) fall on the last day of that month.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecurrenceRule {
+    pub frequency: Frequency,
+    pub interval: u32,
+    pub by_weekday: Vec<Weekday>,
+    pub by_month_day: Option<i32>,
+    pub until: Option<NaiveDate>,
+}
+
+impl RecurrenceRule {
+    fn new(frequency: Frequency, interval: u32) -> Self {
+        RecurrenceRule {
+            frequency,
+            interval,
+            by_weekday: vec![],
+            by_month_day: None,
+            until: None,
+        }
+    }
+
+    pub fn parse(pattern: &str) -> Result<Self, String> {
+        let pattern = pattern.trim();
+        let lower = pattern.to_ascii_lowercase();
+
+        let rule = if lower.starts_with("rrule:") || lower.starts_with("freq=") {
+            let body = if lower.starts_with("rrule:") {
+                &pattern[6..]
+            } else {
+                pattern
+            };
+            parse_rrule(body)?
+        } else if let Some(rest) = lower.strip_prefix("every ") {
+            parse_every(rest)?
+        } else if let Some(days) = lower.strip_prefix("weekly:") {
+            let mut rule = RecurrenceRule::new(Frequency::Weekly, 1);
+            rule.by_weekday = parse_weekdays(days)?;
+            rule
+        } else if let Some(day) = lower.strip_prefix("monthly:") {
+            let mut rule = RecurrenceRule::new(Frequency::Monthly, 1);
+            rule.by_month_day = Some(parse_month_day(day)?);
+            rule
+        } else {
+            match lower.as_str() {
+                "daily" => RecurrenceRule::new(Frequency::Daily, 1),
+                "weekly" => RecurrenceRule::new(Frequency::Weekly, 1),
+                "monthly" => RecurrenceRule::new(Frequency::Monthly, 1),
+                "yearly" | "annually" => RecurrenceRule::new(Frequency::Yearly, 1),
+                _ => return Err(format!("Unrecognized recurrence pattern '{}'", pattern)),
+            }
+        };
+
+        if rule.interval == 0 {
+            return Err("Recurrence interval must be at least 1".to_string());
+        }
+
+        Ok(rule)
+    }
+
+    /// Returns the first occurrence strictly after `after` for a series
+    /// anchored at `anchor`, or `None` once the rule's UNTIL date has passed.
+    pub fn next_after(&self, anchor: NaiveDate, after: NaiveDate) -> Option<NaiveDate> {
+        let start = if after < anchor {
+            anchor
+        } else {
+            after + Duration::days(1)
+        };
+
+        // Every supported rule repeats within one interval of its frequency,
+        // so scanning that many days is guaranteed to find the next occurrence.
+        let period_days = match self.frequency {
+            Frequency::Daily => 7,
+            Frequency::Weekly => 7,
+            Frequency::Monthly => 31,
+            Frequency::Yearly => 366,
+        };
+        let max_days = period_days * (self.interval as i64 + 1);
+
+        let mut date = start;
+        for _ in 0..max_days {
+            if let Some(until) = self.until {
+                if date > until {
+                    return None;
+                }
+            }
+            if self.matches(anchor, date) {
+                return Some(date);
+            }
+            date += Duration::days(1);
+        }
+
+        None
+    }
+
+    /// Whether `date` is an occurrence of the series anchored at `anchor`.
+    pub fn matches(&self, anchor: NaiveDate, date: NaiveDate) -> bool {
+        if date < anchor {
+            return false;
+        }
+        if let Some(until) = self.until {
+            if date > until {
+                return false;
+            }
+        }
+
+        let interval = self.interval as i64;
+        match self.frequency {
+            Frequency::Daily => {
+                let days = (date - anchor).num_days();
+                days % interval == 0
+                    && (self.by_weekday.is_empty() || self.by_weekday.contains(&date.weekday()))
+            }
+            Frequency::Weekly => {
+                let weeks = (week_start(date) - week_start(anchor)).num_days() / 7;
+                let weekday_matches = if self.by_weekday.is_empty() {
+                    date.weekday() == anchor.weekday()
+                } else {
+                    self.by_weekday.contains(&date.weekday())
+                };
+                weeks % interval == 0 && weekday_matches
+            }
+            Frequency::Monthly => {
+                let months = month_index(date) - month_index(anchor);
+                let target = self.by_month_day.unwrap_or(anchor.day() as i32);
+                months % interval == 0
+                    && date.day() == resolve_month_day(date.year(), date.month(), target)
+            }
+            Frequency::Yearly => {
+                let years = (date.year() - anchor.year()) as i64;
+                years % interval == 0
+                    && date.month() == anchor.month()
+                    && date.day() == resolve_month_day(date.year(), date.month(), anchor.day() as i32)
+            }
+        }
+    }
+}
+
+/// Parses a stored `due_date` (`YYYY-MM-DD`, optionally followed by a time).
+pub fn parse_date(value: &str) -> Option<NaiveDate> {
+    let date_part = value.get(..10).unwrap_or(value);
+    NaiveDate::parse_from_str(date_part, "%Y-%m-%d").ok()
+}
+
+fn parse_every(rest: &str) -> Result<RecurrenceRule, String> {
+    let mut parts = rest.split_whitespace();
+    let (count, unit) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(count), Some(unit), None) => (count, unit),
+        (Some(unit), None, None) => ("1", unit),
+        _ => return Err(format!("Unrecognized recurrence pattern 'every {}'", rest)),
+    };
+
+    let interval: u32 = count
+        .parse()
+        .map_err(|_| format!("Invalid recurrence interval '{}'", count))?;
+
+    let frequency = match unit.trim_end_matches('s') {
+        "day" => Frequency::Daily,
+        "week" => Frequency::Weekly,
+        "month" => Frequency::Monthly,
+        "year" => Frequency::Yearly,
+        _ => return Err(format!("Unknown recurrence unit '{}'", unit)),
+    };
+
+    Ok(RecurrenceRule::new(frequency, interval))
+}
+
+fn parse_rrule(body: &str) -> Result<RecurrenceRule, String> {
+    let mut frequency = None;
+    let mut interval = 1;
+    let mut by_weekday = vec![];
+    let mut by_month_day = None;
+    let mut until = None;
+
+    for part in body.split(';').filter(|p| !p.trim().is_empty()) {
+        let (key, value) = part
+            .split_once('=')
+            .ok_or_else(|| format!("Malformed RRULE part '{}'", part))?;
+        let value = value.trim();
+
+        match key.trim().to_ascii_uppercase().as_str() {
+            "FREQ" => {
+                frequency = Some(match value.to_ascii_uppercase().as_str() {
+                    "DAILY" => Frequency::Daily,
+                    "WEEKLY" => Frequency::Weekly,
+                    "MONTHLY" => Frequency::Monthly,
+                    "YEARLY" => Frequency::Yearly,
+                    other => return Err(format!("Unsupported RRULE frequency '{}'", other)),
+                });
+            }
+            "INTERVAL" => {
+                interval = value
+                    .parse()
+                    .map_err(|_| format!("Invalid RRULE interval '{}'", value))?;
+            }
+            "BYDAY" => by_weekday = parse_weekdays(value)?,
+            "BYMONTHDAY" => by_month_day = Some(parse_month_day(value)?),
+            "UNTIL" => {
+                let date_part = value.get(..8).unwrap_or(value);
+                until = Some(
+                    NaiveDate::parse_from_str(date_part, "%Y%m%d")
+                        .map_err(|_| format!("Invalid RRULE UNTIL '{}'", value))?,
+                );
+            }
+            "WKST" => {}
+            other => return Err(format!("Unsupported RRULE part '{}'", other)),
+        }
+    }
+
+    let frequency = frequency.ok_or_else(|| "RRULE is missing FREQ".to_string())?;
+    if by_month_day.is_some() && frequency != Frequency::Monthly {
+        return Err("BYMONTHDAY is only supported with FREQ=MONTHLY".to_string());
+    }
+    if !by_weekday.is_empty() && !matches!(frequency, Frequency::Daily | Frequency::Weekly) {
+        return Err("BYDAY is only supported with FREQ=DAILY or FREQ=WEEKLY".to_string());
+    }
+
+    Ok(RecurrenceRule {
+        frequency,
+        interval,
+        by_weekday,
+        by_month_day,
+        until,
+    })
+}
+
+fn parse_weekdays(value: &str) -> Result<Vec<Weekday>, String> {
+    let mut days = Vec::new();
+    for token in value.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+        let day = match token.to_ascii_lowercase().as_str() {
+            "mo" | "mon" | "monday" => Weekday::Mon,
+            "tu" | "tue" | "tuesday" => Weekday::Tue,
+            "we" | "wed" | "wednesday" => Weekday::Wed,
+            "th" | "thu" | "thursday" => Weekday::Thu,
+            "fr" | "fri" | "friday" => Weekday::Fri,
+            "sa" | "sat" | "saturday" => Weekday::Sat,
+            "su" | "sun" | "sunday" => Weekday::Sun,
+            _ => return Err(format!("Unknown weekday '{}'", token)),
+        };
+        if !days.contains(&day) {
+            days.push(day);
+        }
+    }
+
+    if days.is_empty() {
+        return Err("At least one weekday is required".to_string());
+    }
+
+    Ok(days)
+}
+
+fn parse_month_day(value: &str) -> Result<i32, String> {
+    let day: i32 = value
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid day of month '{}'", value))?;
+
+    if day == 0 || !(-31..=31).contains(&day) {
+        return Err(format!("Day of month must be between 1 and 31 (or -1 for the last day), got {}", day));
+    }
+
+    Ok(day)
+}
+
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+fn month_index(date: NaiveDate) -> i64 {
+    date.year() as i64 * 12 + date.month0() as i64
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .and_then(|d| d.pred_opt())
+        .map(|d| d.day())
+        .unwrap_or(28)
+}
+
+// Positive days past the end of the month clamp to the last day; negative
+// days count back from the end (-1 is the last day).
+fn resolve_month_day(year: i32, month: u32, day: i32) -> u32 {
+    let last = days_in_month(year, month) as i32;
+    let resolved = if day < 0 { last + day + 1 } else { day.min(last) };
+    resolved.max(1) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    // The first `count` occurrences after `anchor`, each found from the one
+    // before as rolling a completed task does
+    fn occurrences(pattern: &str, anchor: &str, count: usize) -> Vec<String> {
+        let rule = RecurrenceRule::parse(pattern).unwrap();
+        let anchor = date(anchor);
+        let mut after = anchor;
+        let mut dates = Vec::new();
+        while dates.len() < count {
+            let Some(next) = rule.next_after(anchor, after) else {
+                break;
+            };
+            dates.push(next.format("%Y-%m-%d").to_string());
+            after = next;
+        }
+        dates
+    }
+
+    #[test]
+    fn parses_the_simple_forms() {
+        let rule = RecurrenceRule::parse("  Weekly ").unwrap();
+        assert_eq!((rule.frequency, rule.interval), (Frequency::Weekly, 1));
+        assert_eq!(RecurrenceRule::parse("annually").unwrap().frequency, Frequency::Yearly);
+
+        let rule = RecurrenceRule::parse("every 3 days").unwrap();
+        assert_eq!((rule.frequency, rule.interval), (Frequency::Daily, 3));
+        assert_eq!(RecurrenceRule::parse("every month").unwrap().frequency, Frequency::Monthly);
+
+        let rule = RecurrenceRule::parse("weekly:mon,Wed,fri,mon").unwrap();
+        assert_eq!(rule.by_weekday, vec![Weekday::Mon, Weekday::Wed, Weekday::Fri]);
+        assert_eq!(RecurrenceRule::parse("monthly:-1").unwrap().by_month_day, Some(-1));
+    }
+
+    #[test]
+    fn parses_rrules() {
+        let rule = RecurrenceRule::parse("RRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH;UNTIL=20261231T000000Z").unwrap();
+        assert_eq!(rule.frequency, Frequency::Weekly);
+        assert_eq!(rule.interval, 2);
+        assert_eq!(rule.by_weekday, vec![Weekday::Mon, Weekday::Thu]);
+        assert_eq!(rule.until, Some(date("2026-12-31")));
+
+        let rule = RecurrenceRule::parse("freq=monthly;bymonthday=15;wkst=MO").unwrap();
+        assert_eq!((rule.frequency, rule.by_month_day), (Frequency::Monthly, Some(15)));
+    }
+
+    #[test]
+    fn rejects_bad_patterns() {
+        for pattern in [
+            "",
+            "fortnightly",
+            "every 0 days",
+            "every two weeks",
+            "every 2 fortnights",
+            "every 1 2 days",
+            "weekly:",
+            "weekly:mon,funday",
+            "monthly:0",
+            "monthly:32",
+            "monthly:x",
+            "RRULE:INTERVAL=2",
+            "RRULE:FREQ=HOURLY",
+            "RRULE:FREQ=DAILY;INTERVAL=0",
+            "RRULE:FREQ=DAILY;INTERVAL=-1",
+            "RRULE:FREQ=DAILY;COUNT=3",
+            "RRULE:FREQ=DAILY;BYDAY",
+            "RRULE:FREQ=DAILY;UNTIL=tomorrow",
+            "RRULE:FREQ=WEEKLY;BYMONTHDAY=1",
+            "RRULE:FREQ=MONTHLY;BYDAY=MO",
+        ] {
+            assert!(RecurrenceRule::parse(pattern).is_err(), "{:?} should be rejected", pattern);
+        }
+    }
+
+    #[test]
+    fn steps_by_interval() {
+        assert_eq!(occurrences("every 3 days", "2025-03-30", 3), ["2025-04-02", "2025-04-05", "2025-04-08"]);
+        assert_eq!(occurrences("every 2 weeks", "2025-03-03", 2), ["2025-03-17", "2025-03-31"]);
+        assert_eq!(occurrences("every 2 years", "2025-06-01", 2), ["2027-06-01", "2029-06-01"]);
+    }
+
+    #[test]
+    fn weekdays_within_every_other_week() {
+        // Monday the 3rd: the weeks of the 3rd, 17th and 31st count
+        assert_eq!(
+            occurrences("RRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH", "2025-03-03", 5),
+            ["2025-03-06", "2025-03-17", "2025-03-20", "2025-03-31", "2025-04-03"]
+        );
+        assert_eq!(
+            occurrences("RRULE:FREQ=DAILY;BYDAY=SA,SU", "2025-03-07", 3),
+            ["2025-03-08", "2025-03-09", "2025-03-15"]
+        );
+    }
+
+    #[test]
+    fn month_end_is_clamped_without_losing_the_day() {
+        assert_eq!(
+            occurrences("monthly", "2026-01-31", 4),
+            ["2026-02-28", "2026-03-31", "2026-04-30", "2026-05-31"]
+        );
+        assert_eq!(
+            occurrences("RRULE:FREQ=MONTHLY", "2026-01-31", 3),
+            ["2026-02-28", "2026-03-31", "2026-04-30"]
+        );
+        assert_eq!(occurrences("monthly:-1", "2024-01-31", 2), ["2024-02-29", "2024-03-31"]);
+        assert_eq!(occurrences("monthly:30", "2025-01-15", 3), ["2025-01-30", "2025-02-28", "2025-03-30"]);
+    }
+
+    #[test]
+    fn leap_day_series_returns_to_the_29th() {
+        assert_eq!(
+            occurrences("yearly", "2024-02-29", 4),
+            ["2025-02-28", "2026-02-28", "2027-02-28", "2028-02-29"]
+        );
+    }
+
+    #[test]
+    fn until_ends_the_series() {
+        assert_eq!(
+            occurrences("RRULE:FREQ=WEEKLY;UNTIL=20250320", "2025-03-03", 5),
+            ["2025-03-10", "2025-03-17"]
+        );
+
+        let rule = RecurrenceRule::parse("RRULE:FREQ=DAILY;UNTIL=20250305").unwrap();
+        assert_eq!(rule.next_after(date("2025-03-01"), date("2025-03-04")), Some(date("2025-03-05")));
+        assert_eq!(rule.next_after(date("2025-03-01"), date("2025-03-05")), None);
+        assert!(!rule.matches(date("2025-03-01"), date("2025-03-06")));
+    }
+
+    #[test]
+    fn nothing_before_the_anchor() {
+        let rule = RecurrenceRule::parse("daily").unwrap();
+        assert!(!rule.matches(date("2025-03-10"), date("2025-03-09")));
+        assert_eq!(rule.next_after(date("2025-03-10"), date("2025-03-01")), Some(date("2025-03-10")));
+    }
+}
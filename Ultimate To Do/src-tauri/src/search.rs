@@ -0,0 +1,94 @@
+//! Search box text to FTS5 queries, and FTS5 highlights back to text parts.
+//!
+//! The box takes plain words, each matched as a prefix, and `"quoted phrases"`,
+//! matched as written. Everything else FTS5 would read as syntax (`AND`, `*`,
+//! column filters, ...) is searched for literally instead.
+
+use serde::{Deserialize, Serialize};
+
+/// Passed to `highlight()` and `snippet()` around every matching term.
+/// Control characters, which don't turn up in task text typed by hand.
+pub const MATCH_START: &str = "\u{2}";
+pub const MATCH_END: &str = "\u{3}";
+
+/// A run of text from a title or description, matching the search or not.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextPart {
+    pub text: String,
+    pub is_match: bool,
+}
+
+/// The FTS5 query for what was typed in the search box, or `None` if there
+/// is nothing to search for. All terms have to match.
+pub fn match_expression(input: &str) -> Option<String> {
+    let mut terms = Vec::new();
+    let mut rest = input;
+
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            break;
+        }
+
+        let (term, is_phrase) = if let Some(quoted) = rest.strip_prefix('"') {
+            // An unclosed quote runs to the end
+            let end = quoted.find('"').unwrap_or(quoted.len());
+            let term = &quoted[..end];
+            rest = quoted.get(end + 1..).unwrap_or("");
+            (term, true)
+        } else {
+            let end = rest.find(|c: char| c.is_whitespace() || c == '"').unwrap_or(rest.len());
+            let term = &rest[..end];
+            rest = &rest[end..];
+            (term, false)
+        };
+
+        // Terms without any letters or digits have no tokens to match
+        if !term.chars().any(char::is_alphanumeric) {
+            continue;
+        }
+        let quoted = format!("\"{}\"", term.replace('"', "\"\""));
+        terms.push(if is_phrase { quoted } else { quoted + "*" });
+    }
+
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+/// Splits text marked with `MATCH_START` and `MATCH_END` into parts.
+pub fn text_parts(marked: &str) -> Vec<TextPart> {
+    let mut parts = Vec::new();
+    let mut rest = marked;
+
+    while !rest.is_empty() {
+        let Some(start) = rest.find(MATCH_START) else {
+            push_part(&mut parts, rest, false);
+            break;
+        };
+        push_part(&mut parts, &rest[..start], false);
+
+        let matched = &rest[start + MATCH_START.len()..];
+        let end = matched.find(MATCH_END).unwrap_or(matched.len());
+        push_part(&mut parts, &matched[..end], true);
+        rest = matched.get(end + MATCH_END.len()..).unwrap_or("");
+    }
+
+    parts
+}
+
+// Adjacent matches ("foo bar" both matching) come out as one part
+fn push_part(parts: &mut Vec<TextPart>, text: &str, is_match: bool) {
+    if text.is_empty() {
+        return;
+    }
+    match parts.last_mut() {
+        Some(last) if last.is_match == is_match => last.text.push_str(text),
+        _ => parts.push(TextPart {
+            text: text.to_string(),
+            is_match,
+        }),
+    }
+}
@@ -0,0 +1,120 @@
+use chrono::{Duration, NaiveDate};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Streaks {
+    pub current: i32,
+    pub longest: i32,
+}
+
+/// Computes study streaks from the (local) dates studied: days on which a
+/// focus session was completed or a task was checked off.
+///
+/// A streak is a run of consecutive calendar days. The current streak is the
+/// run ending today, or yesterday if today has not been studied yet, so an
+/// unfinished day doesn't reset it; it drops to zero once a whole day is
+/// missed. Dates after `today` (e.g. from a clock change) are ignored.
+pub fn calculate_streaks(study_days: &[NaiveDate], today: NaiveDate) -> Streaks {
+    let mut days: Vec<NaiveDate> = study_days.iter().copied().filter(|d| *d <= today).collect();
+    days.sort_unstable();
+    days.dedup();
+
+    let Some(&last_day) = days.last() else {
+        return Streaks::default();
+    };
+
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+
+    for day in &days {
+        run = match previous {
+            Some(prev) if *day - prev == Duration::days(1) => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        previous = Some(*day);
+    }
+
+    // `run` is now the length of the streak ending on `last_day`
+    let current = if today - last_day <= Duration::days(1) { run } else { 0 };
+
+    Streaks { current, longest }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn streaks(days: &[&str], today: &str) -> Streaks {
+        let days: Vec<NaiveDate> = days.iter().map(|d| date(d)).collect();
+        calculate_streaks(&days, date(today))
+    }
+
+    #[test]
+    fn no_study_days() {
+        assert_eq!(streaks(&[], "2025-03-10"), Streaks { current: 0, longest: 0 });
+    }
+
+    #[test]
+    fn single_day_today() {
+        assert_eq!(streaks(&["2025-03-10"], "2025-03-10"), Streaks { current: 1, longest: 1 });
+    }
+
+    #[test]
+    fn run_ending_today() {
+        let days = ["2025-03-08", "2025-03-09", "2025-03-10"];
+        assert_eq!(streaks(&days, "2025-03-10"), Streaks { current: 3, longest: 3 });
+    }
+
+    #[test]
+    fn today_not_studied_yet_keeps_the_streak() {
+        let days = ["2025-03-08", "2025-03-09"];
+        assert_eq!(streaks(&days, "2025-03-10"), Streaks { current: 2, longest: 2 });
+    }
+
+    #[test]
+    fn a_missed_day_resets_the_current_streak() {
+        let days = ["2025-03-07", "2025-03-08"];
+        assert_eq!(streaks(&days, "2025-03-10"), Streaks { current: 0, longest: 2 });
+    }
+
+    #[test]
+    fn a_gap_starts_a_new_run() {
+        let days = ["2025-03-01", "2025-03-02", "2025-03-03", "2025-03-05", "2025-03-06"];
+        assert_eq!(streaks(&days, "2025-03-06"), Streaks { current: 2, longest: 3 });
+    }
+
+    #[test]
+    fn duplicates_and_order_do_not_matter() {
+        let days = ["2025-03-10", "2025-03-09", "2025-03-10", "2025-03-09", "2025-03-08"];
+        assert_eq!(streaks(&days, "2025-03-10"), Streaks { current: 3, longest: 3 });
+    }
+
+    #[test]
+    fn future_days_are_ignored() {
+        let days = ["2025-03-09", "2025-03-10", "2025-03-11", "2025-03-12"];
+        assert_eq!(streaks(&days, "2025-03-10"), Streaks { current: 2, longest: 2 });
+
+        assert_eq!(streaks(&["2025-03-12"], "2025-03-10"), Streaks { current: 0, longest: 0 });
+    }
+
+    #[test]
+    fn runs_cross_month_and_year_ends() {
+        let days = ["2024-12-30", "2024-12-31", "2025-01-01", "2025-01-31", "2025-02-01"];
+        assert_eq!(streaks(&days, "2025-02-01"), Streaks { current: 2, longest: 3 });
+    }
+
+    #[test]
+    fn leap_day_is_a_day() {
+        let days = ["2024-02-28", "2024-02-29", "2024-03-01"];
+        assert_eq!(streaks(&days, "2024-03-01"), Streaks { current: 3, longest: 3 });
+
+        // No 29th in 2025, so the 28th runs straight into March
+        let days = ["2025-02-27", "2025-02-28", "2025-03-01"];
+        assert_eq!(streaks(&days, "2025-03-02"), Streaks { current: 3, longest: 3 });
+    }
+}
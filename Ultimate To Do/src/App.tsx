@@ -3,6 +3,7 @@ import { CheckSquare, Calendar, Clock } from 'lucide-react';
 import { TodoList } from './components/TodoList/TodoList';
 import { Next60Tracker } from './components/Next60Tracker/Next60Tracker';
 import { PomodoroTimer } from './components/PomodoroTimer/PomodoroTimer';
+import { UndoControls } from './components/common/UndoControls';
 
 type Tab = 'tasks' | 'tracker' | 'pomodoro';
 
@@ -17,6 +18,8 @@ function App() {
             <h1 className="text-2xl font-bold text-gray-900">Ultimate To-Do</h1>
             
             <div className="flex space-x-4">
+              <UndoControls />
+
               <button
                 onClick={() => setActiveTab('tasks')}
                 className={`flex items-center space-x-2 px-4 py-2 rounded-lg transition-colors ${
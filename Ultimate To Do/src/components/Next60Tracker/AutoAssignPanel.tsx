@@ -0,0 +1,98 @@
+import React, { useState } from 'react';
+import { Button } from '../common/Button';
+import type { Plan } from '../../types/tracker';
+import type { Task } from '../../types/task';
+
+interface AutoAssignPanelProps {
+  plan: Plan | null;
+  tasks: Task[];
+  onPreview: (capacity: number) => void;
+  onApply: (capacity: number) => void;
+  onDiscard: () => void;
+}
+
+const REASON_LABELS: Record<string, string> = {
+  recurring: 'Recurring',
+  overdue: 'Overdue',
+  due_date: 'Before due date',
+  balanced: 'Balanced',
+};
+
+export const AutoAssignPanel: React.FC<AutoAssignPanelProps> = ({
+  plan,
+  tasks,
+  onPreview,
+  onApply,
+  onDiscard,
+}) => {
+  const [capacity, setCapacity] = useState(6);
+
+  const titles = new Map<number, string>();
+  const collect = (taskList: Task[]) => {
+    taskList.forEach((task) => {
+      titles.set(task.id, task.title);
+      collect(task.subtasks ?? []);
+    });
+  };
+  collect(tasks);
+
+  return (
+    <div className="bg-white rounded-lg shadow p-6 mb-6">
+      <h2 className="text-xl font-semibold mb-2">Auto-assign</h2>
+      <p className="text-sm text-gray-600 mb-4">
+        Spread open tasks over the coming days by due date, priority and effort. A point is about
+        an hour of work or one level of difficulty. Manual assignments stay where they are.
+      </p>
+
+      <div className="flex items-center space-x-2 mb-4">
+        <label className="text-sm text-gray-700" htmlFor="planner-capacity">
+          Effort points per day
+        </label>
+        <input
+          id="planner-capacity"
+          type="number"
+          min={1}
+          max={50}
+          value={capacity}
+          onChange={(e) => setCapacity(Math.max(1, Number(e.target.value) || 1))}
+          className="w-20 px-2 py-1 border border-gray-300 rounded"
+        />
+        <Button size="sm" variant="secondary" onClick={() => onPreview(capacity)}>
+          Preview
+        </Button>
+      </div>
+
+      {plan && (
+        <div className="space-y-3">
+          <p className="text-sm text-gray-700">
+            {plan.assignments.length} assignment(s) between {plan.start_date} and {plan.end_date}
+          </p>
+          <ul className="text-sm max-h-48 overflow-y-auto space-y-1">
+            {plan.assignments.map((a) => (
+              <li key={`${a.task_id}-${a.date}`} className="flex justify-between">
+                <span>{titles.get(a.task_id) ?? `Task ${a.task_id}`}</span>
+                <span className="text-gray-500">
+                  {a.date} · {REASON_LABELS[a.reason]}
+                </span>
+              </li>
+            ))}
+          </ul>
+          {plan.unscheduled.length > 0 && (
+            <p className="text-sm text-red-600">
+              No room before the due date for:{' '}
+              {plan.unscheduled.map((id) => titles.get(id) ?? `Task ${id}`).join(', ')}
+            </p>
+          )}
+          <div className="flex space-x-2">
+            <Button size="sm" onClick={() => onApply(capacity)}>
+              Apply
+            </Button>
+            <Button size="sm" variant="secondary" onClick={onDiscard}>
+              Discard
+            </Button>
+          </div>
+        </div>
+      )}
+    </div>
+  );
+};
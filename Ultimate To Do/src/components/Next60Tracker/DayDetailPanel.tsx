@@ -1,5 +1,5 @@
-import React, { useState } from 'react';
-import { Plus } from 'lucide-react';
+import React from 'react';
+import { Plus, X } from 'lucide-react';
 import { Checkbox } from '../common/Checkbox';
 import { Button } from '../common/Button';
 import type { DayTracker } from '../../types/tracker';
@@ -10,6 +10,8 @@ interface DayDetailPanelProps {
   day: DayTracker;
   tasks: Task[];
   onToggleTask: (taskId: number) => void;
+  onUnassignTask: (taskId: number) => void;
+  onMoveTask: (taskId: number, toDate: string) => void;
   onShowAssignment: () => void;
 }
 
@@ -17,6 +19,8 @@ export const DayDetailPanel: React.FC<DayDetailPanelProps> = ({
   day,
   tasks,
   onToggleTask,
+  onUnassignTask,
+  onMoveTask,
   onShowAssignment,
 }) => {
   const formatDisplayDate = (dateStr: string) => {
@@ -83,7 +87,24 @@ export const DayDetailPanel: React.FC<DayDetailPanelProps> = ({
                       {task.description}
                     </div>
                   )}
+
+                  <label className="text-xs text-gray-500 mt-2 flex items-center space-x-2">
+                    <span>Move to</span>
+                    <input
+                      type="date"
+                      className="border border-gray-300 rounded px-1"
+                      onChange={(e) => e.target.value && onMoveTask(task.id, e.target.value)}
+                    />
+                  </label>
                 </div>
+
+                <button
+                  onClick={() => onUnassignTask(task.id)}
+                  className="text-gray-400 hover:text-red-600"
+                  title="Remove from this day"
+                >
+                  <X size={16} />
+                </button>
               </div>
             ))}
           </div>
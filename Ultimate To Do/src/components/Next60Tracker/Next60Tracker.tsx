@@ -2,10 +2,13 @@ import React, { useEffect, useState } from 'react';
 import { Plus } from 'lucide-react';
 import { useTrackerStore } from '../../stores/trackerStore';
 import { useTaskStore } from '../../stores/taskStore';
+import { useProjectStore } from '../../stores/projectStore';
 import { DayBox } from './DayBox';
 import { DayDetailPanel } from './DayDetailPanel';
 import { TaskAssignment } from './TaskAssignment';
+import { AutoAssignPanel } from './AutoAssignPanel';
 import { Button } from '../common/Button';
+import { ProjectSelect } from '../common/ProjectSelect';
 
 export const Next60Tracker: React.FC = () => {
   const {
@@ -19,9 +22,20 @@ export const Next60Tracker: React.FC = () => {
     assignTask,
     selectDay,
     clearSelection,
+    toggleTaskForDay,
+    unassignTask,
+    moveTask,
+    rescheduleIncomplete,
+    plan,
+    previewPlan,
+    applyPlan,
+    discardPlan,
+    projectId,
+    setProject,
   } = useTrackerStore();
+  const { projects, fetchProjects } = useProjectStore();
 
-  const { tasks, fetchTasks, toggleTask } = useTaskStore();
+  const { tasks, fetchTasks } = useTaskStore();
   const [showTaskAssignment, setShowTaskAssignment] = useState(false);
 
   useEffect(() => {
@@ -29,12 +43,13 @@ export const Next60Tracker: React.FC = () => {
       await initializeDays();
       await fetchDays();
       await fetchTasks();
+      await fetchProjects();
     };
     initialize();
-  }, [initializeDays, fetchDays, fetchTasks]);
+  }, [initializeDays, fetchDays, fetchTasks, fetchProjects]);
 
-  const handleDayClick = async (dayNumber: number) => {
-    await selectDay(dayNumber);
+  const handleDayClick = async (date: string) => {
+    await selectDay(date);
     setShowTaskAssignment(false); // Reset assignment view when selecting new day
   };
 
@@ -50,14 +65,23 @@ export const Next60Tracker: React.FC = () => {
   };
 
   const handleToggleTask = async (taskId: number) => {
-    await toggleTask(taskId);
-    if (selectedDay) {
-      await selectDay(selectedDay);
-    }
-    await fetchDays();
+    if (!selectedDay) return;
+    await toggleTaskForDay(taskId, selectedDay);
+    // Completing a task here can close it or move a recurring one on
+    await fetchTasks();
   };
 
-  const selectedDayData = days.find((d) => d.day_number === selectedDay);
+  const handleUnassignTask = async (taskId: number) => {
+    if (!selectedDay) return;
+    await unassignTask(taskId, selectedDay);
+  };
+
+  const handleMoveTask = async (taskId: number, toDate: string) => {
+    if (!selectedDay) return;
+    await moveTask(taskId, selectedDay, toDate);
+  };
+
+  const selectedDayData = days.find((d) => d.date === selectedDay);
 
   return (
     <div className="grid grid-cols-2 gap-4">
@@ -65,11 +89,18 @@ export const Next60Tracker: React.FC = () => {
         <div className="mb-6">
           <h1 className="text-3xl font-bold text-gray-900 mb-2">Next 60 Days Tracker</h1>
           <p className="text-gray-600">
-            Track your progress over the next 60 days. Assign tasks to specific days and watch
-            your progress unfold!
+            Track your progress over the next 60 days, starting today. Assign tasks to specific
+            days and watch your progress unfold; past days are kept as history.
           </p>
         </div>
 
+        <div className="flex items-center space-x-2 mb-4 text-sm">
+          <ProjectSelect projects={projects} value={projectId} onChange={setProject} />
+          <Button size="sm" variant="secondary" onClick={rescheduleIncomplete}>
+            Move unfinished tasks to today
+          </Button>
+        </div>
+
         {error && (
           <div className="bg-red-100 border border-red-400 text-red-700 px-4 py-3 rounded mb-4">
             {error}
@@ -86,12 +117,20 @@ export const Next60Tracker: React.FC = () => {
                   <DayBox
                     key={day.id}
                     day={day}
-                    onClick={() => handleDayClick(day.day_number)}
+                    onClick={() => handleDayClick(day.date)}
                   />
                 ))}
               </div>
             </div>
 
+            <AutoAssignPanel
+              plan={plan}
+              tasks={tasks}
+              onPreview={previewPlan}
+              onApply={applyPlan}
+              onDiscard={discardPlan}
+            />
+
             <div className="bg-white rounded-lg shadow p-6">
               <h2 className="text-xl font-semibold mb-4">Legend</h2>
               <div className="grid grid-cols-2 md:grid-cols-4 gap-4">
@@ -139,6 +178,8 @@ export const Next60Tracker: React.FC = () => {
             day={selectedDayData}
             tasks={selectedDayTasks}
             onToggleTask={handleToggleTask}
+            onUnassignTask={handleUnassignTask}
+            onMoveTask={handleMoveTask}
             onShowAssignment={() => setShowTaskAssignment(true)}
           />
         )}
@@ -146,7 +187,7 @@ export const Next60Tracker: React.FC = () => {
         {selectedDayData && showTaskAssignment && (
           <div className="bg-white p-6 rounded-lg shadow">
             <h2 className="text-xl font-semibold mb-4">
-              Assign Task to Day {selectedDayData.day_number}
+              Assign Task to {selectedDayData.date}
             </h2>
             <TaskAssignment
               tasks={tasks}
@@ -2,78 +2,78 @@ import React, { useEffect, useState } from 'react';
 import { Play, Pause, Square } from 'lucide-react';
 import { usePomodoroStore } from '../../stores/pomodoroStore';
 import { useTaskStore } from '../../stores/taskStore';
+import { useProjectStore } from '../../stores/projectStore';
 import { TimerDisplay } from './TimerDisplay';
 import { TaskSelector } from './TaskSelector';
 import { SessionHistory } from './SessionHistory';
 import { StatsDashboard } from './StatsDashboard';
 import { Button } from '../common/Button';
+import { TagChip } from '../common/TagChip';
+import { ProjectSelect } from '../common/ProjectSelect';
 
 export const PomodoroTimer: React.FC = () => {
   const {
-    sessionId,
-    timeRemaining,
-    isRunning,
+    timer,
+    error,
     stats,
     history,
+    subscribe,
     startSession,
-    completeSession,
     pauseSession,
     resumeSession,
-    setTimeRemaining,
+    abortSession,
     fetchStats,
     fetchHistory,
+    tagStats,
+    fetchTagStats,
   } = usePomodoroStore();
 
   const { tasks, fetchTasks } = useTaskStore();
+  const { projects, fetchProjects } = useProjectStore();
+  const [statsProjectId, setStatsProjectId] = useState<number | null>(null);
 
   const [selectedTaskId, setSelectedTaskId] = useState<number | null>(null);
   const [duration, setDuration] = useState(25);
 
   useEffect(() => {
     fetchStats();
-    fetchHistory(7);
     fetchTasks();
-  }, [fetchStats, fetchHistory, fetchTasks]);
+    fetchProjects();
+  }, [fetchStats, fetchTasks, fetchProjects]);
 
   useEffect(() => {
-    let interval: number | undefined;
-
-    if (isRunning && timeRemaining > 0) {
-      interval = window.setInterval(() => {
-        setTimeRemaining(timeRemaining - 1);
-      }, 1000);
-    } else if (timeRemaining === 0 && sessionId) {
-      // Timer completed
-      completeSession();
-      // Play notification sound (if available)
+    fetchHistory(7, undefined, statsProjectId ?? undefined);
+    fetchTagStats(7, statsProjectId ?? undefined);
+  }, [fetchHistory, fetchTagStats, statsProjectId]);
+
+  useEffect(() => {
+    const unsubscribe = subscribe(() => {
       if ('Notification' in window && Notification.permission === 'granted') {
         new Notification('Pomodoro Complete!', {
           body: 'Great job! Time for a break.',
         });
       }
-    }
+    });
 
     return () => {
-      if (interval) clearInterval(interval);
+      unsubscribe.then((unlisten) => unlisten());
     };
-  }, [isRunning, timeRemaining, sessionId, setTimeRemaining, completeSession]);
+  }, [subscribe]);
 
   const handleStart = async () => {
     await startSession(selectedTaskId, duration);
   };
 
-  const handlePause = () => {
-    pauseSession();
+  const handlePause = async () => {
+    await pauseSession();
   };
 
-  const handleResume = () => {
-    resumeSession();
+  const handleResume = async () => {
+    await resumeSession();
   };
 
   const handleStop = async () => {
-    if (sessionId) {
-      await completeSession();
-    }
+    await abortSession();
   };
 
   const requestNotificationPermission = () => {
@@ -98,9 +98,10 @@ export const PomodoroTimer: React.FC = () => {
       <div className="grid md:grid-cols-2 gap-6 mb-8">
         {/* Timer Section */}
         <div className="bg-white rounded-lg shadow-lg p-8">
-          <TimerDisplay timeRemaining={timeRemaining} />
+          <TimerDisplay timeRemaining={timer.remaining_seconds} phase={timer.phase} />
+          {error && <p className="mt-4 text-sm text-center text-red-600">{error}</p>}
 
-          {!sessionId ? (
+          {timer.phase === 'idle' ? (
             <div className="mt-8 space-y-4">
               <TaskSelector
                 tasks={tasks}
@@ -134,9 +135,16 @@ export const PomodoroTimer: React.FC = () => {
                 Start Session
               </Button>
             </div>
+          ) : timer.phase === 'break' ? (
+            <div className="mt-8 flex space-x-2">
+              <Button onClick={handleStop} variant="secondary" className="flex-1" size="lg">
+                <Square size={20} className="inline mr-2" />
+                Skip Break
+              </Button>
+            </div>
           ) : (
             <div className="mt-8 flex space-x-2">
-              {isRunning ? (
+              {timer.phase === 'focus' ? (
                 <Button onClick={handlePause} variant="secondary" className="flex-1" size="lg">
                   <Pause size={20} className="inline mr-2" />
                   Pause
@@ -165,6 +173,32 @@ export const PomodoroTimer: React.FC = () => {
       <div className="bg-white rounded-lg shadow-lg p-8">
         <h2 className="text-2xl font-bold text-gray-900 mb-6">Your Statistics</h2>
         <StatsDashboard stats={stats} />
+        {projects.length > 0 && (
+          <div className="flex items-center space-x-2 mt-6 text-sm">
+            <label htmlFor="stats-project" className="text-gray-600">Sessions for</label>
+            <ProjectSelect
+              id="stats-project"
+              projects={projects}
+              value={statsProjectId}
+              onChange={setStatsProjectId}
+            />
+          </div>
+        )}
+        {tagStats.length > 0 && (
+          <div className="mt-6">
+            <h3 className="text-lg font-semibold text-gray-900 mb-3">Last 7 days by tag</h3>
+            <ul className="space-y-2">
+              {tagStats.map((tag) => (
+                <li key={tag.tag_id} className="flex items-center justify-between text-sm">
+                  <TagChip tag={tag} />
+                  <span className="text-gray-600">
+                    {tag.study_minutes} min · {tag.sessions_completed} session(s)
+                  </span>
+                </li>
+              ))}
+            </ul>
+          </div>
+        )}
       </div>
     </div>
   );
@@ -1,10 +1,19 @@
 import React from 'react';
+import type { TimerPhase } from '../../types/pomodoro';
 
 interface TimerDisplayProps {
   timeRemaining: number;
+  phase: TimerPhase;
 }
 
-export const TimerDisplay: React.FC<TimerDisplayProps> = ({ timeRemaining }) => {
+const PHASE_LABELS: Record<TimerPhase, string> = {
+  idle: 'Ready',
+  focus: 'Time Remaining',
+  paused: 'Paused',
+  break: 'Break',
+};
+
+export const TimerDisplay: React.FC<TimerDisplayProps> = ({ timeRemaining, phase }) => {
   const minutes = Math.floor(timeRemaining / 60);
   const seconds = timeRemaining % 60;
 
@@ -14,7 +23,7 @@ export const TimerDisplay: React.FC<TimerDisplayProps> = ({ timeRemaining }) =>
         {String(minutes).padStart(2, '0')}:{String(seconds).padStart(2, '0')}
       </div>
       <div className="text-gray-600 text-lg">
-        {timeRemaining > 0 ? 'Time Remaining' : 'Time\'s Up!'}
+        {PHASE_LABELS[phase]}
       </div>
     </div>
   );
@@ -0,0 +1,32 @@
+import React, { useEffect, useState } from 'react';
+import { useActivityStore } from '../../stores/activityStore';
+import { useTaskStore } from '../../stores/taskStore';
+import { ActivityList } from '../common/ActivityList';
+import { Button } from '../common/Button';
+
+// The recent activity across all tasks, loaded once opened
+export const ActivityFeed: React.FC = () => {
+  const { recent, hasMore, error, fetchRecent, fetchOlder } = useActivityStore();
+  const tasks = useTaskStore((state) => state.tasks);
+  const [isOpen, setIsOpen] = useState(false);
+
+  // Any change reloads the task list
+  useEffect(() => {
+    if (isOpen) fetchRecent();
+  }, [isOpen, tasks, fetchRecent]);
+
+  return (
+    <details className="mt-6 text-sm" onToggle={(e) => setIsOpen(e.currentTarget.open)}>
+      <summary className="cursor-pointer text-gray-400">Recent activity</summary>
+      <div className="mt-2 space-y-2">
+        {error && <div className="text-red-400">{error}</div>}
+        <ActivityList entries={recent} showSubject />
+        {hasMore && (
+          <Button size="sm" variant="secondary" onClick={fetchOlder}>
+            Show older
+          </Button>
+        )}
+      </div>
+    </details>
+  );
+};
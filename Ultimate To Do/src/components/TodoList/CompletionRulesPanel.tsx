@@ -0,0 +1,37 @@
+import React, { useEffect } from 'react';
+import { useTaskStore } from '../../stores/taskStore';
+import { Checkbox } from '../common/Checkbox';
+import type { CompletionRules } from '../../types/task';
+
+const RULE_LABELS: Record<keyof CompletionRules, string> = {
+  complete_subtasks: 'Checking a task off checks off its subtasks',
+  reopen_subtasks: 'Unchecking a task unchecks its subtasks',
+  complete_parent: 'Checking off the last subtask checks off the parent',
+  reopen_parent: 'Unchecking a subtask unchecks the parent',
+};
+
+export const CompletionRulesPanel: React.FC = () => {
+  const { completionRules, fetchCompletionRules, updateCompletionRules } = useTaskStore();
+
+  useEffect(() => {
+    fetchCompletionRules();
+  }, [fetchCompletionRules]);
+
+  if (!completionRules) return null;
+
+  return (
+    <details className="mb-4 text-sm">
+      <summary className="cursor-pointer text-gray-400">Completion rules</summary>
+      <div className="mt-2 space-y-1">
+        {(Object.keys(RULE_LABELS) as (keyof CompletionRules)[]).map((rule) => (
+          <Checkbox
+            key={rule}
+            checked={completionRules[rule]}
+            onChange={(checked) => updateCompletionRules({ ...completionRules, [rule]: checked })}
+            label={RULE_LABELS[rule]}
+          />
+        ))}
+      </div>
+    </details>
+  );
+};
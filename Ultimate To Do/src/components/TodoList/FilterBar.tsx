@@ -0,0 +1,117 @@
+import React, { useEffect, useState } from 'react';
+import { Filter, X } from 'lucide-react';
+import { useViewStore } from '../../stores/viewStore';
+import { Button } from '../common/Button';
+
+const FILTER_HELP =
+  'due:<today  due:today+7  done:false  tag:urgent  project:Work  parent:none  ' +
+  'recurring:true  priority:>=medium  estimate:<=30  "words"  -term  a or b  (...)';
+
+// Filter expressions and saved views; a filter replaces the task tree with a
+// flat list of matches
+export const FilterBar: React.FC = () => {
+  const {
+    views,
+    filter,
+    activeViewId,
+    error,
+    fetchViews,
+    applyFilter,
+    openView,
+    clearFilter,
+    saveView,
+    updateView,
+    removeView,
+  } = useViewStore();
+  const [text, setText] = useState(filter);
+
+  useEffect(() => {
+    fetchViews();
+  }, [fetchViews]);
+
+  // Opening a view fills in its filter
+  useEffect(() => {
+    setText(filter);
+  }, [filter]);
+
+  const activeView = views.find((view) => view.id === activeViewId);
+  const isEdited = text.trim() !== filter.trim();
+
+  const handleSave = async () => {
+    if (activeView && isEdited) {
+      await updateView(activeView.id, text);
+      return;
+    }
+    const name = window.prompt('View name');
+    if (!name) return;
+    await applyFilter(text);
+    await saveView(name);
+  };
+
+  const handleClear = () => {
+    setText('');
+    clearFilter();
+  };
+
+  return (
+    <div className="mb-4 text-sm">
+      <div className="flex items-center space-x-2">
+        <label htmlFor="task-view" className="text-gray-400">View</label>
+        <select
+          id="task-view"
+          value={activeViewId ?? 0}
+          onChange={(e) => {
+            const id = Number(e.target.value);
+            id > 0 ? openView(id) : handleClear();
+          }}
+          className="px-2 py-1 border border-gray-300 rounded"
+        >
+          <option value={0}>{filter ? 'Custom filter' : 'All tasks'}</option>
+          {views.map((view) => (
+            <option key={view.id} value={view.id}>{view.name}</option>
+          ))}
+        </select>
+        {activeView && (
+          <Button
+            size="sm"
+            variant="secondary"
+            onClick={() => window.confirm(`Delete the view "${activeView.name}"?`) && removeView(activeView.id)}
+          >
+            Delete view
+          </Button>
+        )}
+      </div>
+
+      <div className="flex items-center space-x-2 mt-2">
+        <div className="flex flex-1 items-center px-2 py-1 border border-gray-300 rounded">
+          <Filter size={16} className="text-gray-400 mr-2" />
+          <input
+            type="text"
+            value={text}
+            onChange={(e) => setText(e.target.value)}
+            onKeyDown={(e) => {
+              if (e.key === 'Enter') applyFilter(text);
+              if (e.key === 'Escape') handleClear();
+            }}
+            placeholder="Filter, e.g. due:<today done:false tag:urgent"
+            title={FILTER_HELP}
+            className="flex-1 bg-transparent outline-none"
+          />
+          {(text || filter) && (
+            <button onClick={handleClear} className="text-gray-400 hover:text-gray-200" title="Clear filter">
+              <X size={16} />
+            </button>
+          )}
+        </div>
+        <Button size="sm" variant="secondary" onClick={() => applyFilter(text)} disabled={!isEdited}>
+          Apply
+        </Button>
+        <Button size="sm" variant="secondary" onClick={handleSave} disabled={!text.trim()}>
+          {activeView && isEdited ? 'Update view' : 'Save view'}
+        </Button>
+      </div>
+
+      {error && <div className="mt-1 text-red-400">{error}</div>}
+    </div>
+  );
+};
@@ -0,0 +1,79 @@
+import React, { useEffect, useState } from 'react';
+import { Tag as TagIcon } from 'lucide-react';
+import { useTagStore } from '../../stores/tagStore';
+import { TagChip } from '../common/TagChip';
+import type { Task } from '../../types/task';
+
+interface TagEditorProps {
+  task: Task;
+}
+
+export const TagEditor: React.FC<TagEditorProps> = ({ task }) => {
+  const { tags, error, fetchTags, addTag, tagTask, untagTask } = useTagStore();
+  const [newTagName, setNewTagName] = useState('');
+  const [newTagColor, setNewTagColor] = useState('#6b7280');
+
+  useEffect(() => {
+    fetchTags();
+  }, [fetchTags]);
+
+  const available = tags.filter((tag) => !task.tags.some((t) => t.id === tag.id));
+
+  const handleCreate = async (e: React.FormEvent) => {
+    e.preventDefault();
+    if (!newTagName.trim()) return;
+
+    const tag = await addTag(newTagName.trim(), newTagColor);
+    if (tag) {
+      await tagTask(task.id, tag.id);
+      setNewTagName('');
+    }
+  };
+
+  return (
+    <div className="space-y-2">
+      <label className="flex items-center text-sm font-medium text-gray-700">
+        <TagIcon size={16} className="mr-2" />
+        Tags
+      </label>
+
+      <div className="flex flex-wrap gap-1">
+        {task.tags.map((tag) => (
+          <TagChip key={tag.id} tag={tag} onRemove={() => untagTask(task.id, tag.id)} />
+        ))}
+      </div>
+
+      {available.length > 0 && (
+        <select
+          value=""
+          onChange={(e) => e.target.value && tagTask(task.id, Number(e.target.value))}
+          className="w-full px-3 py-2 border border-gray-300 rounded focus:outline-none focus:ring-2 focus:ring-blue-500"
+        >
+          <option value="">Add a tag...</option>
+          {available.map((tag) => (
+            <option key={tag.id} value={tag.id}>{tag.name}</option>
+          ))}
+        </select>
+      )}
+
+      <form onSubmit={handleCreate} className="flex space-x-2">
+        <input
+          type="text"
+          value={newTagName}
+          onChange={(e) => setNewTagName(e.target.value)}
+          placeholder="New tag"
+          className="flex-1 px-3 py-1 border border-gray-300 rounded focus:outline-none focus:ring-2 focus:ring-blue-500"
+        />
+        <input
+          type="color"
+          value={newTagColor}
+          onChange={(e) => setNewTagColor(e.target.value)}
+          className="w-10 h-8 border border-gray-300 rounded"
+          title="Tag color"
+        />
+      </form>
+
+      {error && <p className="text-xs text-red-600">{error}</p>}
+    </div>
+  );
+};
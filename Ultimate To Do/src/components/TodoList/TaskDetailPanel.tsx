@@ -1,8 +1,10 @@
 import React, { useState, useEffect } from 'react';
 import { X, Trash2, Calendar, RefreshCw } from 'lucide-react';
 import { Button } from '../common/Button';
-import type { Task } from '../../types/task';
+import { PRIORITY_LABELS, type Task, type ReparentAction } from '../../types/task';
 import { formatDate } from '../../utils/dateHelpers';
+import { TagEditor } from './TagEditor';
+import { TaskHistory } from './TaskHistory';
 
 interface TaskDetailPanelProps {
   task: Task | null;
@@ -10,6 +12,7 @@ interface TaskDetailPanelProps {
   onClose: () => void;
   onUpdate: (taskId: number, updates: Partial<Task>) => void;
   onDelete: (taskId: number) => void;
+  onReparent?: (taskId: number, action: ReparentAction) => void;
 }
 
 export const TaskDetailPanel: React.FC<TaskDetailPanelProps> = ({
@@ -18,6 +21,7 @@ export const TaskDetailPanel: React.FC<TaskDetailPanelProps> = ({
   onClose,
   onUpdate,
   onDelete,
+  onReparent,
 }) => {
   const [title, setTitle] = useState('');
   const [description, setDescription] = useState('');
@@ -49,7 +53,7 @@ export const TaskDetailPanel: React.FC<TaskDetailPanelProps> = ({
 
   const handleDelete = () => {
     if (!task) return;
-    if (confirm('Are you sure you want to delete this task?')) {
+    if (confirm('Move this task and its subtasks to the trash?')) {
       onDelete(task.id);
       onClose();
     }
@@ -131,6 +135,61 @@ export const TaskDetailPanel: React.FC<TaskDetailPanelProps> = ({
               )}
             </div>
 
+            {/* Priority, effort and difficulty */}
+            <div className="grid grid-cols-3 gap-2">
+              <div>
+                <label className="block text-sm font-medium text-gray-700 mb-2">
+                  Priority
+                </label>
+                <select
+                  value={task.priority}
+                  onChange={(e) => onUpdate(task.id, { priority: Number(e.target.value) })}
+                  className="w-full px-2 py-2 border border-gray-300 rounded focus:outline-none focus:ring-2 focus:ring-blue-500"
+                >
+                  {PRIORITY_LABELS.map((label, value) => (
+                    <option key={value} value={value}>{label}</option>
+                  ))}
+                </select>
+              </div>
+              <div>
+                <label className="block text-sm font-medium text-gray-700 mb-2">
+                  Effort (min)
+                </label>
+                <input
+                  type="number"
+                  min={1}
+                  defaultValue={task.estimated_minutes ?? ''}
+                  key={`effort-${task.id}`}
+                  onBlur={(e) => {
+                    const value = Number(e.target.value);
+                    if (value > 0) onUpdate(task.id, { estimated_minutes: value });
+                  }}
+                  className="w-full px-2 py-2 border border-gray-300 rounded focus:outline-none focus:ring-2 focus:ring-blue-500"
+                />
+              </div>
+              <div>
+                <label className="block text-sm font-medium text-gray-700 mb-2">
+                  Difficulty
+                </label>
+                <select
+                  value={task.difficulty ?? ''}
+                  onChange={(e) => {
+                    const value = Number(e.target.value);
+                    if (value > 0) onUpdate(task.id, { difficulty: value });
+                  }}
+                  className="w-full px-2 py-2 border border-gray-300 rounded focus:outline-none focus:ring-2 focus:ring-blue-500"
+                >
+                  <option value="">-</option>
+                  {[1, 2, 3, 4, 5].map((value) => (
+                    <option key={value} value={value}>{value}</option>
+                  ))}
+                </select>
+              </div>
+            </div>
+
+            {/* Tags */}
+            <TagEditor task={task} />
+
             {/* Recurring */}
             {!task.parent_id && (
               <div className="space-y-3">
@@ -187,7 +246,26 @@ export const TaskDetailPanel: React.FC<TaskDetailPanelProps> = ({
                   This is a subtask
                 </p>
               )}
+              {onReparent && (
+                <div className="flex space-x-2 mt-2">
+                  <Button size="sm" variant="secondary" onClick={() => onReparent(task.id, 'indent')}>
+                    Indent
+                  </Button>
+                  {task.parent_id && (
+                    <>
+                      <Button size="sm" variant="secondary" onClick={() => onReparent(task.id, 'outdent')}>
+                        Outdent
+                      </Button>
+                      <Button size="sm" variant="secondary" onClick={() => onReparent(task.id, 'promote')}>
+                        Make top-level
+                      </Button>
+                    </>
+                  )}
+                </div>
+              )}
             </div>
+
+            <TaskHistory task={task} />
           </div>
 
           {/* Footer */}
@@ -1,7 +1,7 @@
 import React, { useState } from 'react';
 import { Button } from '../common/Button';
 import { Checkbox } from '../common/Checkbox';
-import type { CreateTaskInput } from '../../types/task';
+import { PRIORITY_LABELS, type CreateTaskInput } from '../../types/task';
 
 interface TaskFormProps {
   parentId?: number;
@@ -15,6 +15,7 @@ export const TaskForm: React.FC<TaskFormProps> = ({ parentId, onSubmit, onCancel
   const [dueDate, setDueDate] = useState('');
   const [isRecurring, setIsRecurring] = useState(false);
   const [recurrencePattern, setRecurrencePattern] = useState('daily');
+  const [priority, setPriority] = useState(0);
 
   const handleSubmit = (e: React.FormEvent) => {
     e.preventDefault();
@@ -28,6 +29,7 @@ export const TaskForm: React.FC<TaskFormProps> = ({ parentId, onSubmit, onCancel
       is_recurring: isRecurring,
       recurrence_pattern: isRecurring ? recurrencePattern : undefined,
       parent_id: parentId,
+      priority,
     });
 
     // Reset form
@@ -36,6 +38,7 @@ export const TaskForm: React.FC<TaskFormProps> = ({ parentId, onSubmit, onCancel
     setDueDate('');
     setIsRecurring(false);
     setRecurrencePattern('daily');
+    setPriority(0);
   };
 
   return (
@@ -79,6 +82,21 @@ export const TaskForm: React.FC<TaskFormProps> = ({ parentId, onSubmit, onCancel
         />
       </div>
 
+      <div>
+        <label className="block text-sm font-medium text-gray-700 mb-1">
+          Priority
+        </label>
+        <select
+          value={priority}
+          onChange={(e) => setPriority(Number(e.target.value))}
+          className="w-full px-3 py-2 border border-gray-300 rounded focus:outline-none focus:ring-2 focus:ring-blue-500"
+        >
+          {PRIORITY_LABELS.map((label, value) => (
+            <option key={value} value={value}>{label}</option>
+          ))}
+        </select>
+      </div>
+
       {!parentId && (
         <>
           <Checkbox
@@ -0,0 +1,28 @@
+import React, { useEffect, useState } from 'react';
+import { useActivityStore } from '../../stores/activityStore';
+import { ActivityList } from '../common/ActivityList';
+import type { Task } from '../../types/task';
+
+interface TaskHistoryProps {
+  task: Task;
+}
+
+// Everything logged for one task, loaded once opened
+export const TaskHistory: React.FC<TaskHistoryProps> = ({ task }) => {
+  const { taskId, taskActivity, fetchTaskActivity } = useActivityStore();
+  const [isOpen, setIsOpen] = useState(false);
+
+  // `task` is a new object after every change to it
+  useEffect(() => {
+    if (isOpen) fetchTaskActivity(task.id);
+  }, [isOpen, task, fetchTaskActivity]);
+
+  return (
+    <details className="text-sm" onToggle={(e) => setIsOpen(e.currentTarget.open)}>
+      <summary className="cursor-pointer text-gray-700">History</summary>
+      <div className="mt-2">
+        <ActivityList entries={taskId === task.id ? taskActivity : []} />
+      </div>
+    </details>
+  );
+};
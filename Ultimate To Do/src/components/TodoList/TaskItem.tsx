@@ -3,8 +3,11 @@ import { Trash2, Plus, Calendar, ChevronRight, ChevronDown } from 'lucide-react'
 import { Checkbox, CircleCheck } from '../common/Checkbox';
 import { Button } from '../common/Button';
 import { InlineTaskForm } from './InlineTaskForm';
-import type { Task, CreateTaskInput } from '../../types/task';
+import { PRIORITY_LABELS, type Task, type CreateTaskInput } from '../../types/task';
 import { formatDate, isOverdue } from '../../utils/dateHelpers';
+import { TagChip } from '../common/TagChip';
+
+export type DropPlacement = 'before' | 'after' | 'inside';
 
 interface TaskItemProps {
   task: Task;
@@ -15,8 +18,19 @@ interface TaskItemProps {
   level?: number;
   collapsedIds: Set<number>;
   onToggleCollapse: (id: number) => void;
+  // Enables drag and drop; only set while the list is in manual order
+  onDropTask?: (draggedId: number, target: Task, placement: DropPlacement) => void;
 }
 
+// Top and bottom quarters drop next to the task, the middle drops into it
+const dropPlacement = (e: React.DragEvent<HTMLDivElement>): DropPlacement => {
+  const rect = e.currentTarget.getBoundingClientRect();
+  const offset = (e.clientY - rect.top) / rect.height;
+  if (offset < 0.25) return 'before';
+  if (offset > 0.75) return 'after';
+  return 'inside';
+};
+
 export const TaskItem: React.FC<TaskItemProps> = ({
   task,
   onToggle,
@@ -26,9 +40,11 @@ export const TaskItem: React.FC<TaskItemProps> = ({
   level = 0,
   collapsedIds,
   onToggleCollapse,
+  onDropTask,
 }) => {
   const isCollapsed = collapsedIds.has(task.id);
   const [showSubtaskForm, setShowSubtaskForm] = useState(false);
+  const [dropHint, setDropHint] = useState<DropPlacement | null>(null);
 
   const handleAddSubtask = (subtask: CreateTaskInput) => {
     onAddSubtask(subtask);
@@ -43,8 +59,34 @@ export const TaskItem: React.FC<TaskItemProps> = ({
     <div style={{ marginLeft: `${indent}px` }}>
       <div
         className={`group border rounded-lg p-6 mb-4 transition-all ${
-          overdue ? 'border-red-400' : ''} hover:shadow-md hover:bg-orange-900`
+          overdue ? 'border-red-400' : ''} hover:shadow-md hover:bg-orange-900 ${
+          dropHint === 'before' ? 'border-t-4 border-t-orange-500' : ''} ${
+          dropHint === 'after' ? 'border-b-4 border-b-orange-500' : ''} ${
+          dropHint === 'inside' ? 'bg-orange-900' : ''}`
         }
+        draggable={!!onDropTask}
+        onDragStart={(e) => {
+          e.stopPropagation();
+          e.dataTransfer.setData('text/plain', String(task.id));
+          e.dataTransfer.effectAllowed = 'move';
+        }}
+        onDragOver={(e) => {
+          if (!onDropTask) return;
+          e.preventDefault();
+          e.stopPropagation();
+          setDropHint(dropPlacement(e));
+        }}
+        onDragLeave={() => setDropHint(null)}
+        onDrop={(e) => {
+          if (!onDropTask) return;
+          e.preventDefault();
+          e.stopPropagation();
+          setDropHint(null);
+          const draggedId = Number(e.dataTransfer.getData('text/plain'));
+          if (draggedId && draggedId !== task.id) {
+            onDropTask(draggedId, task, dropPlacement(e));
+          }
+        }}
       >
       {/* <div
         className={`group border p-6 mb-4 transition-all ${
@@ -76,12 +118,40 @@ export const TaskItem: React.FC<TaskItemProps> = ({
                   Recurring
                 </span>
               )}
+              {task.priority > 0 && (
+                <span className="ml-2 text-xs bg-orange-100 text-orange-800 px-2 py-1 rounded">
+                  {PRIORITY_LABELS[task.priority]}
+                </span>
+              )}
             </h3>
 
             {task.description && (
               <p className="text-sm text-gray-600 mt-1">{task.description}</p>
             )}
 
+            {task.progress.total_leaves > 1 && (
+              <div className="flex items-center mt-2 space-x-2 text-xs text-gray-400">
+                <div className="w-24 h-1.5 bg-gray-700 rounded">
+                  <div
+                    className="h-1.5 bg-green-500 rounded"
+                    style={{ width: `${Math.round(task.progress.ratio * 100)}%` }}
+                  />
+                </div>
+                <span>
+                  {task.progress.completed_leaves}/{task.progress.total_leaves} ·{' '}
+                  {Math.round(task.progress.ratio * 100)}%
+                </span>
+              </div>
+            )}
+
+            {task.tags.length > 0 && (
+              <div className="flex flex-wrap gap-1 mt-2">
+                {task.tags.map((tag) => (
+                  <TagChip key={tag.id} tag={tag} />
+                ))}
+              </div>
+            )}
+
             {task.due_date && (
               <div className="flex items-center mt-2 text-sm">
                 <Calendar size={14} className="mr-1" />
@@ -144,6 +214,7 @@ export const TaskItem: React.FC<TaskItemProps> = ({
               level={level}
               collapsedIds={collapsedIds}
               onToggleCollapse={onToggleCollapse}
+              onDropTask={onDropTask}
             />
           ))}
         </div>
@@ -0,0 +1,91 @@
+import React, { useEffect, useState } from 'react';
+import { Search, X } from 'lucide-react';
+import { useTaskStore } from '../../stores/taskStore';
+import type { TaskSearchResult, TextPart } from '../../types/task';
+
+// Wait for a pause in typing before searching
+const SEARCH_DELAY_MS = 200;
+
+interface TaskSearchProps {
+  onSelect: (result: TaskSearchResult) => void;
+}
+
+const Highlighted: React.FC<{ parts: TextPart[] }> = ({ parts }) => (
+  <>
+    {parts.map((part, i) =>
+      part.is_match ? (
+        <mark key={i} className="bg-orange-500 text-black rounded-sm">{part.text}</mark>
+      ) : (
+        <React.Fragment key={i}>{part.text}</React.Fragment>
+      )
+    )}
+  </>
+);
+
+export const TaskSearch: React.FC<TaskSearchProps> = ({ onSelect }) => {
+  const { query, searchText, searchResults, search } = useTaskStore();
+  const [text, setText] = useState(searchText);
+
+  // Also searches again when the project or filters change
+  useEffect(() => {
+    const timer = setTimeout(() => search(text), SEARCH_DELAY_MS);
+    return () => clearTimeout(timer);
+  }, [text, query, search]);
+
+  const handleSelect = (result: TaskSearchResult) => {
+    setText('');
+    search('');
+    onSelect(result);
+  };
+
+  return (
+    <div className="mb-4">
+      <div className="flex items-center px-2 py-1 border border-gray-300 rounded">
+        <Search size={16} className="text-gray-400 mr-2" />
+        <input
+          type="search"
+          value={text}
+          onChange={(e) => setText(e.target.value)}
+          onKeyDown={(e) => e.key === 'Escape' && setText('')}
+          placeholder='Search tasks (use "quotes" for exact phrases)'
+          className="flex-1 bg-transparent outline-none text-sm"
+        />
+        {text && (
+          <button onClick={() => setText('')} className="text-gray-400 hover:text-gray-200" title="Clear search">
+            <X size={16} />
+          </button>
+        )}
+      </div>
+
+      {text.trim() && searchText === text && (
+        <div className="mt-2 space-y-1 text-sm">
+          {searchResults.length === 0 ? (
+            <div className="text-gray-500">No matching tasks</div>
+          ) : (
+            searchResults.map((result) => (
+              <button
+                key={result.task.id}
+                onClick={() => handleSelect(result)}
+                className="block w-full text-left px-2 py-1 rounded hover:bg-gray-800"
+              >
+                {result.path.length > 0 && (
+                  <div className="text-xs text-gray-500">
+                    {result.path.map((entry) => entry.title).join(' › ')}
+                  </div>
+                )}
+                <div className={result.task.is_completed ? 'line-through text-gray-500' : 'text-gray-200'}>
+                  <Highlighted parts={result.title} />
+                </div>
+                {result.description.length > 0 && (
+                  <div className="text-xs text-gray-400">
+                    <Highlighted parts={result.description} />
+                  </div>
+                )}
+              </button>
+            ))
+          )}
+        </div>
+      )}
+    </div>
+  );
+};
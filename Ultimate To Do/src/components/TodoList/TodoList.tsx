@@ -1,11 +1,20 @@
 import React, { useEffect, useState, useMemo } from 'react';
 import { Plus, ChevronRight, ChevronDown } from 'lucide-react';
 import { useTaskStore } from '../../stores/taskStore';
-import { TaskItem } from './TaskItem';
+import { useTagStore } from '../../stores/tagStore';
+import { useProjectStore } from '../../stores/projectStore';
+import { useViewStore } from '../../stores/viewStore';
+import { TaskItem, type DropPlacement } from './TaskItem';
 import { InlineTaskForm } from './InlineTaskForm';
 import { TaskDetailPanel } from './TaskDetailPanel';
+import { CompletionRulesPanel } from './CompletionRulesPanel';
+import { TaskSearch } from './TaskSearch';
+import { FilterBar } from './FilterBar';
+import { TrashPanel } from './TrashPanel';
+import { ActivityFeed } from './ActivityFeed';
 import { Button } from '../common/Button';
-import type { CreateTaskInput, Task } from '../../types/task';
+import { ProjectSelect } from '../common/ProjectSelect';
+import type { CreateTaskInput, Task, TaskSearchResult, TaskSort } from '../../types/task';
 
 function CollapsibleSection({ title, count, children, open }: { title: string, count: number, children: React.ReactNode, open: boolean }) {
   const [isOpen, setIsOpen] = useState(open);
@@ -19,7 +28,10 @@ function CollapsibleSection({ title, count, children, open }: { title: string, c
 }
 
 export const TodoList: React.FC = () => {
-  const { tasks, isLoading, error, fetchTasks, addTask, removeTask, toggleTask, editTask } = useTaskStore();
+  const { tasks, query, isLoading, error, fetchTasks, setQuery, addTask, removeTask, toggleTask, editTask, moveTask, reparentTask } = useTaskStore();
+  const { tags, fetchTags } = useTagStore();
+  const { projects, fetchProjects, addProject, archiveProject } = useProjectStore();
+  const { filter, filteredTasks, refresh: refreshFilter } = useViewStore();
   const [showInlineForm, setShowInlineForm] = useState(false);
   const [selectedTaskId, setSelectedTaskId] = useState<number | null>(null);
 
@@ -37,7 +49,14 @@ export const TodoList: React.FC = () => {
 
   useEffect(() => {
     fetchTasks();
-  }, [fetchTasks]);
+    fetchTags();
+    fetchProjects();
+  }, [fetchTasks, fetchTags, fetchProjects]);
+
+  // Keep the filtered list in step with changes to the tasks
+  useEffect(() => {
+    refreshFilter();
+  }, [tasks, refreshFilter]);
 
   const toggleCollapse = (taskId: number) => {
     setCollapsedIds(prev => {
@@ -75,6 +94,41 @@ export const TodoList: React.FC = () => {
   }, [tasks]);
   
 
+  const activeProject = projects.find((p) => p.id === query.project_id) ?? null;
+
+  const handleNewProject = async () => {
+    const name = window.prompt('Project name');
+    if (!name) return;
+    const project = await addProject(name);
+    if (project) {
+      await setQuery({ ...query, project_id: project.id });
+    }
+  };
+
+  const sortBy = query.sort_by ?? activeProject?.sort_by ?? 'manual';
+
+  // The tasks sharing a parent, as currently shown
+  const findSiblings = (parentId: number | undefined): Task[] => {
+    if (parentId === undefined) return tasks;
+    return findTaskById(tasks, parentId)?.subtasks ?? [];
+  };
+
+  const handleDropTask = async (draggedId: number, target: Task, placement: DropPlacement) => {
+    if (placement === 'inside') {
+      const children = target.subtasks.filter((t) => t.id !== draggedId);
+      await moveTask(draggedId, target.id, children[children.length - 1]?.id ?? null);
+      return;
+    }
+    const parentId = target.parent_id ?? null;
+    if (placement === 'after') {
+      await moveTask(draggedId, parentId, target.id);
+      return;
+    }
+    const siblings = findSiblings(target.parent_id).filter((t) => t.id !== draggedId);
+    const index = siblings.findIndex((t) => t.id === target.id);
+    await moveTask(draggedId, parentId, index > 0 ? siblings[index - 1].id : null);
+  };
+
   const handleAddTask = async (task: CreateTaskInput) => {
     await addTask(task);
     setShowInlineForm(false);
@@ -90,6 +144,16 @@ export const TodoList: React.FC = () => {
     setSelectedTaskId(taskId);
   };
 
+  // Opens the task, expanding its ancestors so it shows in the list
+  const handleSearchSelect = (result: TaskSearchResult) => {
+    setCollapsedIds(prev => {
+      const next = new Set(prev);
+      result.path.forEach((entry) => next.delete(entry.id));
+      return next;
+    });
+    setSelectedTaskId(result.task.id);
+  };
+
   const handleUpdateTask = async (taskId: number, updates: Partial<Task>) => {
     await editTask({
       id: taskId,
@@ -98,6 +162,9 @@ export const TodoList: React.FC = () => {
       due_date: updates.due_date,
       is_recurring: updates.is_recurring,
       recurrence_pattern: updates.recurrence_pattern,
+      priority: updates.priority,
+      estimated_minutes: updates.estimated_minutes,
+      difficulty: updates.difficulty,
     });
   };
 
@@ -126,6 +193,89 @@ export const TodoList: React.FC = () => {
                 </div>
               )}
 
+              <div className="flex items-center space-x-2 mb-2 text-sm">
+                <label htmlFor="task-project" className="text-gray-400">Project</label>
+                <ProjectSelect
+                  id="task-project"
+                  projects={projects}
+                  value={query.project_id ?? null}
+                  // Each project keeps its own sort order
+                  onChange={(projectId) =>
+                    setQuery({ ...query, project_id: projectId ?? undefined, sort_by: undefined })
+                  }
+                />
+                <Button size="sm" variant="secondary" onClick={handleNewProject}>
+                  New project
+                </Button>
+                {activeProject && (
+                  <Button
+                    size="sm"
+                    variant="secondary"
+                    onClick={() => archiveProject(activeProject.id, !activeProject.is_archived)}
+                  >
+                    {activeProject.is_archived ? 'Unarchive' : 'Archive'}
+                  </Button>
+                )}
+              </div>
+
+              <div className="flex items-center space-x-2 mb-4 text-sm">
+                <label htmlFor="task-sort" className="text-gray-400">Sort by</label>
+                <select
+                  id="task-sort"
+                  value={sortBy}
+                  onChange={(e) => setQuery({ ...query, sort_by: e.target.value as TaskSort })}
+                  className="px-2 py-1 border border-gray-300 rounded"
+                >
+                  <option value="manual">Manual</option>
+                  <option value="created_at">Newest</option>
+                  <option value="due_date">Due date</option>
+                  <option value="priority">Priority</option>
+                  <option value="difficulty">Difficulty</option>
+                  <option value="effort">Effort</option>
+                  <option value="title">Title</option>
+                </select>
+                <label htmlFor="task-min-priority" className="text-gray-400">Priority</label>
+                <select
+                  id="task-min-priority"
+                  value={query.min_priority ?? 0}
+                  onChange={(e) => {
+                    const value = Number(e.target.value);
+                    setQuery({ ...query, min_priority: value > 0 ? value : undefined });
+                  }}
+                  className="px-2 py-1 border border-gray-300 rounded"
+                >
+                  <option value={0}>Any</option>
+                  <option value={1}>Low and up</option>
+                  <option value={2}>Medium and up</option>
+                  <option value={3}>High</option>
+                </select>
+                {tags.length > 0 && (
+                  <>
+                    <label htmlFor="task-tag" className="text-gray-400">Tag</label>
+                    <select
+                      id="task-tag"
+                      value={query.tag_ids?.[0] ?? 0}
+                      onChange={(e) => {
+                        const value = Number(e.target.value);
+                        setQuery({ ...query, tag_ids: value > 0 ? [value] : undefined });
+                      }}
+                      className="px-2 py-1 border border-gray-300 rounded"
+                    >
+                      <option value={0}>Any</option>
+                      {tags.map((tag) => (
+                        <option key={tag.id} value={tag.id}>{tag.name}</option>
+                      ))}
+                    </select>
+                  </>
+                )}
+              </div>
+
+              <TaskSearch onSelect={handleSearchSelect} />
+
+              <FilterBar />
+
+              <CompletionRulesPanel />
+
               {showInlineForm && (
                 <div className="mb-4">
                   <InlineTaskForm
@@ -135,7 +285,26 @@ export const TodoList: React.FC = () => {
                 </div>
               )}
 
-              {isLoading && tasks.length === 0 ? (
+              {filter ? (
+                <div className="space-y-2">
+                  {filteredTasks.length === 0 ? (
+                    <div className="text-center py-12 text-gray-500">No tasks match this filter</div>
+                  ) : (
+                    filteredTasks.map((task) => (
+                      <TaskItem
+                        key={task.id}
+                        task={task}
+                        onToggle={toggleTask}
+                        onDelete={removeTask}
+                        onAddSubtask={addTask}
+                        onTaskClick={handleTaskClick}
+                        collapsedIds={collapsedIds}
+                        onToggleCollapse={toggleCollapse}
+                      />
+                    ))
+                  )}
+                </div>
+              ) : isLoading && tasks.length === 0 ? (
                 <div className="text-center py-12 text-gray-500">Loading tasks...</div>
               ) : tasks.length === 0 && !showInlineForm ? (
                 <div className="text-center py-12 text-gray-500">
@@ -155,6 +324,7 @@ export const TodoList: React.FC = () => {
                         onTaskClick={handleTaskClick}
                         collapsedIds={collapsedIds}
                         onToggleCollapse={toggleCollapse}
+                        onDropTask={sortBy === 'manual' ? handleDropTask : undefined}
                       />
                     ))}
                   </CollapsibleSection>
@@ -169,11 +339,15 @@ export const TodoList: React.FC = () => {
                         onTaskClick={handleTaskClick}
                         collapsedIds={collapsedIds}
                         onToggleCollapse={toggleCollapse}
+                        onDropTask={sortBy === 'manual' ? handleDropTask : undefined}
                       />
                     ))}
                   </CollapsibleSection>
                 </div>
               )}
+
+              <TrashPanel />
+              <ActivityFeed />
             </div>
           </div>
           <div className="col-span-1">
@@ -183,6 +357,7 @@ export const TodoList: React.FC = () => {
               onClose={() => setSelectedTaskId(null)}
               onUpdate={handleUpdateTask}
               onDelete={handleDeleteTask}
+              onReparent={reparentTask}
             />
           </div>
         </div>
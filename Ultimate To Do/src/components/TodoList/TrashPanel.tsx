@@ -0,0 +1,81 @@
+import React, { useEffect } from 'react';
+import { RotateCcw, Trash2 } from 'lucide-react';
+import { useTrashStore } from '../../stores/trashStore';
+import { Button } from '../common/Button';
+
+const RETENTION_OPTIONS = [7, 30, 90, 365, 0];
+
+// deleted_at is UTC without a zone marker
+const formatDeletedAt = (deletedAt: string): string =>
+  new Date(deletedAt.replace(' ', 'T') + 'Z').toLocaleString();
+
+export const TrashPanel: React.FC = () => {
+  const { trash, retentionDays, error, fetchTrash, restore, purge, empty, fetchRetention, updateRetention } =
+    useTrashStore();
+
+  useEffect(() => {
+    fetchTrash();
+    fetchRetention();
+  }, [fetchTrash, fetchRetention]);
+
+  return (
+    <details className="mt-6 text-sm">
+      <summary className="cursor-pointer text-gray-400">Trash ({trash.length})</summary>
+      <div className="mt-2 space-y-2">
+        <div className="flex items-center space-x-2">
+          <label htmlFor="trash-retention" className="text-gray-400">Delete for good after</label>
+          <select
+            id="trash-retention"
+            value={retentionDays ?? 30}
+            onChange={(e) => updateRetention(Number(e.target.value))}
+            className="px-2 py-1 border border-gray-300 rounded"
+          >
+            {RETENTION_OPTIONS.map((days) => (
+              <option key={days} value={days}>{days === 0 ? 'Never' : `${days} days`}</option>
+            ))}
+          </select>
+          {trash.length > 0 && (
+            <Button
+              size="sm"
+              variant="danger"
+              onClick={() => window.confirm('Delete everything in the trash for good?') && empty()}
+            >
+              Empty trash
+            </Button>
+          )}
+        </div>
+
+        {error && <div className="text-red-400">{error}</div>}
+
+        {trash.length === 0 ? (
+          <div className="text-gray-500">The trash is empty</div>
+        ) : (
+          trash.map(({ task, deleted_at, subtask_count, restores_under }) => (
+            <div key={task.id} className="flex items-center justify-between p-2 border border-gray-700 rounded">
+              <div>
+                <div className="text-gray-200">{task.title}</div>
+                <div className="text-xs text-gray-500">
+                  Deleted {formatDeletedAt(deleted_at)}
+                  {subtask_count > 0 && ` with ${subtask_count} subtask${subtask_count === 1 ? '' : 's'}`}
+                  {restores_under && ` from "${restores_under}"`}
+                </div>
+              </div>
+              <div className="flex space-x-2">
+                <button onClick={() => restore(task.id)} className="text-gray-400 hover:text-green-500" title="Restore">
+                  <RotateCcw size={16} />
+                </button>
+                <button
+                  onClick={() => window.confirm(`Delete "${task.title}" for good?`) && purge(task.id)}
+                  className="text-gray-400 hover:text-red-500"
+                  title="Delete for good"
+                >
+                  <Trash2 size={16} />
+                </button>
+              </div>
+            </div>
+          ))
+        )}
+      </div>
+    </details>
+  );
+};
@@ -0,0 +1,80 @@
+import React from 'react';
+import { PRIORITY_LABELS, type ActivityEntry } from '../../types/task';
+
+// occurred_at is UTC without a zone marker
+const formatOccurredAt = (occurredAt: string): string =>
+  new Date(occurredAt.replace(' ', 'T') + 'Z').toLocaleString();
+
+const priority = (value?: string) => PRIORITY_LABELS[Number(value ?? 0)] ?? value;
+const orNone = (value?: string) => value ?? 'none';
+
+// One line of text per event type; see src-tauri/src/activity.rs
+export const describeActivity = ({ event_type, subject, old_value, new_value }: ActivityEntry): string => {
+  switch (event_type) {
+    case 'created': return 'Created';
+    case 'retitled': return `Renamed from "${old_value}"`;
+    case 'description_changed': return 'Changed the description';
+    case 'rescheduled': return new_value ? `Due ${new_value}${old_value ? ` (was ${old_value})` : ''}` : 'Due date removed';
+    case 'priority_changed': return `Priority ${priority(old_value)} → ${priority(new_value)}`;
+    case 'estimate_changed': return `Estimate ${orNone(old_value)} → ${orNone(new_value)} min`;
+    case 'difficulty_changed': return `Difficulty ${orNone(old_value)} → ${orNone(new_value)}`;
+    case 'recurrence_changed': return new_value ? `Repeats ${new_value}` : 'No longer repeats';
+    case 'moved': return new_value ? `Moved under "${new_value}"` : 'Made a top-level task';
+    case 'project_changed': return new_value ? `Moved to project ${new_value}` : 'Taken out of its project';
+    case 'trashed': return 'Moved to the trash';
+    case 'restored': return 'Restored from the trash';
+    case 'purged': return 'Deleted for good';
+    case 'assigned': return `Assigned to ${new_value}`;
+    case 'unassigned': return `Unassigned from ${old_value}`;
+    case 'reassigned': return `Moved from ${old_value} to ${new_value}`;
+    case 'completed': return `Done for ${new_value}`;
+    case 'uncompleted': return `No longer done for ${old_value}`;
+    case 'tagged': return `Tagged ${new_value}`;
+    case 'untagged': return `Untagged ${old_value}`;
+    case 'focus_started': return `Focus session started (${new_value} min)`;
+    case 'focus_aborted': return `Focus session stopped early (${new_value} min)`;
+    case 'focus_completed': return `Focus session completed (${new_value} min)`;
+    case 'project_created': return `Project ${subject} created`;
+    case 'project_deleted': return `Project ${subject} deleted`;
+    case 'project_renamed': return `Project ${old_value} renamed to ${new_value}`;
+    case 'project_archived': return `Project ${subject} archived`;
+    case 'project_unarchived': return `Project ${subject} unarchived`;
+    case 'project_edited': return `Project ${subject} settings changed`;
+    case 'tag_created': return `Tag ${subject} created`;
+    case 'tag_deleted': return `Tag ${subject} deleted`;
+    case 'tag_renamed': return `Tag ${old_value} renamed to ${new_value}`;
+    case 'tag_recolored': return `Tag ${subject} recolored`;
+    case 'view_saved': return `View ${subject} saved`;
+    case 'view_deleted': return `View ${subject} deleted`;
+    case 'view_renamed': return `View ${old_value} renamed to ${new_value}`;
+    case 'view_edited': return `View ${subject} filter changed to ${new_value}`;
+    case 'setting_changed': return `Setting ${subject} changed to ${new_value ?? 'the default'}`;
+    default: return event_type;
+  }
+};
+
+interface ActivityListProps {
+  entries: ActivityEntry[];
+  // Show which task each entry is about; off for a single task's history
+  showSubject?: boolean;
+}
+
+export const ActivityList: React.FC<ActivityListProps> = ({ entries, showSubject = false }) => {
+  if (entries.length === 0) {
+    return <div className="text-gray-500">No activity yet</div>;
+  }
+
+  return (
+    <ul className="space-y-1">
+      {entries.map((entry) => (
+        <li key={entry.id} title={entry.command}>
+          {showSubject && entry.task_id !== undefined && entry.task_id !== null && (
+            <span className="text-gray-200">{entry.subject}: </span>
+          )}
+          <span className="text-gray-400">{describeActivity(entry)}</span>
+          <span className="ml-2 text-xs text-gray-500">{formatOccurredAt(entry.occurred_at)}</span>
+        </li>
+      ))}
+    </ul>
+  );
+};
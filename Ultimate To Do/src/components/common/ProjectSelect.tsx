@@ -0,0 +1,42 @@
+import React from 'react';
+import type { Project } from '../../types/task';
+
+interface ProjectSelectProps {
+  id?: string;
+  projects: Project[];
+  value: number | null;
+  onChange: (projectId: number | null) => void;
+  // Label for the "no project selected" option
+  emptyLabel?: string;
+}
+
+// Archived projects are only listed while one of them is selected
+export const ProjectSelect: React.FC<ProjectSelectProps> = ({
+  id,
+  projects,
+  value,
+  onChange,
+  emptyLabel = 'All projects',
+}) => {
+  return (
+    <select
+      id={id}
+      value={value ?? 0}
+      onChange={(e) => {
+        const projectId = Number(e.target.value);
+        onChange(projectId > 0 ? projectId : null);
+      }}
+      className="px-2 py-1 border border-gray-300 rounded"
+    >
+      <option value={0}>{emptyLabel}</option>
+      {projects
+        .filter((project) => !project.is_archived || project.id === value)
+        .map((project) => (
+          <option key={project.id} value={project.id}>
+            {project.name}
+            {project.is_archived ? ' (archived)' : ''}
+          </option>
+        ))}
+    </select>
+  );
+};
@@ -0,0 +1,24 @@
+import React from 'react';
+import { X } from 'lucide-react';
+import type { Tag } from '../../types/task';
+
+interface TagChipProps {
+  tag: Pick<Tag, 'name' | 'color'>;
+  onRemove?: () => void;
+}
+
+export const TagChip: React.FC<TagChipProps> = ({ tag, onRemove }) => {
+  return (
+    <span
+      className="inline-flex items-center text-xs text-white px-2 py-1 rounded"
+      style={{ backgroundColor: tag.color }}
+    >
+      {tag.name}
+      {onRemove && (
+        <button onClick={onRemove} className="ml-1 hover:opacity-75" title="Remove tag">
+          <X size={12} />
+        </button>
+      )}
+    </span>
+  );
+};
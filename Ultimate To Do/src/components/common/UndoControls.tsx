@@ -0,0 +1,72 @@
+import React, { useEffect } from 'react';
+import { Redo2, Undo2 } from 'lucide-react';
+import { useUndoStore } from '../../stores/undoStore';
+import { useTaskStore } from '../../stores/taskStore';
+import { useTrackerStore } from '../../stores/trackerStore';
+import { useTrashStore } from '../../stores/trashStore';
+import { useProjectStore } from '../../stores/projectStore';
+import { useTagStore } from '../../stores/tagStore';
+import { useViewStore } from '../../stores/viewStore';
+
+// Text fields keep their own undo
+const isEditingText = (target: EventTarget | null) =>
+  target instanceof HTMLElement &&
+  (target.isContentEditable || ['INPUT', 'TEXTAREA', 'SELECT'].includes(target.tagName));
+
+// Undo and redo buttons, also on Ctrl+Z and Ctrl+Shift+Z (or Ctrl+Y)
+export const UndoControls: React.FC = () => {
+  const { undoLabel, redoLabel, error, fetchState, undo, redo } = useUndoStore();
+
+  // Every change reloads one of these lists, which is when the next undo
+  // step changes too
+  useEffect(() => {
+    const unsubscribes = [
+      useTaskStore.subscribe((state, prev) => state.tasks !== prev.tasks && fetchState()),
+      useTrackerStore.subscribe((state, prev) => state.days !== prev.days && fetchState()),
+      useTrashStore.subscribe((state, prev) => state.trash !== prev.trash && fetchState()),
+      useProjectStore.subscribe((state, prev) => state.projects !== prev.projects && fetchState()),
+      useTagStore.subscribe((state, prev) => state.tags !== prev.tags && fetchState()),
+      useViewStore.subscribe((state, prev) => state.views !== prev.views && fetchState()),
+    ];
+    fetchState();
+    return () => unsubscribes.forEach((unsubscribe) => unsubscribe());
+  }, [fetchState]);
+
+  useEffect(() => {
+    const handleKeyDown = (e: KeyboardEvent) => {
+      if (!(e.ctrlKey || e.metaKey) || isEditingText(e.target)) return;
+      const key = e.key.toLowerCase();
+      if (key === 'z' && !e.shiftKey) {
+        e.preventDefault();
+        undo();
+      } else if ((key === 'z' && e.shiftKey) || key === 'y') {
+        e.preventDefault();
+        redo();
+      }
+    };
+    window.addEventListener('keydown', handleKeyDown);
+    return () => window.removeEventListener('keydown', handleKeyDown);
+  }, [undo, redo]);
+
+  return (
+    <div className="flex items-center space-x-1">
+      <button
+        onClick={undo}
+        disabled={!undoLabel}
+        className="p-2 rounded-lg text-gray-700 hover:bg-gray-100 disabled:opacity-40 disabled:cursor-not-allowed"
+        title={undoLabel ? `Undo: ${undoLabel} (Ctrl+Z)` : 'Nothing to undo'}
+      >
+        <Undo2 size={20} />
+      </button>
+      <button
+        onClick={redo}
+        disabled={!redoLabel}
+        className="p-2 rounded-lg text-gray-700 hover:bg-gray-100 disabled:opacity-40 disabled:cursor-not-allowed"
+        title={redoLabel ? `Redo: ${redoLabel} (Ctrl+Shift+Z)` : 'Nothing to redo'}
+      >
+        <Redo2 size={20} />
+      </button>
+      {error && <span className="text-sm text-red-600">{error}</span>}
+    </div>
+  );
+};
@@ -0,0 +1,62 @@
+import { create } from 'zustand';
+import type { ActivityEntry } from '../types/task';
+import { getTaskActivity, getRecentActivity, errorMessage } from '../utils/tauri';
+
+const PAGE_SIZE = 50;
+
+interface ActivityStore {
+  // The recent activity feed, newest first
+  recent: ActivityEntry[];
+  // Whether older entries than those in `recent` exist
+  hasMore: boolean;
+  // History of the task whose details are open
+  taskId: number | null;
+  taskActivity: ActivityEntry[];
+  error: string | null;
+  fetchRecent: () => Promise<void>;
+  fetchOlder: () => Promise<void>;
+  fetchTaskActivity: (taskId: number) => Promise<void>;
+}
+
+export const useActivityStore = create<ActivityStore>((set, get) => ({
+  recent: [],
+  hasMore: false,
+  taskId: null,
+  taskActivity: [],
+  error: null,
+
+  fetchRecent: async () => {
+    try {
+      const recent = await getRecentActivity(PAGE_SIZE);
+      set({ recent, hasMore: recent.length === PAGE_SIZE, error: null });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  fetchOlder: async () => {
+    const { recent } = get();
+    if (recent.length === 0) return;
+    try {
+      const older = await getRecentActivity(PAGE_SIZE, recent[recent.length - 1].id);
+      set({ recent: [...recent, ...older], hasMore: older.length === PAGE_SIZE, error: null });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  fetchTaskActivity: async (taskId: number) => {
+    if (get().taskId !== taskId) {
+      set({ taskId, taskActivity: [] });
+    }
+    try {
+      const taskActivity = await getTaskActivity(taskId);
+      // Another task may have been opened in the meantime
+      if (get().taskId === taskId) {
+        set({ taskActivity, error: null });
+      }
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+}));
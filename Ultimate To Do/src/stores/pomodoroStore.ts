@@ -1,83 +1,116 @@
 import { create } from 'zustand';
-import type { PomodoroSession, UserStats } from '../types/pomodoro';
+import type { PomodoroSession, UserStats, TimerSnapshot, TagPomodoroStats } from '../types/pomodoro';
 import {
   startPomodoroSession,
-  completePomodoroSession,
+  pausePomodoroSession,
+  resumePomodoroSession,
+  abortPomodoroSession,
+  getPomodoroTimer,
   getPomodoroStats,
   getSessionHistory,
+  getPomodoroTagStats,
+  onPomodoroTick,
+  onPomodoroPhaseChanged,
+  onPomodoroSessionCompleted,
+  onPomodoroError,
+  errorMessage,
 } from '../utils/tauri';
 
+const IDLE_TIMER: TimerSnapshot = {
+  phase: 'idle',
+  duration_seconds: 0,
+  remaining_seconds: 0,
+};
+
 interface PomodoroStore {
-  currentSession: PomodoroSession | null;
-  sessionId: number | null;
-  timeRemaining: number;
-  isRunning: boolean;
+  // The countdown runs in the backend; this mirrors its latest snapshot
+  timer: TimerSnapshot;
   stats: UserStats | null;
   history: PomodoroSession[];
+  tagStats: TagPomodoroStats[];
   isLoading: boolean;
   error: string | null;
-  startSession: (taskId: number | null, durationMinutes: number) => Promise<void>;
-  completeSession: () => Promise<void>;
-  pauseSession: () => void;
-  resumeSession: () => void;
-  setTimeRemaining: (time: number) => void;
+  subscribe: (onSessionCompleted?: () => void) => Promise<() => void>;
+  fetchTimer: () => Promise<void>;
+  startSession: (taskId: number | null, durationMinutes: number, breakMinutes?: number) => Promise<void>;
+  pauseSession: () => Promise<void>;
+  resumeSession: () => Promise<void>;
+  abortSession: () => Promise<void>;
   fetchStats: () => Promise<void>;
-  fetchHistory: (days?: number) => Promise<void>;
+  fetchHistory: (days?: number, tagId?: number, projectId?: number) => Promise<void>;
+  fetchTagStats: (days?: number, projectId?: number) => Promise<void>;
 }
 
 export const usePomodoroStore = create<PomodoroStore>((set, get) => ({
-  currentSession: null,
-  sessionId: null,
-  timeRemaining: 0,
-  isRunning: false,
+  timer: IDLE_TIMER,
   stats: null,
   history: [],
+  tagStats: [],
   isLoading: false,
   error: null,
 
-  startSession: async (taskId: number | null, durationMinutes: number) => {
+  subscribe: async (onSessionCompleted?: () => void) => {
+    const unlisteners = await Promise.all([
+      onPomodoroTick((timer) => set({ timer })),
+      onPomodoroPhaseChanged((timer) => set({ timer })),
+      onPomodoroSessionCompleted(async () => {
+        onSessionCompleted?.();
+        await get().fetchStats();
+        await get().fetchHistory();
+        await get().fetchTagStats();
+      }),
+      onPomodoroError((error) => set({ error: error.message })),
+    ]);
+    // Pick up a session recovered after a restart
+    await get().fetchTimer();
+    return () => unlisteners.forEach((unlisten) => unlisten());
+  },
+
+  fetchTimer: async () => {
     try {
-      const sessionId = await startPomodoroSession(taskId, durationMinutes);
-      set({
-        sessionId,
-        timeRemaining: durationMinutes * 60,
-        isRunning: true,
-        error: null,
-      });
+      const timer = await getPomodoroTimer();
+      set({ timer });
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
     }
   },
 
-  completeSession: async () => {
-    const { sessionId } = get();
-    if (!sessionId) return;
-
+  startSession: async (taskId: number | null, durationMinutes: number, breakMinutes?: number) => {
     try {
-      await completePomodoroSession(sessionId);
-      set({
-        currentSession: null,
-        sessionId: null,
-        timeRemaining: 0,
-        isRunning: false,
-      });
-      await get().fetchStats();
+      const timer = await startPomodoroSession(taskId, durationMinutes, breakMinutes);
+      set({ timer, error: null });
       await get().fetchHistory();
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
     }
   },
 
-  pauseSession: () => {
-    set({ isRunning: false });
+  pauseSession: async () => {
+    try {
+      const timer = await pausePomodoroSession();
+      set({ timer, error: null });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
   },
 
-  resumeSession: () => {
-    set({ isRunning: true });
+  resumeSession: async () => {
+    try {
+      const timer = await resumePomodoroSession();
+      set({ timer, error: null });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
   },
 
-  setTimeRemaining: (time: number) => {
-    set({ timeRemaining: time });
+  abortSession: async () => {
+    try {
+      const timer = await abortPomodoroSession();
+      set({ timer, error: null });
+      await get().fetchHistory();
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
   },
 
   fetchStats: async () => {
@@ -86,17 +119,26 @@ export const usePomodoroStore = create<PomodoroStore>((set, get) => ({
       const stats = await getPomodoroStats();
       set({ stats, isLoading: false });
     } catch (error) {
-      set({ error: String(error), isLoading: false });
+      set({ error: errorMessage(error), isLoading: false });
     }
   },
 
-  fetchHistory: async (days?: number) => {
+  fetchHistory: async (days?: number, tagId?: number, projectId?: number) => {
     set({ isLoading: true, error: null });
     try {
-      const history = await getSessionHistory(days);
+      const history = await getSessionHistory(days, tagId, projectId);
       set({ history, isLoading: false });
     } catch (error) {
-      set({ error: String(error), isLoading: false });
+      set({ error: errorMessage(error), isLoading: false });
+    }
+  },
+
+  fetchTagStats: async (days?: number, projectId?: number) => {
+    try {
+      const tagStats = await getPomodoroTagStats(days, projectId);
+      set({ tagStats });
+    } catch (error) {
+      set({ error: errorMessage(error) });
     }
   },
 }));
@@ -0,0 +1,117 @@
+import { create } from 'zustand';
+import type { Project } from '../types/task';
+import {
+  getProjects,
+  createProject,
+  updateProject,
+  setProjectArchived,
+  reorderProjects,
+  deleteProject,
+  moveTaskToProject,
+  errorMessage,
+} from '../utils/tauri';
+import type { ProjectChanges } from '../utils/tauri';
+import { useTaskStore } from './taskStore';
+import { useTrackerStore } from './trackerStore';
+
+interface ProjectStore {
+  // Archived projects included; the UI filters them out where needed
+  projects: Project[];
+  error: string | null;
+  fetchProjects: () => Promise<void>;
+  addProject: (name: string, color?: string) => Promise<Project | null>;
+  editProject: (id: number, changes: ProjectChanges) => Promise<void>;
+  archiveProject: (id: number, archived: boolean) => Promise<void>;
+  moveProject: (id: number, offset: number) => Promise<void>;
+  removeProject: (id: number) => Promise<void>;
+  moveTask: (taskId: number, projectId: number | null) => Promise<void>;
+}
+
+// Project changes can show up in the task list and the tracker counters
+const refresh = async (get: () => ProjectStore) => {
+  await get().fetchProjects();
+  await useTaskStore.getState().fetchTasks();
+  await useTrackerStore.getState().fetchDays();
+};
+
+export const useProjectStore = create<ProjectStore>((set, get) => ({
+  projects: [],
+  error: null,
+
+  fetchProjects: async () => {
+    try {
+      const projects = await getProjects(true);
+      set({ projects, error: null });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  addProject: async (name: string, color?: string) => {
+    try {
+      const project = await createProject(name, color);
+      await get().fetchProjects();
+      return project;
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      return null;
+    }
+  },
+
+  editProject: async (id, changes) => {
+    try {
+      await updateProject(id, changes);
+      await refresh(get);
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  archiveProject: async (id: number, archived: boolean) => {
+    try {
+      await setProjectArchived(id, archived);
+      await refresh(get);
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  moveProject: async (id: number, offset: number) => {
+    const ids = get().projects.map((p) => p.id);
+    const from = ids.indexOf(id);
+    const to = from + offset;
+    if (from < 0 || to < 0 || to >= ids.length) return;
+    ids.splice(to, 0, ...ids.splice(from, 1));
+    try {
+      await reorderProjects(ids);
+      await get().fetchProjects();
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  removeProject: async (id: number) => {
+    try {
+      await deleteProject(id);
+      const taskQuery = useTaskStore.getState().query;
+      if (taskQuery.project_id === id) {
+        useTaskStore.setState({ query: { ...taskQuery, project_id: undefined } });
+      }
+      if (useTrackerStore.getState().projectId === id) {
+        useTrackerStore.setState({ projectId: null });
+      }
+      await refresh(get);
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  moveTask: async (taskId: number, projectId: number | null) => {
+    try {
+      await moveTaskToProject(taskId, projectId);
+      await refresh(get);
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+}));
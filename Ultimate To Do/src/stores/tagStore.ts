@@ -0,0 +1,112 @@
+import { create } from 'zustand';
+import type { Tag } from '../types/task';
+import {
+  getAllTags,
+  createTag,
+  renameTag,
+  setTagColor,
+  mergeTags,
+  deleteTag,
+  addTagToTask,
+  removeTagFromTask,
+  errorMessage,
+} from '../utils/tauri';
+import { useTaskStore } from './taskStore';
+
+interface TagStore {
+  tags: Tag[];
+  error: string | null;
+  fetchTags: () => Promise<void>;
+  addTag: (name: string, color?: string) => Promise<Tag | null>;
+  renameTag: (id: number, name: string) => Promise<void>;
+  recolorTag: (id: number, color: string) => Promise<void>;
+  mergeTags: (sourceId: number, targetId: number) => Promise<void>;
+  removeTag: (id: number) => Promise<void>;
+  tagTask: (taskId: number, tagId: number) => Promise<void>;
+  untagTask: (taskId: number, tagId: number) => Promise<void>;
+}
+
+// Tags show up on every task, so changes here refresh the task list as well
+const refresh = async (get: () => TagStore) => {
+  await get().fetchTags();
+  await useTaskStore.getState().fetchTasks();
+};
+
+export const useTagStore = create<TagStore>((set, get) => ({
+  tags: [],
+  error: null,
+
+  fetchTags: async () => {
+    try {
+      const tags = await getAllTags();
+      set({ tags, error: null });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  addTag: async (name: string, color?: string) => {
+    try {
+      const tag = await createTag(name, color);
+      await get().fetchTags();
+      return tag;
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      return null;
+    }
+  },
+
+  renameTag: async (id: number, name: string) => {
+    try {
+      await renameTag(id, name);
+      await refresh(get);
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  recolorTag: async (id: number, color: string) => {
+    try {
+      await setTagColor(id, color);
+      await refresh(get);
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  mergeTags: async (sourceId: number, targetId: number) => {
+    try {
+      await mergeTags(sourceId, targetId);
+      await refresh(get);
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  removeTag: async (id: number) => {
+    try {
+      await deleteTag(id);
+      await refresh(get);
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  tagTask: async (taskId: number, tagId: number) => {
+    try {
+      await addTagToTask(taskId, tagId);
+      await refresh(get);
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  untagTask: async (taskId: number, tagId: number) => {
+    try {
+      await removeTagFromTask(taskId, tagId);
+      await refresh(get);
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+}));
@@ -1,45 +1,99 @@
 import { create } from 'zustand';
-import type { Task, CreateTaskInput, UpdateTaskInput } from '../types/task';
+import type {
+  Task,
+  CreateTaskInput,
+  UpdateTaskInput,
+  TaskQuery,
+  ReparentAction,
+  ReparentResult,
+  CompletionRules,
+  TaskSearchResult,
+} from '../types/task';
 import {
   getAllTasks,
   createTask,
   updateTask,
   deleteTask,
   toggleTaskCompletion,
+  moveTask,
+  indentTask,
+  outdentTask,
+  promoteTask,
+  getCompletionRules,
+  setCompletionRules,
+  searchTasks,
+  errorMessage,
 } from '../utils/tauri';
+import { useTrackerStore } from './trackerStore';
+import { useTrashStore } from './trashStore';
 
 interface TaskStore {
   tasks: Task[];
+  // Sort and filters applied by fetchTasks
+  query: TaskQuery;
+  completionRules: CompletionRules | null;
+  searchText: string;
+  // Matches for searchText in the project being viewed
+  searchResults: TaskSearchResult[];
   isLoading: boolean;
   error: string | null;
   fetchTasks: () => Promise<void>;
+  setQuery: (query: TaskQuery) => Promise<void>;
   addTask: (input: CreateTaskInput) => Promise<void>;
   editTask: (input: UpdateTaskInput) => Promise<void>;
   removeTask: (id: number) => Promise<void>;
   toggleTask: (id: number) => Promise<void>;
+  moveTask: (id: number, parentId: number | null, afterId: number | null) => Promise<void>;
+  reparentTask: (id: number, action: ReparentAction) => Promise<void>;
+  fetchCompletionRules: () => Promise<void>;
+  updateCompletionRules: (rules: CompletionRules) => Promise<void>;
+  search: (text: string) => Promise<void>;
 }
 
+const reparentCommands: Record<ReparentAction, (id: number) => Promise<ReparentResult>> = {
+  indent: indentTask,
+  outdent: outdentTask,
+  promote: promoteTask,
+};
+
+// Completing or moving a task can close or reopen others, on any tracker day
+const refreshTracker = async (result: { affected_days: string[] }) => {
+  if (result.affected_days.length > 0) {
+    await useTrackerStore.getState().fetchDays();
+  }
+};
+
 export const useTaskStore = create<TaskStore>((set, get) => ({
   tasks: [],
+  query: {},
+  completionRules: null,
+  searchText: '',
+  searchResults: [],
   isLoading: false,
   error: null,
 
   fetchTasks: async () => {
     set({ isLoading: true, error: null });
     try {
-      const tasks = await getAllTasks();
+      const tasks = await getAllTasks(get().query);
       set({ tasks, isLoading: false });
     } catch (error) {
-      set({ error: String(error), isLoading: false });
+      set({ error: errorMessage(error), isLoading: false });
     }
   },
 
+  setQuery: async (query: TaskQuery) => {
+    set({ query });
+    await get().fetchTasks();
+  },
+
   addTask: async (input: CreateTaskInput) => {
     try {
-      await createTask(input);
+      // New tasks go into the project being viewed
+      await createTask({ ...input, project_id: input.project_id ?? get().query.project_id });
       await get().fetchTasks();
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
     }
   },
 
@@ -48,25 +102,84 @@ export const useTaskStore = create<TaskStore>((set, get) => ({
       await updateTask(input);
       await get().fetchTasks();
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
     }
   },
 
   removeTask: async (id: number) => {
     try {
-      await deleteTask(id);
+      const result = await deleteTask(id);
       await get().fetchTasks();
+      await refreshTracker(result);
+      await useTrashStore.getState().fetchTrash();
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
     }
   },
 
   toggleTask: async (id: number) => {
     try {
-      await toggleTaskCompletion(id);
+      const result = await toggleTaskCompletion(id);
+      await get().fetchTasks();
+      await refreshTracker(result);
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  moveTask: async (id: number, parentId: number | null, afterId: number | null) => {
+    try {
+      const result = await moveTask(id, parentId, afterId);
+      await get().fetchTasks();
+      await refreshTracker(result);
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  reparentTask: async (id: number, action: ReparentAction) => {
+    try {
+      const result = await reparentCommands[action](id);
       await get().fetchTasks();
+      await refreshTracker(result);
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  fetchCompletionRules: async () => {
+    try {
+      const completionRules = await getCompletionRules();
+      set({ completionRules });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  updateCompletionRules: async (rules: CompletionRules) => {
+    try {
+      await setCompletionRules(rules);
+      set({ completionRules: rules });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  search: async (text: string) => {
+    set({ searchText: text });
+    if (!text.trim()) {
+      set({ searchResults: [] });
+      return;
+    }
+    try {
+      const { project_id, include_completed } = get().query;
+      const searchResults = await searchTasks(text, project_id ?? null, include_completed ?? true);
+      // Drop answers to a search that has since been typed over
+      if (get().searchText === text) {
+        set({ searchResults });
+      }
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
     }
   },
 }));
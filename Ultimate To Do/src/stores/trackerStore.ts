@@ -1,5 +1,5 @@
 import { create } from 'zustand';
-import type { DayTracker } from '../types/tracker';
+import type { DayTracker, Plan } from '../types/tracker';
 import type { Task } from '../types/task';
 
 import {
@@ -8,25 +8,47 @@ import {
   assignTaskToDay,
   updateDayStatus,
   getTasksForDay,
+  completeTaskForDate,
+  unassignTaskFromDay,
+  moveTaskAssignment,
+  rescheduleIncompleteTasks,
+  uncompleteTaskForDate,
+  previewAutoAssign,
+  applyAutoAssign,
+  errorMessage,
 } from '../utils/tauri';
 
 interface TrackerStore {
   days: DayTracker[];
-  selectedDay: number | null;
+  // Only count and show this project's tasks
+  projectId: number | null;
+  selectedDay: string | null;
   selectedDayTasks: Task[];
+  // Pending auto-assign preview, if any
+  plan: Plan | null;
   isLoading: boolean;
   error: string | null;
   initializeDays: () => Promise<void>;
   fetchDays: () => Promise<void>;
-  assignTask: (taskId: number, dayNumber: number, assignedBy: string) => Promise<void>;
-  selectDay: (dayNumber: number) => Promise<void>;
+  setProject: (projectId: number | null) => Promise<void>;
+  assignTask: (taskId: number, date: string, assignedBy: string) => Promise<void>;
+  selectDay: (date: string) => Promise<void>;
   clearSelection: () => void;
+  toggleTaskForDay: (taskId: number, date: string) => Promise<void>;
+  unassignTask: (taskId: number, date: string) => Promise<void>;
+  moveTask: (taskId: number, fromDate: string, toDate: string) => Promise<void>;
+  rescheduleIncomplete: () => Promise<number>;
+  previewPlan: (capacity?: number) => Promise<void>;
+  applyPlan: (capacity?: number) => Promise<void>;
+  discardPlan: () => void;
 }
 
 export const useTrackerStore = create<TrackerStore>((set, get) => ({
   days: [],
+  projectId: null,
   selectedDay: null,
   selectedDayTasks: [],
+  plan: null,
   isLoading: false,
   error: null,
 
@@ -36,47 +58,147 @@ export const useTrackerStore = create<TrackerStore>((set, get) => ({
       await initialize60Days();
       await get().fetchDays();
     } catch (error) {
-      set({ error: String(error), isLoading: false });
+      set({ error: errorMessage(error), isLoading: false });
     }
   },
 
   fetchDays: async () => {
     set({ isLoading: true, error: null });
     try {
-      const days = await getDayTrackerData();
+      const days = await getDayTrackerData(undefined, get().projectId ?? undefined);
       set({ days, isLoading: false });
     } catch (error) {
-      set({ error: String(error), isLoading: false });
+      set({ error: errorMessage(error), isLoading: false });
     }
   },
 
-  assignTask: async (taskId: number, dayNumber: number, assignedBy: string) => {
+  setProject: async (projectId: number | null) => {
+    set({ projectId });
+    await get().fetchDays();
+    const { selectedDay } = get();
+    if (selectedDay) {
+      await get().selectDay(selectedDay);
+    }
+  },
+
+  assignTask: async (taskId: number, date: string, assignedBy: string) => {
     set({ error: null });
     try {
-      await assignTaskToDay(taskId, dayNumber, assignedBy);
+      await assignTaskToDay(taskId, date, assignedBy);
       await get().fetchDays();
-      if (get().selectedDay === dayNumber) {
-        await get().selectDay(dayNumber);
+      if (get().selectedDay === date) {
+        await get().selectDay(date);
       }
     } catch (error) {
-      const errorMessage = error instanceof Error ? error.message : String(error);
-      console.error('Error assigning task:', errorMessage);
-      set({ error: `Failed to assign task: ${errorMessage}` });
+      const message = errorMessage(error);
+      console.error('Error assigning task:', message);
+      set({ error: `Failed to assign task: ${message}` });
       throw error; // Re-throw so the UI can handle it
     }
   },
 
-  selectDay: async (dayNumber: number) => {
+  selectDay: async (date: string) => {
     set({ isLoading: true, error: null });
     try {
-      const tasks = await getTasksForDay(dayNumber);
-      set({ selectedDay: dayNumber, selectedDayTasks: tasks, isLoading: false });
+      const tasks = await getTasksForDay(date, get().projectId ?? undefined);
+      set({ selectedDay: date, selectedDayTasks: tasks, isLoading: false });
     } catch (error) {
-      set({ error: String(error), isLoading: false });
+      set({ error: errorMessage(error), isLoading: false });
     }
   },
 
   clearSelection: () => {
     set({ selectedDay: null, selectedDayTasks: [] });
   },
+
+  toggleTaskForDay: async (taskId: number, date: string) => {
+    set({ error: null });
+    try {
+      const task = get().selectedDayTasks.find((t) => t.id === taskId);
+      if (task?.is_completed) {
+        await uncompleteTaskForDate(taskId, date);
+      } else {
+        await completeTaskForDate(taskId, date);
+      }
+      // Parents and subtasks can change along with the task, on other days too
+      await get().fetchDays();
+      if (get().selectedDay === date) {
+        await get().selectDay(date);
+      }
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  unassignTask: async (taskId: number, date: string) => {
+    set({ error: null });
+    try {
+      await unassignTaskFromDay(taskId, date);
+      await get().fetchDays();
+      if (get().selectedDay === date) {
+        await get().selectDay(date);
+      }
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  moveTask: async (taskId: number, fromDate: string, toDate: string) => {
+    set({ error: null });
+    try {
+      await moveTaskAssignment(taskId, fromDate, toDate, 'manual');
+      await get().fetchDays();
+      const selectedDay = get().selectedDay;
+      if (selectedDay) {
+        await get().selectDay(selectedDay);
+      }
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  rescheduleIncomplete: async () => {
+    set({ error: null });
+    try {
+      const result = await rescheduleIncompleteTasks('manual');
+      await get().fetchDays();
+      const selectedDay = get().selectedDay;
+      if (selectedDay) {
+        await get().selectDay(selectedDay);
+      }
+      return result.moved_task_ids.length;
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      return 0;
+    }
+  },
+
+  previewPlan: async (capacity?: number) => {
+    set({ error: null });
+    try {
+      const plan = await previewAutoAssign(capacity);
+      set({ plan });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  applyPlan: async (capacity?: number) => {
+    set({ error: null });
+    try {
+      await applyAutoAssign(capacity);
+      set({ plan: null });
+      await get().fetchDays();
+      const selectedDay = get().selectedDay;
+      if (selectedDay) {
+        await get().selectDay(selectedDay);
+      }
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  discardPlan: () => {
+    set({ plan: null });
+  },
 }));
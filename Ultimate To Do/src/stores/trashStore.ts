@@ -0,0 +1,92 @@
+import { create } from 'zustand';
+import type { TrashedTask } from '../types/task';
+import {
+  getTrash,
+  restoreTask,
+  purgeTask,
+  emptyTrash,
+  getTrashRetention,
+  setTrashRetention,
+  errorMessage,
+} from '../utils/tauri';
+import { useTaskStore } from './taskStore';
+import { useTrackerStore } from './trackerStore';
+
+interface TrashStore {
+  trash: TrashedTask[];
+  // Days before trashed tasks are deleted for good; 0 keeps them
+  retentionDays: number | null;
+  error: string | null;
+  fetchTrash: () => Promise<void>;
+  restore: (id: number) => Promise<void>;
+  purge: (id: number) => Promise<void>;
+  empty: () => Promise<void>;
+  fetchRetention: () => Promise<void>;
+  updateRetention: (days: number) => Promise<void>;
+}
+
+export const useTrashStore = create<TrashStore>((set, get) => ({
+  trash: [],
+  retentionDays: null,
+  error: null,
+
+  fetchTrash: async () => {
+    try {
+      const trash = await getTrash();
+      set({ trash, error: null });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  restore: async (id: number) => {
+    try {
+      const result = await restoreTask(id);
+      await get().fetchTrash();
+      await useTaskStore.getState().fetchTasks();
+      if (result.affected_days.length > 0) {
+        await useTrackerStore.getState().fetchDays();
+      }
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  purge: async (id: number) => {
+    try {
+      await purgeTask(id);
+      await get().fetchTrash();
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  empty: async () => {
+    try {
+      await emptyTrash();
+      await get().fetchTrash();
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  fetchRetention: async () => {
+    try {
+      const retentionDays = await getTrashRetention();
+      set({ retentionDays });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  updateRetention: async (days: number) => {
+    try {
+      await setTrashRetention(days);
+      set({ retentionDays: days });
+      // A shorter period can purge some of the trash right away
+      await get().fetchTrash();
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+}));
@@ -0,0 +1,72 @@
+import { create } from 'zustand';
+import { undo, redo, getUndoState, errorMessage } from '../utils/tauri';
+import type { UndoResult } from '../types/task';
+import { useTaskStore } from './taskStore';
+import { useTrackerStore } from './trackerStore';
+import { useTrashStore } from './trashStore';
+import { useProjectStore } from './projectStore';
+import { useTagStore } from './tagStore';
+import { useViewStore } from './viewStore';
+import { usePomodoroStore } from './pomodoroStore';
+
+interface UndoStore {
+  // What undo and redo would do next; null when there is nothing
+  undoLabel: string | null;
+  redoLabel: string | null;
+  error: string | null;
+  fetchState: () => Promise<void>;
+  undo: () => Promise<void>;
+  redo: () => Promise<void>;
+}
+
+// An undone change can be anything, so everything shown is loaded again
+const refreshAll = async () => {
+  await Promise.all([
+    useTaskStore.getState().fetchTasks(),
+    useTaskStore.getState().fetchCompletionRules(),
+    useTrackerStore.getState().fetchDays(),
+    useTrashStore.getState().fetchTrash(),
+    useTrashStore.getState().fetchRetention(),
+    useProjectStore.getState().fetchProjects(),
+    useTagStore.getState().fetchTags(),
+    useViewStore.getState().fetchViews(),
+    usePomodoroStore.getState().fetchStats(),
+  ]);
+  await useViewStore.getState().refresh();
+};
+
+const step = async (
+  set: (state: Partial<UndoStore>) => void,
+  get: () => UndoStore,
+  command: () => Promise<UndoResult | null>
+) => {
+  try {
+    const result = await command();
+    if (result) {
+      await refreshAll();
+    }
+    set({ error: null });
+  } catch (error) {
+    set({ error: errorMessage(error) });
+  }
+  await get().fetchState();
+};
+
+export const useUndoStore = create<UndoStore>((set, get) => ({
+  undoLabel: null,
+  redoLabel: null,
+  error: null,
+
+  fetchState: async () => {
+    try {
+      const state = await getUndoState();
+      set({ undoLabel: state.undo_label ?? null, redoLabel: state.redo_label ?? null });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  undo: () => step(set, get, undo),
+
+  redo: () => step(set, get, redo),
+}));
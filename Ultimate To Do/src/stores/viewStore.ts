@@ -0,0 +1,111 @@
+import { create } from 'zustand';
+import type { SavedView, Task } from '../types/task';
+import {
+  getSavedViews,
+  createSavedView,
+  updateSavedView,
+  deleteSavedView,
+  filterTasks,
+  runSavedView,
+  errorMessage,
+} from '../utils/tauri';
+
+interface ViewStore {
+  views: SavedView[];
+  // The filter being shown; empty shows the normal task list
+  filter: string;
+  // Set while the filter is a saved view, unchanged
+  activeViewId: number | null;
+  // Matches for filter, as a flat list
+  filteredTasks: Task[];
+  error: string | null;
+  fetchViews: () => Promise<void>;
+  applyFilter: (filter: string) => Promise<void>;
+  openView: (id: number) => Promise<void>;
+  // Runs the current filter or view again, e.g. after tasks changed
+  refresh: () => Promise<void>;
+  clearFilter: () => void;
+  saveView: (name: string) => Promise<void>;
+  updateView: (id: number, filter: string) => Promise<void>;
+  removeView: (id: number) => Promise<void>;
+}
+
+export const useViewStore = create<ViewStore>((set, get) => ({
+  views: [],
+  filter: '',
+  activeViewId: null,
+  filteredTasks: [],
+  error: null,
+
+  fetchViews: async () => {
+    try {
+      const views = await getSavedViews();
+      set({ views });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  applyFilter: async (filter: string) => {
+    set({ filter, activeViewId: null });
+    await get().refresh();
+  },
+
+  openView: async (id: number) => {
+    const view = get().views.find((v) => v.id === id);
+    if (!view) return;
+    set({ filter: view.filter, activeViewId: id });
+    await get().refresh();
+  },
+
+  refresh: async () => {
+    const { filter, activeViewId } = get();
+    if (!filter.trim()) {
+      set({ filteredTasks: [], error: null });
+      return;
+    }
+    try {
+      const filteredTasks =
+        activeViewId !== null ? await runSavedView(activeViewId) : await filterTasks(filter);
+      set({ filteredTasks, error: null });
+    } catch (error) {
+      set({ filteredTasks: [], error: errorMessage(error) });
+    }
+  },
+
+  clearFilter: () => {
+    set({ filter: '', activeViewId: null, filteredTasks: [], error: null });
+  },
+
+  saveView: async (name: string) => {
+    try {
+      const view = await createSavedView(name, get().filter);
+      await get().fetchViews();
+      set({ activeViewId: view.id });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  updateView: async (id: number, filter: string) => {
+    try {
+      await updateSavedView(id, { filter });
+      await get().fetchViews();
+      await get().openView(id);
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  removeView: async (id: number) => {
+    try {
+      await deleteSavedView(id);
+      if (get().activeViewId === id) {
+        set({ activeViewId: null });
+      }
+      await get().fetchViews();
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+}));
@@ -4,7 +4,9 @@ export interface PomodoroSession {
   start_time: string;
   end_time?: string;
   duration_minutes: number;
+  break_minutes: number;
   completed: boolean;
+  status: 'running' | 'paused' | 'completed' | 'aborted';
   date: string;
 }
 
@@ -16,3 +18,21 @@ export interface UserStats {
   total_study_minutes: number;
   last_study_date?: string;
 }
+
+export type TimerPhase = 'idle' | 'focus' | 'paused' | 'break';
+
+export interface TimerSnapshot {
+  phase: TimerPhase;
+  session_id?: number;
+  task_id?: number;
+  duration_seconds: number;
+  remaining_seconds: number;
+}
+
+export interface TagPomodoroStats {
+  tag_id: number;
+  name: string;
+  color: string;
+  sessions_completed: number;
+  study_minutes: number;
+}
@@ -8,9 +8,108 @@ export interface Task {
   is_recurring: boolean;
   recurrence_pattern?: string;
   created_at: string;
+  // 0 (none) to 3 (high)
+  priority: number;
+  estimated_minutes?: number;
+  // 1 (easy) to 5 (hard)
+  difficulty?: number;
+  project_id?: number;
+  progress: TaskProgress;
+  tags: Tag[];
   subtasks: Task[];
 }
 
+export interface Tag {
+  id: number;
+  name: string;
+  // #rrggbb
+  color: string;
+  // Only filled in by getAllTags
+  task_count: number;
+}
+
+// Counted over the task's leaves; a task without subtasks is its own leaf
+export interface TaskProgress {
+  completed_leaves: number;
+  total_leaves: number;
+  // 0 to 1, with leaves weighted by their estimated minutes
+  ratio: number;
+}
+
+export interface SubtreeProgress {
+  task_id: number;
+  parent_id?: number;
+  depth: number;
+  progress: TaskProgress;
+}
+
+// A named filter such as "due:<today done:false tag:urgent"
+export interface SavedView {
+  id: number;
+  name: string;
+  filter: string;
+  // Due date when not set
+  sort_by?: TaskSort;
+}
+
+// A run of search result text, highlighted if it matched
+export interface TextPart {
+  text: string;
+  is_match: boolean;
+}
+
+export interface TaskPathEntry {
+  id: number;
+  title: string;
+}
+
+export interface TaskSearchResult {
+  // Without subtasks; matching subtasks are results of their own
+  task: Task;
+  title: TextPart[];
+  // Excerpt around the matches, empty without a description
+  description: TextPart[];
+  // Ancestors from the top-level task down to the parent
+  path: TaskPathEntry[];
+}
+
+// Subtasks are always in their root task's project
+export interface Project {
+  id: number;
+  name: string;
+  // #rrggbb
+  color: string;
+  position: number;
+  is_archived: boolean;
+  // Applied to new tasks that don't set their own
+  default_priority: number;
+  default_estimated_minutes?: number;
+  // Order of the project's tasks unless the query picks one
+  sort_by?: TaskSort;
+  open_task_count: number;
+}
+
+// 'manual' is the order set by dragging tasks around
+export type TaskSort = 'manual' | 'created_at' | 'due_date' | 'priority' | 'difficulty' | 'effort' | 'title';
+
+export interface TaskQuery {
+  sort_by?: TaskSort;
+  descending?: boolean;
+  min_priority?: number;
+  min_difficulty?: number;
+  max_difficulty?: number;
+  max_estimated_minutes?: number;
+  include_completed?: boolean;
+  // Tasks with any of these tags, or all of them with match_all_tags
+  tag_ids?: number[];
+  match_all_tags?: boolean;
+  project_id?: number;
+  // Without project_id, tasks in archived projects are hidden unless this is set
+  include_archived?: boolean;
+}
+
+export const PRIORITY_LABELS = ['None', 'Low', 'Medium', 'High'];
+
 export interface CreateTaskInput {
   title: string;
   description?: string;
@@ -18,6 +117,10 @@ export interface CreateTaskInput {
   is_recurring: boolean;
   recurrence_pattern?: string;
   parent_id?: number;
+  priority?: number;
+  estimated_minutes?: number;
+  difficulty?: number;
+  project_id?: number;
 }
 
 export interface UpdateTaskInput {
@@ -27,4 +130,101 @@ export interface UpdateTaskInput {
   due_date?: string;
   is_recurring?: boolean;
   recurrence_pattern?: string;
+  priority?: number;
+  estimated_minutes?: number;
+  difficulty?: number;
+}
+
+// Deleted tasks go to the trash with their subtasks
+export interface DeleteTaskResult {
+  deleted_task_ids: number[];
+  removed_assignments: number;
+  // Parents closed because only done subtasks were left
+  changed_task_ids: number[];
+  affected_days: string[];
+}
+
+export interface TrashedTask {
+  // Without subtasks
+  task: Task;
+  // UTC, YYYY-MM-DD HH:MM:SS.SSS
+  deleted_at: string;
+  // Subtasks deleted along with it
+  subtask_count: number;
+  // Parent it goes back under; unset if it returns as a top-level task
+  restores_under?: string;
+}
+
+export interface RestoreTaskResult {
+  task_id: number;
+  parent_id?: number;
+  restored_task_ids: number[];
+  restored_assignments: number;
+  changed_task_ids: number[];
+  affected_days: string[];
+}
+
+export interface UndoResult {
+  // Names the change, e.g. "Delete task"
+  label: string;
+  // Tracker days whose counters changed
+  affected_days: string[];
+}
+
+// Labels of what undo and redo would do next; unset when there is nothing
+export interface UndoState {
+  undo_label?: string;
+  redo_label?: string;
+}
+
+// A line of the activity log; see src-tauri/src/activity.rs for the event types
+export interface ActivityEntry {
+  id: number;
+  // UTC, YYYY-MM-DD HH:MM:SS.SSS
+  occurred_at: string;
+  // The command it came from, e.g. "Edit task" or "Undo: Edit task"
+  command: string;
+  event_type: string;
+  // Unset for projects, tags, views and settings
+  task_id?: number;
+  // The task's title at the time, or the name of what changed
+  subject: string;
+  old_value?: string;
+  new_value?: string;
+}
+
+export interface CompletionResult {
+  task_id: number;
+  // A recurring task that moved on to its next occurrence stays open
+  is_completed: boolean;
+  // Every task whose completion changed, starting with task_id
+  changed_task_ids: number[];
+  affected_days: string[];
+}
+
+// How completing or reopening a task spreads through its tree
+export interface CompletionRules {
+  complete_subtasks: boolean;
+  reopen_subtasks: boolean;
+  complete_parent: boolean;
+  reopen_parent: boolean;
+}
+
+export type ReparentAction = 'indent' | 'outdent' | 'promote';
+
+export interface ReparentResult {
+  task_id: number;
+  parent_id?: number;
+  // Parents closed or reopened because their subtasks changed
+  changed_task_ids: number[];
+  affected_days: string[];
+}
+
+export interface TaskCompletion {
+  id: number;
+  task_id: number;
+  date: string;
+  completed_at: string;
+  // 'subtasks': closed because all of its subtasks were done
+  source: 'task_list' | 'tracker' | 'parent' | 'subtasks' | 'legacy';
 }
@@ -1,5 +1,6 @@
 export interface DayTracker {
   id: number;
+  // Position in the requested window, starting at 1; days are keyed by `date`
   day_number: number;
   date: string;
   completion_status: 'white' | 'red' | 'yellow' | 'light_green' | 'deep_green';
@@ -10,6 +11,31 @@ export interface DayTracker {
 export interface TaskAssignment {
   id: number;
   task_id: number;
-  day_number: number;
+  date: string;
   assigned_by: 'manual' | 'ai';
+  moved_from?: string;
+  moved_by?: 'manual' | 'ai';
+  moved_at?: string;
+}
+
+export interface RescheduleResult {
+  moved_task_ids: number[];
+  affected_days: string[];
+}
+
+export type PlanReason = 'recurring' | 'overdue' | 'due_date' | 'balanced';
+
+export interface PlannedAssignment {
+  task_id: number;
+  date: string;
+  reason: PlanReason;
+}
+
+export interface Plan {
+  start_date: string;
+  end_date: string;
+  capacity: number;
+  assignments: PlannedAssignment[];
+  // Tasks that did not fit before their due date
+  unscheduled: number[];
 }
@@ -1,7 +1,61 @@
 import { invoke } from '@tauri-apps/api/core';
-import type { Task, CreateTaskInput, UpdateTaskInput } from '../types/task';
-import type { DayTracker } from '../types/tracker';
-import type { PomodoroSession, UserStats } from '../types/pomodoro';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import type {
+  Task,
+  CreateTaskInput,
+  UpdateTaskInput,
+  DeleteTaskResult,
+  TrashedTask,
+  RestoreTaskResult,
+  UndoResult,
+  UndoState,
+  ActivityEntry,
+  TaskCompletion,
+  TaskQuery,
+  Tag,
+  Project,
+  TaskSort,
+  ReparentResult,
+  CompletionResult,
+  CompletionRules,
+  SubtreeProgress,
+  TaskSearchResult,
+  SavedView,
+} from '../types/task';
+import type { DayTracker, Plan, RescheduleResult } from '../types/tracker';
+import type {
+  PomodoroSession,
+  UserStats,
+  TimerSnapshot,
+  TagPomodoroStats,
+} from '../types/pomodoro';
+
+// Errors
+// Every command rejects with an AppError; the codes match `AppError::code` in src-tauri/src/error.rs
+export type AppErrorCode =
+  | 'NOT_FOUND'
+  | 'VALIDATION'
+  | 'CONFLICT'
+  | 'DATABASE'
+  | 'IO'
+  | 'POISONED';
+
+export interface AppError {
+  code: AppErrorCode;
+  message: string;
+}
+
+export const isAppError = (error: unknown): error is AppError =>
+  typeof error === 'object' &&
+  error !== null &&
+  'code' in error &&
+  'message' in error;
+
+export const errorMessage = (error: unknown): string => {
+  if (isAppError(error)) return error.message;
+  if (error instanceof Error) return error.message;
+  return String(error);
+};
 
 // Task Commands
 export const createTask = async (input: CreateTaskInput): Promise<number> => {
@@ -12,11 +66,15 @@ export const createTask = async (input: CreateTaskInput): Promise<number> => {
     isRecurring: input.is_recurring,
     recurrencePattern: input.recurrence_pattern,
     parentId: input.parent_id,
+    priority: input.priority,
+    estimatedMinutes: input.estimated_minutes,
+    difficulty: input.difficulty,
+    projectId: input.project_id ?? null,
   });
 };
 
-export const getAllTasks = async (): Promise<Task[]> => {
-  return await invoke('get_all_tasks');
+export const getAllTasks = async (query?: TaskQuery): Promise<Task[]> => {
+  return await invoke('get_all_tasks', { query: query ?? null });
 };
 
 export const updateTask = async (input: UpdateTaskInput): Promise<void> => {
@@ -27,57 +85,278 @@ export const updateTask = async (input: UpdateTaskInput): Promise<void> => {
     dueDate: input.due_date,
     isRecurring: input.is_recurring,
     recurrencePattern: input.recurrence_pattern,
+    priority: input.priority,
+    estimatedMinutes: input.estimated_minutes,
+    difficulty: input.difficulty,
   });
 };
 
-export const deleteTask = async (id: number): Promise<void> => {
+// Moves the task and its subtasks to the trash
+export const deleteTask = async (id: number): Promise<DeleteTaskResult> => {
   return await invoke('delete_task', { id });
 };
 
-export const toggleTaskCompletion = async (id: number): Promise<boolean> => {
+// Most recently deleted first
+export const getTrash = async (): Promise<TrashedTask[]> => {
+  return await invoke('get_trash');
+};
+
+export const restoreTask = async (id: number): Promise<RestoreTaskResult> => {
+  return await invoke('restore_task', { id });
+};
+
+// Deletes a task in the trash for good
+export const purgeTask = async (id: number): Promise<void> => {
+  return await invoke('purge_task', { id });
+};
+
+// Returns the number of tasks deleted for good
+export const emptyTrash = async (): Promise<number> => {
+  return await invoke('empty_trash');
+};
+
+// Days before trashed tasks are deleted for good; 0 keeps them
+export const getTrashRetention = async (): Promise<number> => {
+  return await invoke('get_trash_retention');
+};
+
+export const setTrashRetention = async (days: number): Promise<void> => {
+  return await invoke('set_trash_retention', { days });
+};
+
+// Places the task right after `afterId` among the children of `parentId`
+// (top level when null), or first without `afterId`
+export const moveTask = async (
+  id: number,
+  parentId: number | null,
+  afterId: number | null
+): Promise<ReparentResult> => {
+  return await invoke('move_task', { id, parentId, afterId });
+};
+
+// Makes the task the last subtask of the task above it
+export const indentTask = async (id: number): Promise<ReparentResult> => {
+  return await invoke('indent_task', { id });
+};
+
+// Moves a subtask up one level, right after its parent
+export const outdentTask = async (id: number): Promise<ReparentResult> => {
+  return await invoke('outdent_task', { id });
+};
+
+// Makes a subtask a top-level task
+export const promoteTask = async (id: number): Promise<ReparentResult> => {
+  return await invoke('promote_task', { id });
+};
+
+// The task and everything below it, parents first
+export const getSubtreeProgress = async (taskId: number): Promise<SubtreeProgress[]> => {
+  return await invoke('get_subtree_progress', { taskId });
+};
+
+// Words match as prefixes, "quoted phrases" as written; best match first
+export const searchTasks = async (
+  query: string,
+  projectId: number | null = null,
+  includeCompleted = true
+): Promise<TaskSearchResult[]> => {
+  return await invoke('search_tasks', { query, projectId, includeCompleted });
+};
+
+// Filters and saved views; see src-tauri/src/filters.rs for the language.
+// Matching tasks at any level, as a flat list without subtasks
+export const filterTasks = async (filter: string, sortBy: TaskSort | null = null): Promise<Task[]> => {
+  return await invoke('filter_tasks', { filter, sortBy });
+};
+
+export const getSavedViews = async (): Promise<SavedView[]> => {
+  return await invoke('get_saved_views');
+};
+
+export const createSavedView = async (
+  name: string,
+  filter: string,
+  sortBy: TaskSort | null = null
+): Promise<SavedView> => {
+  return await invoke('create_saved_view', { name, filter, sortBy });
+};
+
+// Fields left undefined are not changed
+export const updateSavedView = async (
+  id: number,
+  // sortBy: null goes back to the default order
+  changes: { name?: string; filter?: string; sortBy?: TaskSort | null }
+): Promise<SavedView> => {
+  return await invoke('update_saved_view', {
+    id,
+    name: changes.name ?? null,
+    filter: changes.filter ?? null,
+    sortBy: changes.sortBy ?? null,
+    clearSortBy: changes.sortBy === null,
+  });
+};
+
+export const deleteSavedView = async (id: number): Promise<void> => {
+  return await invoke('delete_saved_view', { id });
+};
+
+export const runSavedView = async (id: number): Promise<Task[]> => {
+  return await invoke('run_saved_view', { id });
+};
+
+export const toggleTaskCompletion = async (id: number): Promise<CompletionResult> => {
   return await invoke('toggle_task_completion', { id });
 };
 
+// Completion for a single tracker day
+export const completeTaskForDate = async (
+  taskId: number,
+  date: string
+): Promise<CompletionResult> => {
+  return await invoke('complete_task_for_date', { taskId, date });
+};
+
+export const uncompleteTaskForDate = async (
+  taskId: number,
+  date: string
+): Promise<CompletionResult> => {
+  return await invoke('uncomplete_task_for_date', { taskId, date });
+};
+
+export const getCompletionRules = async (): Promise<CompletionRules> => {
+  return await invoke('get_completion_rules');
+};
+
+export const setCompletionRules = async (rules: CompletionRules): Promise<void> => {
+  return await invoke('set_completion_rules', { rules });
+};
+
+export const getTaskCompletions = async (taskId: number): Promise<TaskCompletion[]> => {
+  return await invoke('get_task_completions', { taskId });
+};
+
 // Day Tracker Commands
-export const initialize60Days = async (): Promise<void> => {
-  return await invoke('initialize_60_days');
+// Dates are YYYY-MM-DD. Without a start date the window begins at the
+// configured anchor, or today.
+export const initialize60Days = async (startDate?: string): Promise<void> => {
+  return await invoke('initialize_60_days', { startDate: startDate ?? null });
 };
 
-export const getDayTrackerData = async (): Promise<DayTracker[]> => {
-  return await invoke('get_day_tracker_data');
+// With a project, the day counters only cover that project's tasks
+export const getDayTrackerData = async (
+  startDate?: string,
+  projectId?: number
+): Promise<DayTracker[]> => {
+  return await invoke('get_day_tracker_data', {
+    startDate: startDate ?? null,
+    projectId: projectId ?? null,
+  });
+};
+
+export const getTrackerHistory = async (
+  startDate: string,
+  endDate: string,
+  projectId?: number
+): Promise<DayTracker[]> => {
+  return await invoke('get_tracker_history', { startDate, endDate, projectId: projectId ?? null });
+};
+
+export const setTrackerAnchor = async (startDate: string | null): Promise<void> => {
+  return await invoke('set_tracker_anchor', { startDate });
+};
+
+export const getTrackerAnchor = async (): Promise<string | null> => {
+  return await invoke('get_tracker_anchor');
 };
 
 export const assignTaskToDay = async (
   taskId: number,
-  dayNumber: number,
+  date: string,
   assignedBy: string
 ): Promise<void> => {
   return await invoke('assign_task_to_day', {
     taskId,
-    dayNumber,
+    date,
     assignedBy,
   });
 };
 
-export const updateDayStatus = async (dayNumber: number): Promise<void> => {
-  return await invoke('update_day_status', { dayNumber });
+export const unassignTaskFromDay = async (taskId: number, date: string): Promise<void> => {
+  return await invoke('unassign_task_from_day', { taskId, date });
 };
 
-export const getTasksForDay = async (dayNumber: number): Promise<Task[]> => {
-  return await invoke('get_tasks_for_day', { dayNumber });
+export const moveTaskAssignment = async (
+  taskId: number,
+  fromDate: string,
+  toDate: string,
+  movedBy: string
+): Promise<void> => {
+  return await invoke('move_task_assignment', { taskId, fromDate, toDate, movedBy });
+};
+
+// Moves tasks left unfinished on earlier days onto `toDate` (today by default)
+export const rescheduleIncompleteTasks = async (
+  movedBy: string,
+  toDate?: string
+): Promise<RescheduleResult> => {
+  return await invoke('reschedule_incomplete_tasks', { toDate: toDate ?? null, movedBy });
+};
+
+export const updateDayStatus = async (date: string): Promise<void> => {
+  return await invoke('update_day_status', { date });
+};
+
+export const getTasksForDay = async (date: string, projectId?: number): Promise<Task[]> => {
+  return await invoke('get_tasks_for_day', { date, projectId: projectId ?? null });
+};
+
+// Planner Commands
+export const previewAutoAssign = async (capacity?: number): Promise<Plan> => {
+  return await invoke('preview_auto_assign', { startDate: null, capacity: capacity ?? null });
+};
+
+export const applyAutoAssign = async (capacity?: number): Promise<Plan> => {
+  return await invoke('apply_auto_assign', { startDate: null, capacity: capacity ?? null });
+};
+
+export const getPlannerCapacity = async (): Promise<number> => {
+  return await invoke('get_planner_capacity');
+};
+
+export const setPlannerCapacity = async (capacity: number): Promise<void> => {
+  return await invoke('set_planner_capacity', { capacity });
 };
 
 // Pomodoro Commands
 export const startPomodoroSession = async (
   taskId: number | null,
-  durationMinutes: number
-): Promise<number> => {
+  durationMinutes: number,
+  breakMinutes?: number
+): Promise<TimerSnapshot> => {
   return await invoke('start_pomodoro_session', {
     taskId,
     durationMinutes,
+    breakMinutes,
   });
 };
 
+export const pausePomodoroSession = async (): Promise<TimerSnapshot> => {
+  return await invoke('pause_pomodoro_session');
+};
+
+export const resumePomodoroSession = async (): Promise<TimerSnapshot> => {
+  return await invoke('resume_pomodoro_session');
+};
+
+// Stops the focus session without counting it, or skips the break
+export const abortPomodoroSession = async (): Promise<TimerSnapshot> => {
+  return await invoke('abort_pomodoro_session');
+};
+
+export const getPomodoroTimer = async (): Promise<TimerSnapshot> => {
+  return await invoke('get_pomodoro_timer');
+};
+
 export const completePomodoroSession = async (sessionId: number): Promise<void> => {
   return await invoke('complete_pomodoro_session', { sessionId });
 };
@@ -86,6 +365,169 @@ export const getPomodoroStats = async (): Promise<UserStats> => {
   return await invoke('get_pomodoro_stats');
 };
 
-export const getSessionHistory = async (days?: number): Promise<PomodoroSession[]> => {
-  return await invoke('get_session_history', { days });
+export const getSessionHistory = async (
+  days?: number,
+  tagId?: number,
+  projectId?: number
+): Promise<PomodoroSession[]> => {
+  return await invoke('get_session_history', {
+    days,
+    tagId: tagId ?? null,
+    projectId: projectId ?? null,
+  });
+};
+
+export const getPomodoroTagStats = async (
+  days?: number,
+  projectId?: number
+): Promise<TagPomodoroStats[]> => {
+  return await invoke('get_pomodoro_tag_stats', { days, projectId: projectId ?? null });
+};
+
+// Project Commands
+export const getProjects = async (includeArchived?: boolean): Promise<Project[]> => {
+  return await invoke('get_projects', { includeArchived: includeArchived ?? null });
+};
+
+export const createProject = async (name: string, color?: string): Promise<Project> => {
+  return await invoke('create_project', { name, color: color ?? null });
+};
+
+// Fields left undefined keep their current value
+export interface ProjectChanges {
+  name?: string;
+  // null puts the color back to the default and clears the others
+  color?: string | null;
+  defaultPriority?: number;
+  defaultEstimatedMinutes?: number | null;
+  sortBy?: TaskSort | null;
+}
+
+export const updateProject = async (id: number, changes: ProjectChanges): Promise<Project> => {
+  const clear = [
+    changes.color === null && 'color',
+    changes.defaultEstimatedMinutes === null && 'default_estimated_minutes',
+    changes.sortBy === null && 'sort_by',
+  ].filter(Boolean);
+  return await invoke('update_project', {
+    id,
+    name: changes.name ?? null,
+    color: changes.color ?? null,
+    defaultPriority: changes.defaultPriority ?? null,
+    defaultEstimatedMinutes: changes.defaultEstimatedMinutes ?? null,
+    sortBy: changes.sortBy ?? null,
+    clear,
+  });
+};
+
+export const setProjectArchived = async (id: number, archived: boolean): Promise<void> => {
+  return await invoke('set_project_archived', { id, archived });
+};
+
+// `ids` must list every project, archived ones included
+export const reorderProjects = async (ids: number[]): Promise<void> => {
+  return await invoke('reorder_projects', { ids });
+};
+
+// The project's tasks are kept, without a project
+export const deleteProject = async (id: number): Promise<void> => {
+  return await invoke('delete_project', { id });
+};
+
+export const moveTaskToProject = async (taskId: number, projectId: number | null): Promise<void> => {
+  return await invoke('move_task_to_project', { taskId, projectId });
+};
+
+// Tag Commands
+export const getAllTags = async (): Promise<Tag[]> => {
+  return await invoke('get_all_tags');
+};
+
+export const createTag = async (name: string, color?: string): Promise<Tag> => {
+  return await invoke('create_tag', { name, color: color ?? null });
+};
+
+export const renameTag = async (id: number, name: string): Promise<void> => {
+  return await invoke('rename_tag', { id, name });
+};
+
+export const setTagColor = async (id: number, color: string): Promise<void> => {
+  return await invoke('set_tag_color', { id, color });
+};
+
+// Retags everything tagged `sourceId` with `targetId` and deletes the source
+export const mergeTags = async (sourceId: number, targetId: number): Promise<void> => {
+  return await invoke('merge_tags', { sourceId, targetId });
+};
+
+export const deleteTag = async (id: number): Promise<void> => {
+  return await invoke('delete_tag', { id });
+};
+
+export const addTagToTask = async (taskId: number, tagId: number): Promise<void> => {
+  return await invoke('add_tag_to_task', { taskId, tagId });
+};
+
+export const removeTagFromTask = async (taskId: number, tagId: number): Promise<void> => {
+  return await invoke('remove_tag_from_task', { taskId, tagId });
+};
+
+// Undo Commands
+// Every change to tasks, projects, tags, views and settings can be undone,
+// latest first, also after a restart. Both resolve to null when there is
+// nothing to undo or redo.
+export const undo = async (): Promise<UndoResult | null> => {
+  return await invoke('undo');
+};
+
+export const redo = async (): Promise<UndoResult | null> => {
+  return await invoke('redo');
+};
+
+export const getUndoState = async (): Promise<UndoState> => {
+  return await invoke('get_undo_state');
+};
+
+// Activity Commands
+// Newest first; tasks keep their history after being deleted for good
+export const getTaskActivity = async (taskId: number, limit?: number): Promise<ActivityEntry[]> => {
+  return await invoke('get_task_activity', { taskId, limit });
+};
+
+// Pass the id of the last entry shown as `beforeId` for the next page
+export const getRecentActivity = async (limit?: number, beforeId?: number): Promise<ActivityEntry[]> => {
+  return await invoke('get_recent_activity', { limit, beforeId });
+};
+
+// Pomodoro Events (emitted by the backend timer, see src-tauri/src/pomodoro.rs)
+export const onPomodoroTick = (handler: (timer: TimerSnapshot) => void): Promise<UnlistenFn> =>
+  listen<TimerSnapshot>('pomodoro-tick', (event) => handler(event.payload));
+
+export const onPomodoroPhaseChanged = (
+  handler: (timer: TimerSnapshot) => void
+): Promise<UnlistenFn> =>
+  listen<TimerSnapshot>('pomodoro-phase-changed', (event) => handler(event.payload));
+
+export const onPomodoroSessionCompleted = (
+  handler: (sessionId: number) => void
+): Promise<UnlistenFn> =>
+  listen<number>('pomodoro-session-completed', (event) => handler(event.payload));
+
+// The timer couldn't advance; it tries again on the next tick
+export const onPomodoroError = (handler: (error: AppError) => void): Promise<UnlistenFn> =>
+  listen<AppError>('pomodoro-error', (event) => handler(event.payload));
+
+// Settings Commands
+export interface DatabaseLocation {
+  path: string;
+  source: 'command_line' | 'environment' | 'settings' | 'default';
+}
+
+export const getDatabaseLocation = async (): Promise<DatabaseLocation> => {
+  return await invoke('get_database_location');
+};
+
+// Takes effect on the next launch; pass null to go back to the default location
+export const setDatabasePath = async (path: string | null): Promise<void> => {
+  return await invoke('set_database_path', { path });
 };